@@ -0,0 +1,36 @@
+use cancer::{decode as decode_word, AArch64Parser};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+// Decodes every word of the example binary, code or not, to get a sizeable input.
+fn decode(c: &mut Criterion) {
+    let data = std::fs::read("a64_example").unwrap();
+    let parser = AArch64Parser::new();
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("slice", |b| {
+        b.iter(|| parser.decode_slice(&data, 0).unwrap().count())
+    });
+    group.bench_function("static", |b| {
+        b.iter(|| {
+            data.chunks_exact(4)
+                .filter(|bytes| {
+                    decode_word(u32::from_le_bytes((*bytes).try_into().unwrap())).is_ok()
+                })
+                .count()
+        })
+    });
+    group.bench_function("records", |b| {
+        b.iter(|| parser.decode_records(&data).unwrap())
+    });
+    #[cfg(feature = "rayon")]
+    group.bench_function("par_records", |b| {
+        b.iter(|| parser.par_decode_records(&data).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
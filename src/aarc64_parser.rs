@@ -1,35 +1,79 @@
+use crate::error::Error;
+use crate::features::FeatureSet;
 use crate::instructions::brnch_xcept_gen_sys_instr::{
-    BranchesExceptionGenNSysInstr, ConditionalBranchImmediate, ConditionalBranchImmediateData,
+    BranchesExceptionGenNSysInstr, CompareAndBranchImmediate, CompareAndBranchImmediateData,
+    ConditionalBranchImmediate, ConditionalBranchImmediateData, TestAndBranchImmediate,
+    TestAndBranchImmediateData, UnconditionalBranchImmediate, UnconditionalBranchImmediateData,
+    UnconditionalBranchRegister, UnconditionalBranchRegisterData,
 };
+use crate::instructions::data_processing_immediate::*;
 use crate::instructions::data_processing_register::{
-    AddSubtractShiftedRegister, AddSubtractShiftedRegisterData, DataProcessingRegister,
+    AddSubtractExtendedRegister, AddSubtractExtendedRegisterData, AddSubtractShiftedRegister,
+    AddSubtractShiftedRegisterData, DataProcessingRegister,
 };
 use crate::instructions::loads_and_stores::{
-    LoadStoreRegisterUnsignedImmediate, LoadStoreRegisterUnsignedImmediateData, LoadsAndStores,
+    LoadRegisterLiteral, LoadRegisterLiteralData, LoadStoreRegisterImmediateIndexed,
+    LoadStoreRegisterImmediateIndexedData, LoadStoreRegisterRegisterOffset,
+    LoadStoreRegisterRegisterOffsetData, LoadStoreRegisterUnsignedImmediate,
+    LoadStoreRegisterUnsignedImmediateData, LoadsAndStores,
 };
-use crate::instructions::{self, data_processing_immediate::*};
-use crate::instructions::{brnch_xcept_gen_sys_instr, MainEncodingTable};
-use crate::pattern_matcher::PatternMatcher;
+use crate::instructions::{MainEncodingTable, Reserved, Unpredictable};
+use crate::pattern_matcher::{Field, PatternMatcher};
 use crate::AArch64Inst;
 
 pub struct AArch64Parser {
+    features: FeatureSet,
+
     main_encoding_pm: PatternMatcher<MainEncodingTable>,
+    reserved_pm: PatternMatcher<Reserved>,
 
     data_processing_immediate_pm: PatternMatcher<DataProcessingImmediate>,
+    pc_rel_addressing_pm: PatternMatcher<PCrelAddressing>,
     add_subtract_immediate_pm: PatternMatcher<AddSubtractImmediate>,
+    move_wide_immediate_pm: PatternMatcher<MoveWideImmediate>,
 
     brnch_xcept_gen_sys_instr_pm: PatternMatcher<BranchesExceptionGenNSysInstr>,
     conditional_branch_immediate_pm: PatternMatcher<ConditionalBranchImmediate>,
+    unconditional_branch_register_pm: PatternMatcher<UnconditionalBranchRegister>,
+    unconditional_branch_immediate_pm: PatternMatcher<UnconditionalBranchImmediate>,
+    compare_and_branch_immediate_pm: PatternMatcher<CompareAndBranchImmediate>,
+    test_and_branch_immediate_pm: PatternMatcher<TestAndBranchImmediate>,
 
     loads_and_stores_pm: PatternMatcher<LoadsAndStores>,
     load_store_register_unsigned_immediate_pm: PatternMatcher<LoadStoreRegisterUnsignedImmediate>,
+    load_store_register_immediate_indexed_pm: PatternMatcher<LoadStoreRegisterImmediateIndexed>,
+    load_store_register_register_offset_pm: PatternMatcher<LoadStoreRegisterRegisterOffset>,
+    load_register_literal_pm: PatternMatcher<LoadRegisterLiteral>,
 
     data_processing_register_pm: PatternMatcher<DataProcessingRegister>,
     add_subtract_shifted_register_pm: PatternMatcher<AddSubtractShiftedRegister>,
+    add_subtract_extended_register_pm: PatternMatcher<AddSubtractExtendedRegister>,
+}
+
+// Built entirely at compile time, so `decode` needs neither construction nor allocation.
+pub(crate) static DECODER: AArch64Parser = AArch64Parser::new();
+
+pub fn decode(instr: u32) -> Result<AArch64Inst, Error> {
+    DECODER.parse(instr)
+}
+
+impl Default for AArch64Parser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AArch64Parser {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
+        Self::with_feature_set(FeatureSet::all())
+    }
+
+    pub fn with_features(features: impl Into<FeatureSet>) -> Self {
+        Self::with_feature_set(features.into())
+    }
+
+    // Everything here runs at compile time when used in a `const` or `static`.
+    pub const fn with_feature_set(features: FeatureSet) -> Self {
         let main_encoding_pm = PatternMatcher::<MainEncodingTable>::builder()
             .args("op0", 31..32)
             .args("op1", 25..29)
@@ -65,6 +109,14 @@ impl AArch64Parser {
             .with("x111")
             .build();
 
+        let reserved_pm = PatternMatcher::<Reserved>::builder()
+            .args("op0", 29..32)
+            .args("op1", 16..25)
+            .inst(Reserved::PermanentlyUndefined)
+            .with("000")
+            .with("0_0000_0000")
+            .build();
+
         let data_processing_immediate_pm = PatternMatcher::<DataProcessingImmediate>::builder()
             .args("op0", 23..26)
             .inst(DataProcessingImmediate::PCrelAddressing)
@@ -83,6 +135,17 @@ impl AArch64Parser {
             .with("111")
             .build();
 
+        let pc_rel_addressing_pm = PatternMatcher::<PCrelAddressing>::builder()
+            .args("op", 31..32)
+            .args("immlo", 29..31)
+            .args("immhi", 5..24)
+            .args("Rd", 0..5)
+            .inst(PCrelAddressing::ADR)
+            .with("0")
+            .inst(PCrelAddressing::ADRP)
+            .with("1")
+            .build();
+
         let add_subtract_immediate_pm = PatternMatcher::<AddSubtractImmediate>::builder()
             .args("sf", 31..32)
             .args("op", 30..31)
@@ -125,6 +188,46 @@ impl AArch64Parser {
             .with("1")
             .build();
 
+        let move_wide_immediate_pm = PatternMatcher::<MoveWideImmediate>::builder()
+            .args("sf", 31..32)
+            .args("opc", 29..31)
+            .args("hw", 21..23)
+            .args("imm16", 5..21)
+            .args("Rd", 0..5)
+            .inst(MoveWideImmediate::Unallocated0)
+            .with("x")
+            .with("01")
+            .with("xx")
+            .inst(MoveWideImmediate::Unallocated1)
+            .with("0")
+            .with("xx")
+            .with("1x")
+            .inst(MoveWideImmediate::MOVN32)
+            .with("0")
+            .with("00")
+            .with("0x")
+            .inst(MoveWideImmediate::MOVZ32)
+            .with("0")
+            .with("10")
+            .with("0x")
+            .inst(MoveWideImmediate::MOVK32)
+            .with("0")
+            .with("11")
+            .with("0x")
+            .inst(MoveWideImmediate::MOVN64)
+            .with("1")
+            .with("00")
+            .with("xx")
+            .inst(MoveWideImmediate::MOVZ64)
+            .with("1")
+            .with("10")
+            .with("xx")
+            .inst(MoveWideImmediate::MOVK64)
+            .with("1")
+            .with("11")
+            .with("xx")
+            .build();
+
         let brnch_xcept_gen_sys_instr_pm =
             PatternMatcher::<BranchesExceptionGenNSysInstr>::builder()
                 .args("op0", 29..32)
@@ -150,7 +253,7 @@ impl AArch64Parser {
                 .with("110")
                 .with("01_0000_0011_0011")
                 .with("xxxxx")
-                .inst(BranchesExceptionGenNSysInstr::PSTATE)
+                .inst(BranchesExceptionGenNSysInstr::Pstate)
                 .with("110")
                 .with("01_0000_0xxx_0100")
                 .with("xxxxx")
@@ -200,6 +303,74 @@ impl AArch64Parser {
                 .with("x")
                 .build();
 
+        let unconditional_branch_register_pm =
+            PatternMatcher::<UnconditionalBranchRegister>::builder()
+                .args("opc", 21..25)
+                .args("op2", 16..21)
+                .args("op3", 10..16)
+                .args("Rn", 5..10)
+                .args("op4", 0..5)
+                .inst(UnconditionalBranchRegister::BR)
+                .with("0000")
+                .with("11111")
+                .with("000_000")
+                .with("xxxxx")
+                .with("00000")
+                .inst(UnconditionalBranchRegister::BLR)
+                .with("0001")
+                .with("11111")
+                .with("000_000")
+                .with("xxxxx")
+                .with("00000")
+                .inst(UnconditionalBranchRegister::RET)
+                .with("0010")
+                .with("11111")
+                .with("000_000")
+                .with("xxxxx")
+                .with("00000")
+                .build();
+        let unconditional_branch_immediate_pm =
+            PatternMatcher::<UnconditionalBranchImmediate>::builder()
+                .args("op", 31..32)
+                .args("imm26", 0..26)
+                .inst(UnconditionalBranchImmediate::B)
+                .with("0")
+                .inst(UnconditionalBranchImmediate::BL)
+                .with("1")
+                .build();
+        let compare_and_branch_immediate_pm =
+            PatternMatcher::<CompareAndBranchImmediate>::builder()
+                .args("sf", 31..32)
+                .args("op", 24..25)
+                .args("imm19", 5..24)
+                .args("Rt", 0..5)
+                .inst(CompareAndBranchImmediate::CBZ32)
+                .with("0")
+                .with("0")
+                .inst(CompareAndBranchImmediate::CBNZ32)
+                .with("0")
+                .with("1")
+                .inst(CompareAndBranchImmediate::CBZ64)
+                .with("1")
+                .with("0")
+                .inst(CompareAndBranchImmediate::CBNZ64)
+                .with("1")
+                .with("1")
+                .build();
+        let test_and_branch_immediate_pm = PatternMatcher::<TestAndBranchImmediate>::builder()
+            .args("b5", 31..32)
+            .args("op", 24..25)
+            .args("b40", 19..24)
+            .args("imm14", 5..19)
+            .args("Rt", 0..5)
+            .inst(TestAndBranchImmediate::TBZ)
+            .with("x")
+            .with("0")
+            .inst(TestAndBranchImmediate::TBNZ)
+            .with("x")
+            .with("1")
+            .build();
+
         let loads_and_stores_pm = PatternMatcher::<LoadsAndStores>::builder()
             .args("op0", 28..32)
             .args("op1", 26..27)
@@ -517,169 +688,536 @@ impl AArch64Parser {
                 .with("1")
                 .with("01")
                 .build();
-        let data_processing_register_pm = PatternMatcher::<DataProcessingRegister>::builder()
-            .args("op0", 30..31)
-            .args("op1", 28..29)
-            .args("op2", 21..25)
-            .args("op3", 10..16)
-            .inst(DataProcessingRegister::DataProcessing2Source)
-            .with("0")
-            .with("1")
-            .with("0110")
-            .with("xxx_xxx")
-            .inst(DataProcessingRegister::DataProcessing1Source)
-            .with("1")
-            .with("1")
-            .with("0110")
-            .with("xxx_xxx")
-            .inst(DataProcessingRegister::LogicalShiftedRegister)
-            .with("x")
-            .with("0")
-            .with("0xxx")
-            .with("xxx_xxx")
-            .inst(DataProcessingRegister::AddSubtractShiftedRegister)
-            .with("x")
-            .with("0")
-            .with("1xx0")
-            .with("xxx_xxx")
-            .inst(DataProcessingRegister::AddSubtractExtendedRegister)
-            .with("x")
-            .with("0")
-            .with("1xx1")
-            .with("xxx_xxx")
-            .inst(DataProcessingRegister::AddSubtractWithCarry)
-            .with("x")
-            .with("1")
-            .with("0000")
-            .with("000_000")
-            .inst(DataProcessingRegister::RotateRightIntoFlags)
-            .with("x")
-            .with("1")
-            .with("0000")
-            .with("x00_001")
-            .inst(DataProcessingRegister::EvaluateInfoFlags)
-            .with("x")
-            .with("1")
-            .with("0000")
-            .with("xx0_010")
-            .inst(DataProcessingRegister::ConditionalCompareRegister)
-            .with("x")
-            .with("1")
-            .with("0010")
-            .with("xxx_x0x")
-            .inst(DataProcessingRegister::ConditionalCompareImmediate)
-            .with("x")
-            .with("1")
-            .with("0010")
-            .with("xxx_x1x")
-            .inst(DataProcessingRegister::ConditionalSelect)
-            .with("x")
-            .with("1")
-            .with("0100")
-            .with("xxx_xxx")
-            .inst(DataProcessingRegister::ConditionalSelect)
-            .with("x")
-            .with("1")
-            .with("1xxx")
-            .with("xxx_xxx")
-            .build();
-        let add_subtract_shifted_register_pm =
-            PatternMatcher::<AddSubtractShiftedRegister>::builder()
-                .args("sf", 31..32)
-                .args("op", 30..31)
-                .args("S", 29..30)
-                .args("shift", 22..24)
-                .args("imm6", 10..16)
-                .args("Rm", 16..21)
+        let load_store_register_immediate_indexed_pm =
+            PatternMatcher::<LoadStoreRegisterImmediateIndexed>::builder()
+                .args("size", 30..32)
+                .args("V", 26..27)
+                .args("opc", 22..24)
+                .args("imm9", 12..21)
                 .args("Rn", 5..10)
-                .args("Rd", 0..5)
-                .inst(AddSubtractShiftedRegister::Unallocated0)
-                .with("x")
-                .with("x")
-                .with("x")
-                .with("11")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::Unallocated1)
-                .with("0")
-                .with("x")
-                .with("x")
-                .with("xx")
-                .with("1xx_xxx")
-                .inst(AddSubtractShiftedRegister::ADDShiftedRegister32)
-                .with("0")
+                .args("Rt", 0..5)
+                .inst(LoadStoreRegisterImmediateIndexed::STRBImmediate)
+                .with("00")
                 .with("0")
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRBImmediate)
+                .with("00")
                 .with("0")
-                .with("xx")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::ADDSShiftedRegister64)
+                .with("01")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRSBImmediate64)
+                .with("00")
                 .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRSBImmediate32)
+                .with("00")
                 .with("0")
+                .with("11")
+                .inst(LoadStoreRegisterImmediateIndexed::STRImmediateSIMDFP8)
+                .with("00")
                 .with("1")
-                .with("xx")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::SUBShiftedRegister32)
-                .with("0")
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRImmediateSIMDFP8)
+                .with("00")
+                .with("1")
+                .with("01")
+                .inst(LoadStoreRegisterImmediateIndexed::STRImmediateSIMDFP128)
+                .with("00")
+                .with("1")
+                .with("10")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRImmediateSIMDFP128)
+                .with("00")
                 .with("1")
+                .with("11")
+                .inst(LoadStoreRegisterImmediateIndexed::STRHImmediate)
+                .with("01")
                 .with("0")
-                .with("xx")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::SUBSShiftedRegister32)
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRHImmediate)
+                .with("01")
+                .with("0")
+                .with("01")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRSHImmediate64)
+                .with("01")
+                .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRSHImmediate32)
+                .with("01")
                 .with("0")
+                .with("11")
+                .inst(LoadStoreRegisterImmediateIndexed::STRImmediateSIMDFP16)
+                .with("01")
                 .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRImmediateSIMDFP16)
+                .with("01")
                 .with("1")
-                .with("xx")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::ADDShiftedRegister64)
+                .with("01")
+                .inst(LoadStoreRegisterImmediateIndexed::Unallocated0)
+                .with("01")
                 .with("1")
+                .with("1x")
+                .inst(LoadStoreRegisterImmediateIndexed::Unallocated1)
+                .with("1x")
                 .with("0")
-                .with("0")
-                .with("xx")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::ADDSShiftedRegister64)
+                .with("11")
+                .inst(LoadStoreRegisterImmediateIndexed::Unallocated2)
+                .with("1x")
                 .with("1")
+                .with("1x")
+                .inst(LoadStoreRegisterImmediateIndexed::STRImmediate32)
+                .with("10")
                 .with("0")
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRImmediate32)
+                .with("10")
+                .with("0")
+                .with("01")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRSWImmediate)
+                .with("10")
+                .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterImmediateIndexed::STRImmediateSIMDFP32)
+                .with("10")
                 .with("1")
-                .with("xx")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::SUBShiftedRegister64)
-                .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRImmediateSIMDFP32)
+                .with("10")
                 .with("1")
+                .with("01")
+                .inst(LoadStoreRegisterImmediateIndexed::STRImmediate64)
+                .with("11")
                 .with("0")
-                .with("xx")
-                .with("xxx_xxx")
-                .inst(AddSubtractShiftedRegister::SUBSShiftedRegister64)
-                .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRImmediate64)
+                .with("11")
+                .with("0")
+                .with("01")
+                .inst(LoadStoreRegisterImmediateIndexed::Unallocated3)
+                .with("11")
+                .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterImmediateIndexed::STRImmediateSIMDFP64)
+                .with("11")
                 .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterImmediateIndexed::LDRImmediateSIMDFP64)
+                .with("11")
                 .with("1")
-                .with("xx")
-                .with("xxx_xxx")
+                .with("01")
+                .build();
+        let load_store_register_register_offset_pm =
+            PatternMatcher::<LoadStoreRegisterRegisterOffset>::builder()
+                .args("size", 30..32)
+                .args("V", 26..27)
+                .args("opc", 22..24)
+                .args("Rm", 16..21)
+                .args("option", 13..16)
+                .args("S", 12..13)
+                .args("Rn", 5..10)
+                .args("Rt", 0..5)
+                .inst(LoadStoreRegisterRegisterOffset::Unallocated0)
+                .with("x1")
+                .with("1")
+                .with("1x")
+                .inst(LoadStoreRegisterRegisterOffset::STRBRegister)
+                .with("00")
+                .with("0")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRBRegister)
+                .with("00")
+                .with("0")
+                .with("01")
+                .inst(LoadStoreRegisterRegisterOffset::LDRSBRegister64)
+                .with("00")
+                .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterRegisterOffset::LDRSBRegister32)
+                .with("00")
+                .with("0")
+                .with("11")
+                .inst(LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP8)
+                .with("00")
+                .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP8)
+                .with("00")
+                .with("1")
+                .with("01")
+                .inst(LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP128)
+                .with("00")
+                .with("1")
+                .with("10")
+                .inst(LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP128)
+                .with("00")
+                .with("1")
+                .with("11")
+                .inst(LoadStoreRegisterRegisterOffset::STRHRegister)
+                .with("01")
+                .with("0")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRHRegister)
+                .with("01")
+                .with("0")
+                .with("01")
+                .inst(LoadStoreRegisterRegisterOffset::LDRSHRegister64)
+                .with("01")
+                .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterRegisterOffset::LDRSHRegister32)
+                .with("01")
+                .with("0")
+                .with("11")
+                .inst(LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP16)
+                .with("01")
+                .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP16)
+                .with("01")
+                .with("1")
+                .with("01")
+                .inst(LoadStoreRegisterRegisterOffset::Unallocated1)
+                .with("1x")
+                .with("0")
+                .with("11")
+                .inst(LoadStoreRegisterRegisterOffset::Unallocated2)
+                .with("1x")
+                .with("1")
+                .with("1x")
+                .inst(LoadStoreRegisterRegisterOffset::STRRegister32)
+                .with("10")
+                .with("0")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRRegister32)
+                .with("10")
+                .with("0")
+                .with("01")
+                .inst(LoadStoreRegisterRegisterOffset::LDRSWRegister)
+                .with("10")
+                .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP32)
+                .with("10")
+                .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP32)
+                .with("10")
+                .with("1")
+                .with("01")
+                .inst(LoadStoreRegisterRegisterOffset::STRRegister64)
+                .with("11")
+                .with("0")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRRegister64)
+                .with("11")
+                .with("0")
+                .with("01")
+                .inst(LoadStoreRegisterRegisterOffset::PRFMRegister)
+                .with("11")
+                .with("0")
+                .with("10")
+                .inst(LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP64)
+                .with("11")
+                .with("1")
+                .with("00")
+                .inst(LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP64)
+                .with("11")
+                .with("1")
+                .with("01")
+                .build();
+        let load_register_literal_pm = PatternMatcher::<LoadRegisterLiteral>::builder()
+            .args("opc", 30..32)
+            .args("V", 26..27)
+            .args("imm19", 5..24)
+            .args("Rt", 0..5)
+            .inst(LoadRegisterLiteral::LDRLiteral32)
+            .with("00")
+            .with("0")
+            .inst(LoadRegisterLiteral::LDRLiteralSIMDFP32)
+            .with("00")
+            .with("1")
+            .inst(LoadRegisterLiteral::LDRLiteral64)
+            .with("01")
+            .with("0")
+            .inst(LoadRegisterLiteral::LDRLiteralSIMDFP64)
+            .with("01")
+            .with("1")
+            .inst(LoadRegisterLiteral::LDRSWLiteral)
+            .with("10")
+            .with("0")
+            .inst(LoadRegisterLiteral::LDRLiteralSIMDFP128)
+            .with("10")
+            .with("1")
+            .inst(LoadRegisterLiteral::PRFMLiteral)
+            .with("11")
+            .with("0")
+            .inst(LoadRegisterLiteral::Unallocated0)
+            .with("11")
+            .with("1")
+            .build();
+        let data_processing_register_pm = PatternMatcher::<DataProcessingRegister>::builder()
+            .args("op0", 30..31)
+            .args("op1", 28..29)
+            .args("op2", 21..25)
+            .args("op3", 10..16)
+            .inst(DataProcessingRegister::DataProcessing2Source)
+            .with("0")
+            .with("1")
+            .with("0110")
+            .with("xxx_xxx")
+            .inst(DataProcessingRegister::DataProcessing1Source)
+            .with("1")
+            .with("1")
+            .with("0110")
+            .with("xxx_xxx")
+            .inst(DataProcessingRegister::LogicalShiftedRegister)
+            .with("x")
+            .with("0")
+            .with("0xxx")
+            .with("xxx_xxx")
+            .inst(DataProcessingRegister::AddSubtractShiftedRegister)
+            .with("x")
+            .with("0")
+            .with("1xx0")
+            .with("xxx_xxx")
+            .inst(DataProcessingRegister::AddSubtractExtendedRegister)
+            .with("x")
+            .with("0")
+            .with("1xx1")
+            .with("xxx_xxx")
+            .inst(DataProcessingRegister::AddSubtractWithCarry)
+            .with("x")
+            .with("1")
+            .with("0000")
+            .with("000_000")
+            .inst(DataProcessingRegister::RotateRightIntoFlags)
+            .with("x")
+            .with("1")
+            .with("0000")
+            .with("x00_001")
+            .inst(DataProcessingRegister::EvaluateInfoFlags)
+            .with("x")
+            .with("1")
+            .with("0000")
+            .with("xx0_010")
+            .inst(DataProcessingRegister::ConditionalCompareRegister)
+            .with("x")
+            .with("1")
+            .with("0010")
+            .with("xxx_x0x")
+            .inst(DataProcessingRegister::ConditionalCompareImmediate)
+            .with("x")
+            .with("1")
+            .with("0010")
+            .with("xxx_x1x")
+            .inst(DataProcessingRegister::ConditionalSelect)
+            .with("x")
+            .with("1")
+            .with("0100")
+            .with("xxx_xxx")
+            .inst(DataProcessingRegister::DataProcessing3Source)
+            .with("x")
+            .with("1")
+            .with("1xxx")
+            .with("xxx_xxx")
+            .build();
+        let add_subtract_shifted_register_pm =
+            PatternMatcher::<AddSubtractShiftedRegister>::builder()
+                .args("sf", 31..32)
+                .args("op", 30..31)
+                .args("S", 29..30)
+                .args("shift", 22..24)
+                .args("imm6", 10..16)
+                .args("Rm", 16..21)
+                .args("Rn", 5..10)
+                .args("Rd", 0..5)
+                .inst(AddSubtractShiftedRegister::Unallocated0)
+                .with("x")
+                .with("x")
+                .with("x")
+                .with("11")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::Unallocated1)
+                .with("0")
+                .with("x")
+                .with("x")
+                .with("xx")
+                .with("1xx_xxx")
+                .inst(AddSubtractShiftedRegister::ADDShiftedRegister32)
+                .with("0")
+                .with("0")
+                .with("0")
+                .with("xx")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::ADDSShiftedRegister32)
+                .with("0")
+                .with("0")
+                .with("1")
+                .with("xx")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::SUBShiftedRegister32)
+                .with("0")
+                .with("1")
+                .with("0")
+                .with("xx")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::SUBSShiftedRegister32)
+                .with("0")
+                .with("1")
+                .with("1")
+                .with("xx")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::ADDShiftedRegister64)
+                .with("1")
+                .with("0")
+                .with("0")
+                .with("xx")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::ADDSShiftedRegister64)
+                .with("1")
+                .with("0")
+                .with("1")
+                .with("xx")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::SUBShiftedRegister64)
+                .with("1")
+                .with("1")
+                .with("0")
+                .with("xx")
+                .with("xxx_xxx")
+                .inst(AddSubtractShiftedRegister::SUBSShiftedRegister64)
+                .with("1")
+                .with("1")
+                .with("1")
+                .with("xx")
+                .with("xxx_xxx")
+                .build();
+
+        let add_subtract_extended_register_pm =
+            PatternMatcher::<AddSubtractExtendedRegister>::builder()
+                .args("sf", 31..32)
+                .args("op", 30..31)
+                .args("S", 29..30)
+                .args("opt", 22..24)
+                .args("imm3", 10..13)
+                .args("Rm", 16..21)
+                .args("option", 13..16)
+                .args("Rn", 5..10)
+                .args("Rd", 0..5)
+                .inst(AddSubtractExtendedRegister::Unallocated0)
+                .with("x")
+                .with("x")
+                .with("x")
+                .with("x1")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::Unallocated1)
+                .with("x")
+                .with("x")
+                .with("x")
+                .with("1x")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::Unallocated2)
+                .with("x")
+                .with("x")
+                .with("x")
+                .with("xx")
+                .with("1x1")
+                .inst(AddSubtractExtendedRegister::Unallocated3)
+                .with("x")
+                .with("x")
+                .with("x")
+                .with("xx")
+                .with("11x")
+                .inst(AddSubtractExtendedRegister::ADDExtendedRegister32)
+                .with("0")
+                .with("0")
+                .with("0")
+                .with("00")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::ADDSExtendedRegister32)
+                .with("0")
+                .with("0")
+                .with("1")
+                .with("00")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::SUBExtendedRegister32)
+                .with("0")
+                .with("1")
+                .with("0")
+                .with("00")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::SUBSExtendedRegister32)
+                .with("0")
+                .with("1")
+                .with("1")
+                .with("00")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::ADDExtendedRegister64)
+                .with("1")
+                .with("0")
+                .with("0")
+                .with("00")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::ADDSExtendedRegister64)
+                .with("1")
+                .with("0")
+                .with("1")
+                .with("00")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::SUBExtendedRegister64)
+                .with("1")
+                .with("1")
+                .with("0")
+                .with("00")
+                .with("xxx")
+                .inst(AddSubtractExtendedRegister::SUBSExtendedRegister64)
+                .with("1")
+                .with("1")
+                .with("1")
+                .with("00")
+                .with("xxx")
                 .build();
 
         Self {
+            features,
+
             main_encoding_pm,
+            reserved_pm,
 
             data_processing_immediate_pm,
+            pc_rel_addressing_pm,
             add_subtract_immediate_pm,
+            move_wide_immediate_pm,
 
             brnch_xcept_gen_sys_instr_pm,
             conditional_branch_immediate_pm,
+            unconditional_branch_register_pm,
+            unconditional_branch_immediate_pm,
+            compare_and_branch_immediate_pm,
+            test_and_branch_immediate_pm,
 
             loads_and_stores_pm,
             load_store_register_unsigned_immediate_pm,
+            load_store_register_immediate_indexed_pm,
+            load_store_register_register_offset_pm,
+            load_register_literal_pm,
 
             data_processing_register_pm,
             add_subtract_shifted_register_pm,
+            add_subtract_extended_register_pm,
         }
     }
 
-    pub fn parse(&self, instr: u32) -> AArch64Inst {
+    pub fn parse(&self, instr: u32) -> Result<AArch64Inst, Error> {
         match self.main_encoding_pm.match_pattern(instr) {
+            Some(pat) if !self.features.contains(pat.feature()) => {
+                Ok(AArch64Inst::UNDEFINED(instr))
+            }
             Some(pat) => match pat {
-                MainEncodingTable::Reserved => todo!(),
-                MainEncodingTable::SmeEncodings => todo!(),
-                MainEncodingTable::Unallocated0 => todo!(),
-                MainEncodingTable::SveEncodings => todo!(),
-                MainEncodingTable::Unallocated1 => todo!(),
+                MainEncodingTable::Reserved => self.parse_reserved(instr),
+                MainEncodingTable::SmeEncodings => Err(Error::UnknownInstructionError(instr)),
+                MainEncodingTable::Unallocated0 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                MainEncodingTable::SveEncodings => Err(Error::UnknownInstructionError(instr)),
+                MainEncodingTable::Unallocated1 => Ok(AArch64Inst::UNALLOCATED(instr)),
                 MainEncodingTable::DataProcessingImmediate => {
                     self.parse_data_processing_immediate(instr)
                 }
@@ -690,30 +1228,201 @@ impl AArch64Parser {
                 MainEncodingTable::DataProcessingRegister => {
                     self.parse_data_processing_register(instr)
                 }
-                MainEncodingTable::DataProcessingScalarFloatingPointAndAdvancedSIMD => todo!(),
+                MainEncodingTable::DataProcessingScalarFloatingPointAndAdvancedSIMD => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    // Arguments of every encoding table the word is dispatched through, outermost first.
+    // Feature checks are skipped so that gated encodings can be inspected too.
+    pub fn fields(&self, instr: u32) -> impl Iterator<Item = Field> + '_ {
+        let mut group = None;
+        let mut leaf = None;
+
+        match self.main_encoding_pm.match_pattern(instr) {
+            Some(MainEncodingTable::Reserved) => {
+                group = Some(self.reserved_pm.fields("Reserved", instr));
+            }
+            Some(MainEncodingTable::DataProcessingImmediate) => {
+                let pm = &self.data_processing_immediate_pm;
+                group = Some(pm.fields("Data processing - immediate", instr));
+
+                match pm.match_pattern(instr) {
+                    Some(DataProcessingImmediate::PCrelAddressing) => {
+                        leaf = Some(
+                            self.pc_rel_addressing_pm
+                                .fields("PC-rel. addressing", instr),
+                        )
+                    }
+                    Some(DataProcessingImmediate::AddSubtractImmediate) => {
+                        leaf = Some(
+                            self.add_subtract_immediate_pm
+                                .fields("Add/subtract (immediate)", instr),
+                        )
+                    }
+                    Some(DataProcessingImmediate::MoveWideImmediate) => {
+                        leaf = Some(
+                            self.move_wide_immediate_pm
+                                .fields("Move wide (immediate)", instr),
+                        )
+                    }
+                    _ => {}
+                }
+            }
+            Some(MainEncodingTable::BranchesExceptionGenNSysInstr) => {
+                let pm = &self.brnch_xcept_gen_sys_instr_pm;
+                group = Some(pm.fields("Branches, exception generating and system", instr));
+
+                match pm.match_pattern(instr) {
+                    Some(BranchesExceptionGenNSysInstr::ConditionalBranchImmediate) => {
+                        leaf = Some(
+                            self.conditional_branch_immediate_pm
+                                .fields("Conditional branch (immediate)", instr),
+                        )
+                    }
+                    Some(BranchesExceptionGenNSysInstr::UnconditionalBranchRegister) => {
+                        leaf = Some(
+                            self.unconditional_branch_register_pm
+                                .fields("Unconditional branch (register)", instr),
+                        )
+                    }
+                    Some(BranchesExceptionGenNSysInstr::UnconditionalBranchImmediate) => {
+                        leaf = Some(
+                            self.unconditional_branch_immediate_pm
+                                .fields("Unconditional branch (immediate)", instr),
+                        )
+                    }
+                    Some(BranchesExceptionGenNSysInstr::CompareAndBranchImmediate) => {
+                        leaf = Some(
+                            self.compare_and_branch_immediate_pm
+                                .fields("Compare and branch (immediate)", instr),
+                        )
+                    }
+                    Some(BranchesExceptionGenNSysInstr::TestAndBranchImmediate) => {
+                        leaf = Some(
+                            self.test_and_branch_immediate_pm
+                                .fields("Test and branch (immediate)", instr),
+                        )
+                    }
+                    _ => {}
+                }
+            }
+            Some(MainEncodingTable::LoadsAndStores) => {
+                let pm = &self.loads_and_stores_pm;
+                group = Some(pm.fields("Loads and stores", instr));
+
+                match pm.match_pattern(instr) {
+                    Some(LoadsAndStores::LoadStoreRegisterUnsignedImmediate) => {
+                        leaf = Some(
+                            self.load_store_register_unsigned_immediate_pm
+                                .fields("Load/store register (unsigned immediate)", instr),
+                        )
+                    }
+                    Some(
+                        LoadsAndStores::LoadStoreRegisterImmediatePreIndexed
+                        | LoadsAndStores::LoadStoreRegisterImmediatePostIndexed,
+                    ) => {
+                        leaf = Some(
+                            self.load_store_register_immediate_indexed_pm
+                                .fields("Load/store register (immediate indexed)", instr),
+                        )
+                    }
+                    Some(LoadsAndStores::LoadRegisterLiteral) => {
+                        leaf = Some(
+                            self.load_register_literal_pm
+                                .fields("Load register (literal)", instr),
+                        )
+                    }
+                    Some(LoadsAndStores::LoadStoreRegisterRegisterOffset) => {
+                        leaf = Some(
+                            self.load_store_register_register_offset_pm
+                                .fields("Load/store register (register offset)", instr),
+                        )
+                    }
+                    _ => {}
+                }
+            }
+            Some(MainEncodingTable::DataProcessingRegister) => {
+                let pm = &self.data_processing_register_pm;
+                group = Some(pm.fields("Data processing - register", instr));
+
+                match pm.match_pattern(instr) {
+                    Some(DataProcessingRegister::AddSubtractShiftedRegister) => {
+                        leaf = Some(
+                            self.add_subtract_shifted_register_pm
+                                .fields("Add/subtract (shifted register)", instr),
+                        )
+                    }
+                    Some(DataProcessingRegister::AddSubtractExtendedRegister) => {
+                        leaf = Some(
+                            self.add_subtract_extended_register_pm
+                                .fields("Add/subtract (extended register)", instr),
+                        )
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+
+        self.main_encoding_pm
+            .fields("Main encoding", instr)
+            .chain(group.into_iter().flatten())
+            .chain(leaf.into_iter().flatten())
+    }
+
+    fn parse_reserved(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        match self.reserved_pm.match_pattern(instr) {
+            Some(Reserved::PermanentlyUndefined) => Ok(AArch64Inst::UNDEFINED(instr)),
+            None => Ok(AArch64Inst::RESERVED(instr)),
         }
     }
     //==============================Data processing Immediate==============================
-    fn parse_data_processing_immediate(&self, instr: u32) -> AArch64Inst {
+    fn parse_data_processing_immediate(&self, instr: u32) -> Result<AArch64Inst, Error> {
         match self.data_processing_immediate_pm.match_pattern(instr) {
+            Some(pat) if !self.features.contains(pat.feature()) => {
+                Ok(AArch64Inst::UNDEFINED(instr))
+            }
             Some(pat) => match pat {
-                DataProcessingImmediate::PCrelAddressing => todo!(),
+                DataProcessingImmediate::PCrelAddressing => self.parse_pc_rel_addressing(instr),
                 DataProcessingImmediate::AddSubtractImmediate => {
                     self.parse_add_subtract_immediate(instr)
                 }
-                DataProcessingImmediate::AddSubtractImmediateWithTags => todo!(),
-                DataProcessingImmediate::LogicalImmediate => todo!(),
-                DataProcessingImmediate::MoveWideImmediate => todo!(),
-                DataProcessingImmediate::Bitfield => todo!(),
-                DataProcessingImmediate::Extract => todo!(),
+                DataProcessingImmediate::AddSubtractImmediateWithTags => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingImmediate::LogicalImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingImmediate::MoveWideImmediate => self.parse_move_wide_immediate(instr),
+                DataProcessingImmediate::Bitfield => Err(Error::UnknownInstructionError(instr)),
+                DataProcessingImmediate::Extract => Err(Error::UnknownInstructionError(instr)),
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_pc_rel_addressing(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = PCrelAddressingData {
+            op: self.pc_rel_addressing_pm.get_arg(instr, 0),
+            immlo: self.pc_rel_addressing_pm.get_arg(instr, 1),
+            immhi: self.pc_rel_addressing_pm.get_arg(instr, 2),
+            rd: self.pc_rel_addressing_pm.get_arg(instr, 3),
+        };
+
+        match self.pc_rel_addressing_pm.match_pattern(instr) {
+            Some(pat) => match pat {
+                PCrelAddressing::ADR => Ok(AArch64Inst::ADR(data)),
+                PCrelAddressing::ADRP => Ok(AArch64Inst::ADRP(data)),
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
         }
     }
 
-    fn parse_add_subtract_immediate(&self, instr: u32) -> AArch64Inst {
+    fn parse_add_subtract_immediate(&self, instr: u32) -> Result<AArch64Inst, Error> {
         let data = AddSubtractImmediateData {
             sf: self.add_subtract_immediate_pm.get_arg(instr, 0),
             op: self.add_subtract_immediate_pm.get_arg(instr, 1),
@@ -726,41 +1435,86 @@ impl AArch64Parser {
 
         match self.add_subtract_immediate_pm.match_pattern(instr) {
             Some(pat) => match pat {
-                AddSubtractImmediate::ADDImmediate32 => todo!(),
-                AddSubtractImmediate::ADDSImmediate32 => todo!(),
-                AddSubtractImmediate::SUBImmediate32 => todo!(),
-                AddSubtractImmediate::SUBSImmediate32 => todo!(),
-                AddSubtractImmediate::ADDImmediate64 => todo!(),
-                AddSubtractImmediate::ADDSImmediate64 => todo!(),
-                AddSubtractImmediate::SUBImmediate64 => AArch64Inst::SubImmediate64(data),
-                AddSubtractImmediate::SUBSImmediate64 => todo!(),
+                AddSubtractImmediate::ADDImmediate32 => Err(Error::UnknownInstructionError(instr)),
+                AddSubtractImmediate::ADDSImmediate32 => Err(Error::UnknownInstructionError(instr)),
+                AddSubtractImmediate::SUBImmediate32 => Err(Error::UnknownInstructionError(instr)),
+                AddSubtractImmediate::SUBSImmediate32 => Ok(AArch64Inst::SUBSImmediate32(data)),
+                AddSubtractImmediate::ADDImmediate64 => Ok(AArch64Inst::AddImmediate64(data)),
+                AddSubtractImmediate::ADDSImmediate64 => Err(Error::UnknownInstructionError(instr)),
+                AddSubtractImmediate::SUBImmediate64 => Ok(AArch64Inst::SubImmediate64(data)),
+                AddSubtractImmediate::SUBSImmediate64 => Ok(AArch64Inst::SUBSImmediate64(data)),
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+    fn parse_move_wide_immediate(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = MoveWideImmediateData {
+            sf: self.move_wide_immediate_pm.get_arg(instr, 0),
+            opc: self.move_wide_immediate_pm.get_arg(instr, 1),
+            hw: self.move_wide_immediate_pm.get_arg(instr, 2),
+            imm16: self.move_wide_immediate_pm.get_arg(instr, 3),
+            rd: self.move_wide_immediate_pm.get_arg(instr, 4),
+        };
+
+        match self.move_wide_immediate_pm.match_pattern(instr) {
+            Some(pat) => match pat {
+                MoveWideImmediate::Unallocated0 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                MoveWideImmediate::Unallocated1 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                MoveWideImmediate::MOVN32 => Ok(AArch64Inst::MOVN32(data)),
+                MoveWideImmediate::MOVZ32 => Ok(AArch64Inst::MOVZ32(data)),
+                MoveWideImmediate::MOVK32 => Ok(AArch64Inst::MOVK32(data)),
+                MoveWideImmediate::MOVN64 => Ok(AArch64Inst::MOVN64(data)),
+                MoveWideImmediate::MOVZ64 => Ok(AArch64Inst::MOVZ64(data)),
+                MoveWideImmediate::MOVK64 => Ok(AArch64Inst::MOVK64(data)),
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
         }
     }
     //==============================Branches, Exception Generating and System instructions==============================
-    fn parse_brnch_xcept_gen_sys_instr(&self, instr: u32) -> AArch64Inst {
+    fn parse_brnch_xcept_gen_sys_instr(&self, instr: u32) -> Result<AArch64Inst, Error> {
         match self.brnch_xcept_gen_sys_instr_pm.match_pattern(instr) {
             Some(pat) => match pat {
-                BranchesExceptionGenNSysInstr::ConditionalBranchImmediate => self.parse_conditional_branch_immediate(instr),
-                BranchesExceptionGenNSysInstr::ExceptionGeneration => todo!(),
-                BranchesExceptionGenNSysInstr::SystemInstructionsWithRegisterArgument => todo!(),
-                BranchesExceptionGenNSysInstr::Hints => todo!(),
-                BranchesExceptionGenNSysInstr::Barriers => todo!(),
-                BranchesExceptionGenNSysInstr::PSTATE => todo!(),
-                BranchesExceptionGenNSysInstr::SystemWithResult => todo!(),
-                BranchesExceptionGenNSysInstr::SystemInstructions => todo!(),
-                BranchesExceptionGenNSysInstr::SystemRegisterMove => todo!(),
-                BranchesExceptionGenNSysInstr::UnconditionalBranchRegister => todo!(),
-                BranchesExceptionGenNSysInstr::UnconditionalBranchImmediate => todo!(),
-                BranchesExceptionGenNSysInstr::CompareAndBranchImmediate => todo!(),
-                BranchesExceptionGenNSysInstr::TestAndBranchImmediate => todo!(),
+                BranchesExceptionGenNSysInstr::ConditionalBranchImmediate => {
+                    self.parse_conditional_branch_immediate(instr)
+                }
+                BranchesExceptionGenNSysInstr::ExceptionGeneration => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                BranchesExceptionGenNSysInstr::SystemInstructionsWithRegisterArgument => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                BranchesExceptionGenNSysInstr::Hints => Err(Error::UnknownInstructionError(instr)),
+                BranchesExceptionGenNSysInstr::Barriers => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                BranchesExceptionGenNSysInstr::Pstate => Err(Error::UnknownInstructionError(instr)),
+                BranchesExceptionGenNSysInstr::SystemWithResult => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                BranchesExceptionGenNSysInstr::SystemInstructions => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                BranchesExceptionGenNSysInstr::SystemRegisterMove => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                BranchesExceptionGenNSysInstr::UnconditionalBranchRegister => {
+                    self.parse_unconditional_branch_register(instr)
+                }
+                BranchesExceptionGenNSysInstr::UnconditionalBranchImmediate => {
+                    self.parse_unconditional_branch_immediate(instr)
+                }
+                BranchesExceptionGenNSysInstr::CompareAndBranchImmediate => {
+                    self.parse_compare_and_branch_immediate(instr)
+                }
+                BranchesExceptionGenNSysInstr::TestAndBranchImmediate => {
+                    self.parse_test_and_branch_immediate(instr)
+                }
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
         }
     }
 
-    fn parse_conditional_branch_immediate(&self, instr: u32) -> AArch64Inst {
+    fn parse_conditional_branch_immediate(&self, instr: u32) -> Result<AArch64Inst, Error> {
         let data = ConditionalBranchImmediateData {
             o1: self.conditional_branch_immediate_pm.get_arg(instr, 0),
             o0: self.conditional_branch_immediate_pm.get_arg(instr, 1),
@@ -769,65 +1523,193 @@ impl AArch64Parser {
         };
 
         match self.conditional_branch_immediate_pm.match_pattern(instr) {
+            Some(pat) if !self.features.contains(pat.feature()) => {
+                Ok(AArch64Inst::UNDEFINED(instr))
+            }
+            Some(pat) => match pat {
+                ConditionalBranchImmediate::BCond => Ok(AArch64Inst::BCond(data)),
+                ConditionalBranchImmediate::BcCond => Ok(AArch64Inst::BcCond(data)),
+                ConditionalBranchImmediate::Unallocated => Ok(AArch64Inst::UNALLOCATED(instr)),
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_unconditional_branch_register(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = UnconditionalBranchRegisterData {
+            opc: self.unconditional_branch_register_pm.get_arg(instr, 0),
+            op2: self.unconditional_branch_register_pm.get_arg(instr, 1),
+            op3: self.unconditional_branch_register_pm.get_arg(instr, 2),
+            rn: self.unconditional_branch_register_pm.get_arg(instr, 3),
+            op4: self.unconditional_branch_register_pm.get_arg(instr, 4),
+        };
+
+        match self.unconditional_branch_register_pm.match_pattern(instr) {
+            Some(pat) => match pat {
+                UnconditionalBranchRegister::BR => Ok(AArch64Inst::BR(data)),
+                UnconditionalBranchRegister::BLR => Ok(AArch64Inst::BLR(data)),
+                UnconditionalBranchRegister::RET => Ok(AArch64Inst::RET(data)),
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_unconditional_branch_immediate(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = UnconditionalBranchImmediateData {
+            op: self.unconditional_branch_immediate_pm.get_arg(instr, 0),
+            imm26: self.unconditional_branch_immediate_pm.get_arg(instr, 1),
+        };
+
+        match self.unconditional_branch_immediate_pm.match_pattern(instr) {
             Some(pat) => match pat {
-                ConditionalBranchImmediate::BCond => AArch64Inst::BCond(data),
-                ConditionalBranchImmediate::BcCond => todo!(),
-                ConditionalBranchImmediate::Unallocated => todo!(),
+                UnconditionalBranchImmediate::B => Ok(AArch64Inst::B(data)),
+                UnconditionalBranchImmediate::BL => Ok(AArch64Inst::BL(data)),
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_compare_and_branch_immediate(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = CompareAndBranchImmediateData {
+            sf: self.compare_and_branch_immediate_pm.get_arg(instr, 0),
+            op: self.compare_and_branch_immediate_pm.get_arg(instr, 1),
+            imm19: self.compare_and_branch_immediate_pm.get_arg(instr, 2),
+            rt: self.compare_and_branch_immediate_pm.get_arg(instr, 3),
+        };
+
+        match self.compare_and_branch_immediate_pm.match_pattern(instr) {
+            Some(pat) => match pat {
+                CompareAndBranchImmediate::CBZ32 => Ok(AArch64Inst::CBZ32(data)),
+                CompareAndBranchImmediate::CBNZ32 => Ok(AArch64Inst::CBNZ32(data)),
+                CompareAndBranchImmediate::CBZ64 => Ok(AArch64Inst::CBZ64(data)),
+                CompareAndBranchImmediate::CBNZ64 => Ok(AArch64Inst::CBNZ64(data)),
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_test_and_branch_immediate(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = TestAndBranchImmediateData {
+            b5: self.test_and_branch_immediate_pm.get_arg(instr, 0),
+            op: self.test_and_branch_immediate_pm.get_arg(instr, 1),
+            b40: self.test_and_branch_immediate_pm.get_arg(instr, 2),
+            imm14: self.test_and_branch_immediate_pm.get_arg(instr, 3),
+            rt: self.test_and_branch_immediate_pm.get_arg(instr, 4),
+        };
+
+        match self.test_and_branch_immediate_pm.match_pattern(instr) {
+            Some(pat) => match pat {
+                TestAndBranchImmediate::TBZ => Ok(AArch64Inst::TBZ(data)),
+                TestAndBranchImmediate::TBNZ => Ok(AArch64Inst::TBNZ(data)),
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
         }
     }
     //==============================Load and Stores==============================
-    fn parse_load_and_stores(&self, instr: u32) -> AArch64Inst {
+    fn parse_load_and_stores(&self, instr: u32) -> Result<AArch64Inst, Error> {
         match self.loads_and_stores_pm.match_pattern(instr) {
+            Some(pat) if !self.features.contains(pat.feature()) => {
+                Ok(AArch64Inst::UNDEFINED(instr))
+            }
             Some(pat) => match pat {
-                LoadsAndStores::CompareAndSwapPair => todo!(),
-                LoadsAndStores::AdvancedSIMDLoadStoreMultipleStructures => todo!(),
-                LoadsAndStores::AdvancedSIMDLoadStoreMultipleStructuresPostIndexed => todo!(),
-                LoadsAndStores::Unallocated0 => todo!(),
-                LoadsAndStores::AdvancedSIMDLoadStoreSingleStructure => todo!(),
-                LoadsAndStores::AdvancedSIMDLoadStoreSingleStructurePostIndexed => todo!(),
-                LoadsAndStores::Unallocated1 => todo!(),
-                LoadsAndStores::Unallocated2 => todo!(),
-                LoadsAndStores::Unallocated3 => todo!(),
-                LoadsAndStores::Unallocated4 => todo!(),
-                LoadsAndStores::Unallocated5 => todo!(),
-                LoadsAndStores::LoadStoreMemoryTags => todo!(),
-                LoadsAndStores::LoadStoreExclusivePair => todo!(),
-                LoadsAndStores::Unallocated6 => todo!(),
-                LoadsAndStores::LoadStoreExclusiveRegister => todo!(),
-                LoadsAndStores::LoadStoreOrdered => todo!(),
-                LoadsAndStores::CompareAndSwap => todo!(),
-                LoadsAndStores::LDAPRSTLRUnscalaedImmediate => todo!(),
-                LoadsAndStores::LoadRegisterLiteral => todo!(),
-                LoadsAndStores::MemoryCopyAndMemorySet => todo!(),
-                LoadsAndStores::LoadStoreNoAllocatePairOffset => todo!(),
-                LoadsAndStores::LoadStoreRegisterPairPostIndexed => todo!(),
-                LoadsAndStores::LoadStoreRegisterPairOffset => todo!(),
-                LoadsAndStores::LoadStoreRegisterPairPreIndexed => todo!(),
-                LoadsAndStores::LoadStoreRegisterUnscalaedImmediate => todo!(),
-                LoadsAndStores::LoadStoreRegisterImmediatePostIndexed => todo!(),
-                LoadsAndStores::LoadStoreRegisterUnprivileged => todo!(),
-                LoadsAndStores::LoadStoreRegisterImmediatePreIndexed => todo!(),
-                LoadsAndStores::AtomicMemoryOperations => todo!(),
-                LoadsAndStores::LoadStoreRegisterRegisterOffset => todo!(),
-                LoadsAndStores::LoadStoreRegisterPac => todo!(),
+                LoadsAndStores::CompareAndSwapPair => Err(Error::UnknownInstructionError(instr)),
+                LoadsAndStores::AdvancedSIMDLoadStoreMultipleStructures => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::AdvancedSIMDLoadStoreMultipleStructuresPostIndexed => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::Unallocated0 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                LoadsAndStores::AdvancedSIMDLoadStoreSingleStructure => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::AdvancedSIMDLoadStoreSingleStructurePostIndexed => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::Unallocated1 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                LoadsAndStores::Unallocated2 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                LoadsAndStores::Unallocated3 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                LoadsAndStores::Unallocated4 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                LoadsAndStores::Unallocated5 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                LoadsAndStores::LoadStoreMemoryTags => Err(Error::UnknownInstructionError(instr)),
+                LoadsAndStores::LoadStoreExclusivePair => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::Unallocated6 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                LoadsAndStores::LoadStoreExclusiveRegister => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreOrdered => Err(Error::UnknownInstructionError(instr)),
+                LoadsAndStores::CompareAndSwap => Err(Error::UnknownInstructionError(instr)),
+                LoadsAndStores::LDAPRSTLRUnscalaedImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadRegisterLiteral => self.parse_load_register_literal(instr),
+                LoadsAndStores::MemoryCopyAndMemorySet => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreNoAllocatePairOffset => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreRegisterPairPostIndexed => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreRegisterPairOffset => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreRegisterPairPreIndexed => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreRegisterUnscalaedImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreRegisterImmediatePostIndexed => {
+                    self.parse_load_store_register_immediate_indexed(instr, false)
+                }
+                LoadsAndStores::LoadStoreRegisterUnprivileged => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreRegisterImmediatePreIndexed => {
+                    self.parse_load_store_register_immediate_indexed(instr, true)
+                }
+                LoadsAndStores::AtomicMemoryOperations => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadsAndStores::LoadStoreRegisterRegisterOffset => {
+                    self.parse_load_store_register_register_offset(instr)
+                }
+                LoadsAndStores::LoadStoreRegisterPac => Err(Error::UnknownInstructionError(instr)),
                 LoadsAndStores::LoadStoreRegisterUnsignedImmediate => {
                     self.parse_load_store_register_unsigned_immediate(instr)
                 }
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
         }
     }
 
-    fn parse_load_store_register_unsigned_immediate(&self, instr: u32) -> AArch64Inst {
+    fn parse_load_store_register_unsigned_immediate(
+        &self,
+        instr: u32,
+    ) -> Result<AArch64Inst, Error> {
         let data = LoadStoreRegisterUnsignedImmediateData {
-            size: self.add_subtract_immediate_pm.get_arg(instr, 0),
-            v: self.add_subtract_immediate_pm.get_arg(instr, 1),
-            opc: self.add_subtract_immediate_pm.get_arg(instr, 2),
-            imm12: self.add_subtract_immediate_pm.get_arg(instr, 3),
-            rn: self.add_subtract_immediate_pm.get_arg(instr, 4),
-            rt: self.add_subtract_immediate_pm.get_arg(instr, 5),
+            size: self
+                .load_store_register_unsigned_immediate_pm
+                .get_arg(instr, 0),
+            v: self
+                .load_store_register_unsigned_immediate_pm
+                .get_arg(instr, 1),
+            opc: self
+                .load_store_register_unsigned_immediate_pm
+                .get_arg(instr, 2),
+            imm12: self
+                .load_store_register_unsigned_immediate_pm
+                .get_arg(instr, 3),
+            rn: self
+                .load_store_register_unsigned_immediate_pm
+                .get_arg(instr, 4),
+            rt: self
+                .load_store_register_unsigned_immediate_pm
+                .get_arg(instr, 5),
         };
 
         match self
@@ -835,64 +1717,361 @@ impl AArch64Parser {
             .match_pattern(instr)
         {
             Some(pat) => match pat {
-                LoadStoreRegisterUnsignedImmediate::Unallocated0 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRBImmediate => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRBImmediate => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRSBImmediate64 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRSBImmediate32 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP8 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP8 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP128 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP128 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRHImmediate => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRHImmediate => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRSHImmediate64 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRSHImmediate32 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP16 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP16 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::Unallocated1 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::Unallocated2 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRImmediate32 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRImmediate32 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRSWImmediate => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP32 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP32 => todo!(),
+                LoadStoreRegisterUnsignedImmediate::Unallocated0 => {
+                    Ok(AArch64Inst::UNALLOCATED(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRBImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRBImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRSBImmediate64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRSBImmediate32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP8 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP8 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP128 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP128 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRHImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRHImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRSHImmediate64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRSHImmediate32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP16 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP16 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::Unallocated1 => {
+                    Ok(AArch64Inst::UNALLOCATED(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::Unallocated2 => {
+                    Ok(AArch64Inst::UNALLOCATED(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRImmediate32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRImmediate32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRSWImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
                 LoadStoreRegisterUnsignedImmediate::STRImmediate64 => {
-                    AArch64Inst::STRImmediate64(data)
+                    Ok(AArch64Inst::STRImmediate64(data))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRImmediate64 => {
+                    Ok(AArch64Inst::LDRImmediate64(data))
+                }
+                LoadStoreRegisterUnsignedImmediate::PRFMImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP64 => {
+                    Err(Error::UnknownInstructionError(instr))
                 }
-                LoadStoreRegisterUnsignedImmediate::LDRImmediate64 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::PRFMImmediate => todo!(),
-                LoadStoreRegisterUnsignedImmediate::STRImmediateSIMDFP64 => todo!(),
-                LoadStoreRegisterUnsignedImmediate::LDRImmediateSIMDFP64 => todo!(),
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_load_store_register_register_offset(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = LoadStoreRegisterRegisterOffsetData {
+            size: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 0),
+            v: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 1),
+            opc: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 2),
+            rm: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 3),
+            option: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 4),
+            s: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 5),
+            rn: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 6),
+            rt: self
+                .load_store_register_register_offset_pm
+                .get_arg(instr, 7),
+        };
+
+        let Some(pat) = self
+            .load_store_register_register_offset_pm
+            .match_pattern(instr)
+        else {
+            return Err(Error::UnknownInstructionError(instr));
+        };
+
+        // Sub-word index extensions are UNDEFINED in the allocated encodings.
+        let unallocated = matches!(
+            pat,
+            LoadStoreRegisterRegisterOffset::Unallocated0
+                | LoadStoreRegisterRegisterOffset::Unallocated1
+                | LoadStoreRegisterRegisterOffset::Unallocated2
+        );
+        if !unallocated && data.option & 0b010 == 0 {
+            return Ok(AArch64Inst::UNDEFINED(instr));
+        }
+
+        match pat {
+            LoadStoreRegisterRegisterOffset::STRBRegister => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRBRegister => Ok(AArch64Inst::LDRBRegister(data)),
+            LoadStoreRegisterRegisterOffset::LDRSBRegister64 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRSBRegister32 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP8 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP8 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP128 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP128 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::STRHRegister => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRHRegister => Ok(AArch64Inst::LDRHRegister(data)),
+            LoadStoreRegisterRegisterOffset::LDRSHRegister64 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRSHRegister32 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP16 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP16 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::Unallocated0 => Ok(AArch64Inst::UNALLOCATED(instr)),
+            LoadStoreRegisterRegisterOffset::Unallocated1 => Ok(AArch64Inst::UNALLOCATED(instr)),
+            LoadStoreRegisterRegisterOffset::Unallocated2 => Ok(AArch64Inst::UNALLOCATED(instr)),
+            LoadStoreRegisterRegisterOffset::STRRegister32 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRRegister32 => Ok(AArch64Inst::LDRRegister32(data)),
+            LoadStoreRegisterRegisterOffset::LDRSWRegister => Ok(AArch64Inst::LDRSWRegister(data)),
+            LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP32 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP32 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::STRRegister64 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRRegister64 => Ok(AArch64Inst::LDRRegister64(data)),
+            LoadStoreRegisterRegisterOffset::PRFMRegister => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::STRRegisterSIMDFP64 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+            LoadStoreRegisterRegisterOffset::LDRRegisterSIMDFP64 => {
+                Err(Error::UnknownInstructionError(instr))
+            }
+        }
+    }
+
+    fn parse_load_register_literal(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = LoadRegisterLiteralData {
+            opc: self.load_register_literal_pm.get_arg(instr, 0),
+            v: self.load_register_literal_pm.get_arg(instr, 1),
+            imm19: self.load_register_literal_pm.get_arg(instr, 2),
+            rt: self.load_register_literal_pm.get_arg(instr, 3),
+        };
+
+        match self.load_register_literal_pm.match_pattern(instr) {
+            Some(pat) => match pat {
+                LoadRegisterLiteral::LDRLiteral32 => Ok(AArch64Inst::LDRLiteral32(data)),
+                LoadRegisterLiteral::LDRLiteralSIMDFP32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadRegisterLiteral::LDRLiteral64 => Ok(AArch64Inst::LDRLiteral64(data)),
+                LoadRegisterLiteral::LDRLiteralSIMDFP64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadRegisterLiteral::LDRSWLiteral => Ok(AArch64Inst::LDRSWLiteral(data)),
+                LoadRegisterLiteral::LDRLiteralSIMDFP128 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                LoadRegisterLiteral::PRFMLiteral => Err(Error::UnknownInstructionError(instr)),
+                LoadRegisterLiteral::Unallocated0 => Ok(AArch64Inst::UNALLOCATED(instr)),
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_load_store_register_immediate_indexed(
+        &self,
+        instr: u32,
+        pre_indexed: bool,
+    ) -> Result<AArch64Inst, Error> {
+        let data = LoadStoreRegisterImmediateIndexedData {
+            size: self
+                .load_store_register_immediate_indexed_pm
+                .get_arg(instr, 0),
+            v: self
+                .load_store_register_immediate_indexed_pm
+                .get_arg(instr, 1),
+            opc: self
+                .load_store_register_immediate_indexed_pm
+                .get_arg(instr, 2),
+            imm9: self
+                .load_store_register_immediate_indexed_pm
+                .get_arg(instr, 3),
+            rn: self
+                .load_store_register_immediate_indexed_pm
+                .get_arg(instr, 4),
+            rt: self
+                .load_store_register_immediate_indexed_pm
+                .get_arg(instr, 5),
+        };
+
+        let pat = match self
+            .load_store_register_immediate_indexed_pm
+            .match_pattern(instr)
+        {
+            Some(pat) => pat,
+            None => return Err(Error::UnknownInstructionError(instr)),
+        };
+
+        // Writeback into the transfer register is CONSTRAINED UNPREDICTABLE, except via SP.
+        let writeback_overlap = data.v == 0 && data.rn == data.rt && data.rn != 31;
+
+        match pat {
+            LoadStoreRegisterImmediateIndexed::Unallocated0
+            | LoadStoreRegisterImmediateIndexed::Unallocated1
+            | LoadStoreRegisterImmediateIndexed::Unallocated2
+            | LoadStoreRegisterImmediateIndexed::Unallocated3 => {
+                Ok(AArch64Inst::UNALLOCATED(instr))
+            }
+            _ if writeback_overlap => Ok(AArch64Inst::UNPREDICTABLE(
+                instr,
+                Unpredictable::WritebackOverlap,
+            )),
+            LoadStoreRegisterImmediateIndexed::STRImmediate32 if pre_indexed => {
+                Ok(AArch64Inst::STRImmediatePreIndexed32(data))
+            }
+            LoadStoreRegisterImmediateIndexed::STRImmediate32 => {
+                Ok(AArch64Inst::STRImmediatePostIndexed32(data))
+            }
+            LoadStoreRegisterImmediateIndexed::LDRImmediate32 if pre_indexed => {
+                Ok(AArch64Inst::LDRImmediatePreIndexed32(data))
+            }
+            LoadStoreRegisterImmediateIndexed::LDRImmediate32 => {
+                Ok(AArch64Inst::LDRImmediatePostIndexed32(data))
+            }
+            LoadStoreRegisterImmediateIndexed::STRImmediate64 if pre_indexed => {
+                Ok(AArch64Inst::STRImmediatePreIndexed64(data))
+            }
+            LoadStoreRegisterImmediateIndexed::STRImmediate64 => {
+                Ok(AArch64Inst::STRImmediatePostIndexed64(data))
+            }
+            LoadStoreRegisterImmediateIndexed::LDRImmediate64 if pre_indexed => {
+                Ok(AArch64Inst::LDRImmediatePreIndexed64(data))
+            }
+            LoadStoreRegisterImmediateIndexed::LDRImmediate64 => {
+                Ok(AArch64Inst::LDRImmediatePostIndexed64(data))
+            }
+            _ => Err(Error::UnknownInstructionError(instr)),
         }
     }
 
     //==============================Data processing Register==============================
-    fn parse_data_processing_register(&self, instr: u32) -> AArch64Inst {
+    fn parse_data_processing_register(&self, instr: u32) -> Result<AArch64Inst, Error> {
         match self.data_processing_register_pm.match_pattern(instr) {
+            Some(pat) if !self.features.contains(pat.feature()) => {
+                Ok(AArch64Inst::UNDEFINED(instr))
+            }
             Some(pat) => match pat {
-                DataProcessingRegister::DataProcessing2Source => todo!(),
-                DataProcessingRegister::DataProcessing1Source => todo!(),
-                DataProcessingRegister::LogicalShiftedRegister => todo!(),
+                DataProcessingRegister::DataProcessing2Source => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::DataProcessing1Source => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::LogicalShiftedRegister => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
                 DataProcessingRegister::AddSubtractShiftedRegister => {
                     self.parse_add_subtract_shifted_register(instr)
                 }
-                DataProcessingRegister::AddSubtractExtendedRegister => todo!(),
-                DataProcessingRegister::AddSubtractWithCarry => todo!(),
-                DataProcessingRegister::RotateRightIntoFlags => todo!(),
-                DataProcessingRegister::EvaluateInfoFlags => todo!(),
-                DataProcessingRegister::ConditionalCompareRegister => todo!(),
-                DataProcessingRegister::ConditionalCompareImmediate => todo!(),
-                DataProcessingRegister::ConditionalSelect => todo!(),
-                DataProcessingRegister::DataProcessing3Source => todo!(),
+                DataProcessingRegister::AddSubtractExtendedRegister => {
+                    self.parse_add_subtract_extended_register(instr)
+                }
+                DataProcessingRegister::AddSubtractWithCarry => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::RotateRightIntoFlags => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::EvaluateInfoFlags => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::ConditionalCompareRegister => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::ConditionalCompareImmediate => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::ConditionalSelect => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                DataProcessingRegister::DataProcessing3Source => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
         }
     }
 
-    fn parse_add_subtract_shifted_register(&self, instr: u32) -> AArch64Inst {
+    fn parse_add_subtract_shifted_register(&self, instr: u32) -> Result<AArch64Inst, Error> {
         let data = AddSubtractShiftedRegisterData {
             sf: self.add_subtract_shifted_register_pm.get_arg(instr, 0),
             op: self.add_subtract_shifted_register_pm.get_arg(instr, 1),
@@ -906,20 +2085,215 @@ impl AArch64Parser {
 
         match self.add_subtract_shifted_register_pm.match_pattern(instr) {
             Some(pat) => match pat {
-                AddSubtractShiftedRegister::Unallocated0 => todo!(),
-                AddSubtractShiftedRegister::Unallocated1 => todo!(),
-                AddSubtractShiftedRegister::ADDShiftedRegister32 => todo!(),
-                AddSubtractShiftedRegister::ADDSShiftedReigster32 => todo!(),
-                AddSubtractShiftedRegister::SUBShiftedRegister32 => todo!(),
-                AddSubtractShiftedRegister::SUBSShiftedRegister32 => todo!(),
-                AddSubtractShiftedRegister::ADDShiftedRegister64 => todo!(),
-                AddSubtractShiftedRegister::ADDSShiftedRegister64 => todo!(),
-                AddSubtractShiftedRegister::SUBShiftedRegister64 => todo!(),
+                AddSubtractShiftedRegister::Unallocated0 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                AddSubtractShiftedRegister::Unallocated1 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                AddSubtractShiftedRegister::ADDShiftedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractShiftedRegister::ADDSShiftedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractShiftedRegister::SUBShiftedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractShiftedRegister::SUBSShiftedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractShiftedRegister::ADDShiftedRegister64 => {
+                    Ok(AArch64Inst::ADDShiftedRegister64(data))
+                }
+                AddSubtractShiftedRegister::ADDSShiftedRegister64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractShiftedRegister::SUBShiftedRegister64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
                 AddSubtractShiftedRegister::SUBSShiftedRegister64 => {
-                    AArch64Inst::SUBSShiftedRegister64(data)
+                    Ok(AArch64Inst::SUBSShiftedRegister64(data))
+                }
+            },
+            None => Err(Error::UnknownInstructionError(instr)),
+        }
+    }
+
+    fn parse_add_subtract_extended_register(&self, instr: u32) -> Result<AArch64Inst, Error> {
+        let data = AddSubtractExtendedRegisterData {
+            sf: self.add_subtract_extended_register_pm.get_arg(instr, 0),
+            op: self.add_subtract_extended_register_pm.get_arg(instr, 1),
+            s: self.add_subtract_extended_register_pm.get_arg(instr, 2),
+            opt: self.add_subtract_extended_register_pm.get_arg(instr, 3),
+            imm3: self.add_subtract_extended_register_pm.get_arg(instr, 4),
+            rm: self.add_subtract_extended_register_pm.get_arg(instr, 5),
+            option: self.add_subtract_extended_register_pm.get_arg(instr, 6),
+            rn: self.add_subtract_extended_register_pm.get_arg(instr, 7),
+            rd: self.add_subtract_extended_register_pm.get_arg(instr, 8),
+        };
+
+        match self.add_subtract_extended_register_pm.match_pattern(instr) {
+            Some(pat) => match pat {
+                AddSubtractExtendedRegister::Unallocated0 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                AddSubtractExtendedRegister::Unallocated1 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                AddSubtractExtendedRegister::Unallocated2 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                AddSubtractExtendedRegister::Unallocated3 => Ok(AArch64Inst::UNALLOCATED(instr)),
+                AddSubtractExtendedRegister::ADDExtendedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractExtendedRegister::ADDSExtendedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractExtendedRegister::SUBExtendedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractExtendedRegister::SUBSExtendedRegister32 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractExtendedRegister::ADDExtendedRegister64 => {
+                    Ok(AArch64Inst::ADDExtendedRegister64(data))
+                }
+                AddSubtractExtendedRegister::ADDSExtendedRegister64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractExtendedRegister::SUBExtendedRegister64 => {
+                    Err(Error::UnknownInstructionError(instr))
+                }
+                AddSubtractExtendedRegister::SUBSExtendedRegister64 => {
+                    Err(Error::UnknownInstructionError(instr))
                 }
             },
-            None => todo!(),
+            None => Err(Error::UnknownInstructionError(instr)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::Feature::*;
+
+    #[test]
+    fn feature_gating_test() {
+        // bc.eq #0
+        let bc_cond = 0x5400_0010;
+        // b.eq #0
+        let b_cond = 0x5400_0000;
+
+        let parser = AArch64Parser::new();
+        assert!(matches!(parser.parse(bc_cond), Ok(AArch64Inst::BcCond(_))));
+        assert_eq!(parser.parse(bc_cond).unwrap().feature(), FEAT_HBC);
+
+        let parser = AArch64Parser::with_features(ArmV8_0 | FEAT_LSE);
+        assert!(matches!(
+            parser.parse(bc_cond),
+            Ok(AArch64Inst::UNDEFINED(_))
+        ));
+        assert!(matches!(parser.parse(b_cond), Ok(AArch64Inst::BCond(_))));
+
+        let parser = AArch64Parser::with_features(ArmV8_0 | FEAT_HBC);
+        assert!(matches!(parser.parse(bc_cond), Ok(AArch64Inst::BcCond(_))));
+    }
+
+    #[test]
+    fn static_decode_test() {
+        const BASELINE: AArch64Parser = AArch64Parser::with_feature_set(FeatureSet::empty());
+        // bc.eq #0
+        assert!(matches!(
+            BASELINE.parse(0x5400_0010),
+            Ok(AArch64Inst::UNDEFINED(_))
+        ));
+
+        let parser = AArch64Parser::new();
+        let file_data = std::fs::read("a64_example").unwrap();
+        for bytes in file_data.chunks_exact(4) {
+            let raw = u32::from_le_bytes(bytes.try_into().unwrap());
+            assert_eq!(
+                format!("{:?}", decode(raw)),
+                format!("{:?}", parser.parse(raw))
+            );
+        }
+    }
+
+    #[test]
+    fn fields_test() {
+        let parser = AArch64Parser::new();
+
+        // sub sp, sp, #0x40
+        let fields: Vec<_> = parser.fields(0xd101_03ff).collect();
+        let tables: Vec<_> = fields.iter().map(|field| field.table).collect();
+        assert_eq!(tables.first(), Some(&"Main encoding"));
+        assert_eq!(tables.last(), Some(&"Add/subtract (immediate)"));
+
+        let imm12 = fields.iter().find(|field| field.name == "imm12").unwrap();
+        assert_eq!((imm12.start, imm12.end, imm12.value), (10, 22, 0x40));
+        let op1 = fields.iter().find(|field| field.name == "op1").unwrap();
+        assert_eq!((op1.table, op1.value), ("Main encoding", 0b1000));
+
+        // Unimplemented groups stop at the group table.
+        assert_eq!(
+            parser.fields(0xd503_201f).last().unwrap().table,
+            "Branches, exception generating and system"
+        );
+    }
+
+    #[test]
+    fn invalid_encoding_test() {
+        let parser = AArch64Parser::new();
+
+        // udf #0x1234
+        assert!(matches!(
+            parser.parse(0x0000_1234),
+            Ok(AArch64Inst::UNDEFINED(0x0000_1234))
+        ));
+        assert!(matches!(
+            parser.parse(0x0001_0000),
+            Ok(AArch64Inst::RESERVED(0x0001_0000))
+        ));
+        assert!(matches!(
+            parser.parse(0x0200_0000),
+            Ok(AArch64Inst::UNALLOCATED(0x0200_0000))
+        ));
+        // b.eq with o1 set
+        assert!(matches!(
+            parser.parse(0x5500_0000),
+            Ok(AArch64Inst::UNALLOCATED(_))
+        ));
+        // add x0, x1, x2, ror #0
+        assert!(matches!(
+            parser.parse(0x8bc2_0020),
+            Ok(AArch64Inst::UNALLOCATED(_))
+        ));
+        // ldr x0, [x0, w0, uxtb]
+        assert!(matches!(
+            parser.parse(0xf860_0800),
+            Ok(AArch64Inst::UNDEFINED(_))
+        ));
+        // size 11, opc 11 with the same extension
+        assert!(matches!(
+            parser.parse(0xf8e0_0800),
+            Ok(AArch64Inst::UNALLOCATED(_))
+        ));
+    }
+
+    #[test]
+    fn writeback_overlap_test() {
+        let parser = AArch64Parser::new();
+
+        // str x30, [sp, #-16]!
+        assert!(matches!(
+            parser.parse(0xf81f_0ffe),
+            Ok(AArch64Inst::STRImmediatePreIndexed64(_))
+        ));
+        // ldr x0, [x0], #8
+        assert!(matches!(
+            parser.parse(0xf840_8400),
+            Ok(AArch64Inst::UNPREDICTABLE(
+                0xf840_8400,
+                Unpredictable::WritebackOverlap
+            ))
+        ));
+        // ldr x1, [x0], #8
+        assert!(matches!(
+            parser.parse(0xf840_8401),
+            Ok(AArch64Inst::LDRImmediatePostIndexed64(_))
+        ));
+    }
+}
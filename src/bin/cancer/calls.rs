@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use cancer::{AArch64Parser, Call, CallGraph, Program, RawLayout};
+use clap::Parser;
+use serde::Serialize;
+
+use crate::input::{parse_address, Format, Input};
+
+#[derive(Parser, Debug)]
+pub struct CallsArgs {
+    #[arg(help = "ELF, Mach-O, PE, Intel HEX, S-record or raw binary file")]
+    file: PathBuf,
+
+    #[arg(long, help = "Print the graph as JSON instead of Graphviz DOT")]
+    json: bool,
+
+    #[arg(
+        long,
+        value_name = "FUNCTION",
+        conflicts_with = "callees",
+        help = "Only list the functions calling FUNCTION (name or address)"
+    )]
+    callers: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FUNCTION",
+        help = "Only list the functions FUNCTION (name or address) calls"
+    )]
+    callees: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format, detected from the file header when omitted"
+    )]
+    format: Option<Format>,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, default_value = "0", help = "Load address of a raw binary")]
+    base: u64,
+
+    #[arg(long = "entry", value_name = "ADDR", value_parser = parse_address, help = "Known entry points of a raw binary, labelled as functions")]
+    entries: Vec<u64>,
+}
+
+#[derive(Serialize)]
+struct Graph<'a> {
+    functions: Vec<Node>,
+    calls: &'a [Call],
+}
+
+#[derive(Serialize)]
+struct Node {
+    address: u64,
+    name: String,
+    import: bool,
+}
+
+pub fn run(args: &CallsArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let input = Input::read(&args.file, args.format)?;
+    let layout = RawLayout {
+        base_address: args.base,
+        entry_points: args.entries.clone(),
+        ..Default::default()
+    };
+    let program = input.program(&layout)?;
+    let graph = program.call_graph(&AArch64Parser::new());
+
+    match (&args.callers, &args.callees) {
+        (Some(function), _) => {
+            let address = resolve(&program, function)?;
+            write_functions(&program, graph.callers(address), out)
+        }
+        (_, Some(function)) => {
+            let address = resolve(&program, function)?;
+            write_functions(&program, graph.callees(address), out)
+        }
+        _ if args.json => write_json(&program, &graph, out),
+        _ => Ok(out.write_all(graph.to_dot(&program).as_bytes())?),
+    }
+}
+
+// Symbol names, PLT import names with or without `@plt`, or addresses.
+pub fn resolve(program: &Program, function: &str) -> Result<u64, Box<dyn Error>> {
+    if let Some(symbol) = program.symbols().by_name(function) {
+        return Ok(symbol.address);
+    }
+    let import = function.strip_suffix("@plt").unwrap_or(function);
+    if let Some(stub) = program.plt_stubs().find(|stub| stub.name == import) {
+        return Ok(stub.address);
+    }
+
+    parse_address(function).map_err(|_| format!("no function named `{}`", function).into())
+}
+
+fn write_functions(
+    program: &Program,
+    functions: impl Iterator<Item = u64>,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    for address in functions {
+        writeln!(
+            out,
+            "{:016x} <{}>",
+            address,
+            program.function_label(address)
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_json(
+    program: &Program,
+    graph: &CallGraph,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let functions = graph
+        .functions()
+        .map(|address| Node {
+            address,
+            name: program.function_label(address),
+            import: graph.is_import(address),
+        })
+        .collect();
+    let graph = Graph {
+        functions,
+        calls: graph.calls(),
+    };
+
+    serde_json::to_writer(&mut *out, &graph)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cancer::test_utils::raw_program;
+
+    #[test]
+    fn calls_test() {
+        // sub_1000: bl sub_100c; blr x8; b sub_100c
+        // sub_100c: ret
+        let program = raw_program(
+            &[0x9400_0003, 0xd63f_0100, 0x1400_0001, 0xd65f_03c0],
+            &[0x1000, 0x100c],
+        );
+        let graph = program.call_graph(&AArch64Parser::new());
+
+        let mut out = Vec::new();
+        write_json(&program, &graph, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["functions"][1]["name"], "sub_100c");
+        assert_eq!(json["functions"][1]["import"], false);
+        assert_eq!(json["calls"][0]["callee"], 0x100c);
+        assert_eq!(json["calls"][1]["kind"], "Indirect");
+        assert_eq!(json["calls"][2]["kind"], "TailCall");
+
+        let mut out = Vec::new();
+        let callers = graph.callers(resolve(&program, "sub_100c").unwrap());
+        write_functions(&program, callers, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0000000000001000 <sub_1000>\n"
+        );
+        assert_eq!(resolve(&program, "0x100c").unwrap(), 0x100c);
+        assert!(resolve(&program, "missing").is_err());
+    }
+}
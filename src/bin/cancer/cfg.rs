@@ -0,0 +1,95 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use cancer::{AArch64Parser, Program, RawLayout};
+use clap::Parser;
+
+use crate::input::{parse_address, Format, Input};
+
+#[derive(Parser, Debug)]
+pub struct CfgArgs {
+    #[arg(help = "ELF, Mach-O, PE, Intel HEX, S-record or raw binary file")]
+    file: PathBuf,
+
+    #[arg(help = "Name or start address of the function")]
+    function: String,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format, detected from the file header when omitted"
+    )]
+    format: Option<Format>,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, default_value = "0", help = "Load address of a raw binary")]
+    base: u64,
+
+    #[arg(long = "entry", value_name = "ADDR", value_parser = parse_address, help = "Known entry points of a raw binary, labelled as functions")]
+    entries: Vec<u64>,
+}
+
+pub fn run(args: &CfgArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let input = Input::read(&args.file, args.format)?;
+    let layout = RawLayout {
+        base_address: args.base,
+        entry_points: args.entries.clone(),
+        ..Default::default()
+    };
+    let program = input.program(&layout)?;
+
+    write_dot(&program, &args.function, out)
+}
+
+fn write_dot(
+    program: &Program,
+    function: &str,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let address = match program.symbols().by_name(function) {
+        Some(symbol) => Some(symbol.address),
+        None => parse_address(function).ok(),
+    };
+    let function = program
+        .functions()
+        .into_iter()
+        .find(|candidate| Some(candidate.symbol.address) == address)
+        .ok_or_else(|| format!("no function named `{}`", function))?;
+
+    let parser = AArch64Parser::new();
+    let cfg = program.cfg(&parser, &function.code);
+    out.write_all(
+        cfg.to_dot(&parser, |address| program.label(address))
+            .as_bytes(),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cancer::test_utils::raw_program;
+
+    #[test]
+    fn write_dot_test() {
+        // sub_1000: cbz x0, 0x100c; bl sub_1010; ret
+        // sub_100c: ret
+        let program = raw_program(
+            &[0xb400_0060, 0x9400_0003, 0xd65f_03c0, 0xd65f_03c0],
+            &[0x1000, 0x100c],
+        );
+
+        let mut out = Vec::new();
+        write_dot(&program, "sub_1000", &mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert!(dot.contains("\"0x1000\" -> \"ext0\" [color=darkgreen];"));
+        assert!(dot.contains("\"ext0\" [label=\"sub_100c\", shape=plaintext];"));
+        assert!(dot.contains("\"0x1000\" -> \"0x1004\" [style=dashed];"));
+
+        let mut out = Vec::new();
+        write_dot(&program, "0x100c", &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("100c: ret"));
+        assert!(write_dot(&program, "missing", &mut Vec::new()).is_err());
+    }
+}
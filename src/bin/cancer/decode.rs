@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::io::{self, Read, Write};
+
+use cancer::{AArch64Parser, Endianness, Field};
+use clap::{Parser, ValueEnum};
+
+use crate::input::parse_address;
+use crate::json::{self, Labels};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ByteOrder {
+    Little,
+    Big,
+    Mixed,
+}
+
+impl From<ByteOrder> for Endianness {
+    fn from(order: ByteOrder) -> Self {
+        match order {
+            ByteOrder::Little => Endianness::Little,
+            ByteOrder::Big => Endianness::Big,
+            ByteOrder::Mixed => Endianness::Mixed,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct DecodeArgs {
+    #[arg(help = "Hex instruction words, read from stdin when omitted")]
+    words: Vec<String>,
+
+    #[arg(long, help = "Treat the input as a hex byte stream in memory order")]
+    bytes: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "little",
+        help = "Byte order of --bytes input"
+    )]
+    endian: ByteOrder,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, help = "Address of the first word, for branch targets")]
+    address: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Print the underlying instruction instead of its preferred alias"
+    )]
+    no_aliases: bool,
+
+    #[arg(long, help = "Only print the disassembly, without the encoding fields")]
+    brief: bool,
+
+    #[arg(long, help = "Print one JSON object per instruction (JSON Lines)")]
+    json: bool,
+}
+
+pub fn run(args: &DecodeArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let text = match args.words.is_empty() {
+        true => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        false => args.words.join(" "),
+    };
+
+    let words = match args.bytes {
+        true => parse_bytes(&text, args.endian.into())?,
+        false => parse_words(&text)?,
+    };
+
+    decode(&words, args, out)
+}
+
+fn decode(words: &[u32], args: &DecodeArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let parser = AArch64Parser::new();
+    let base = args.address.unwrap_or(0);
+
+    for (idx, word) in words.iter().enumerate() {
+        let address = base + idx as u64 * 4;
+        if args.json {
+            let labels = Labels::default();
+            let result = parser.parse(*word);
+            json::write_line(
+                out,
+                address,
+                &word.to_le_bytes(),
+                &result,
+                !args.no_aliases,
+                labels,
+            )?;
+            continue;
+        }
+
+        if args.address.is_some() {
+            write!(out, "{:x}: ", address)?;
+        }
+        write!(out, "{:08x}", word)?;
+
+        match parser.parse(*word) {
+            Ok(inst) => {
+                let disassembly = inst.display(address).aliases(!args.no_aliases);
+                writeln!(out, "\t{}", disassembly)?;
+            }
+            Err(_) => writeln!(out, "\t<unknown>")?,
+        }
+
+        if !args.brief {
+            write_fields(parser.fields(*word), out)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Grouped by encoding table, each field with its bit range and value in binary.
+fn write_fields(fields: impl Iterator<Item = Field>, out: &mut impl Write) -> io::Result<()> {
+    let mut table = "";
+    for field in fields {
+        if field.table != table {
+            table = field.table;
+            writeln!(out, "    {}", table)?;
+        }
+
+        let bits = match field.end - field.start {
+            1 => format!("{}", field.start),
+            _ => format!("{}:{}", field.end - 1, field.start),
+        };
+        let width = (field.end - field.start) as usize;
+        write!(
+            out,
+            "        {:<6} {:<6} {:0width$b}",
+            field.name,
+            bits,
+            field.value,
+            width = width
+        )?;
+        if field.value > 1 {
+            write!(out, " ({:#x})", field.value)?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| token.trim_start_matches("0x").trim_start_matches("0X"))
+}
+
+// Each token is one word as a number, the way registers and crash dumps print it.
+fn parse_words(text: &str) -> Result<Vec<u32>, String> {
+    tokens(text)
+        .map(|token| {
+            let digits = token.replace('_', "");
+            match digits.len() {
+                1..=8 => u32::from_str_radix(&digits, 16).ok(),
+                _ => None,
+            }
+            .ok_or_else(|| format!("invalid instruction word `{}`", token))
+        })
+        .collect()
+}
+
+// All tokens form one byte stream, the way a hex dump of memory prints it.
+fn parse_bytes(text: &str, endianness: Endianness) -> Result<Vec<u32>, Box<dyn Error>> {
+    let digits: String = tokens(text).collect();
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("invalid hex digit `{}` in byte stream", c).into());
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits in `{}`", digits).into());
+    }
+
+    let bytes = (0..digits.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&digits[idx..idx + 2], 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("invalid byte stream: {}", err))?;
+    if !bytes.len().is_multiple_of(4) {
+        let len = bytes.len() % 4;
+        let offset = (bytes.len() - len) as u64;
+        return Err(cancer::Error::TruncatedInstructionError(offset, len).into());
+    }
+
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| endianness.word(chunk.try_into().unwrap()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(
+            parse_words("d10103ff 0xf9000fe0,\nd65f_03c0"),
+            Ok(vec![0xd101_03ff, 0xf900_0fe0, 0xd65f_03c0])
+        );
+        assert!(parse_words("d10103ff0").is_err());
+        assert!(parse_words("xyz").is_err());
+
+        let words = [0xd101_03ff, 0xd65f_03c0];
+        assert_eq!(
+            parse_bytes("ff0301d1 c0 03 5f d6", Endianness::Little).unwrap(),
+            words
+        );
+        assert_eq!(
+            parse_bytes("d10103ff d65f03c0", Endianness::Big).unwrap(),
+            words
+        );
+        assert!(parse_bytes("ff0301", Endianness::Little).is_err());
+        assert!(parse_bytes("ff0", Endianness::Little).is_err());
+        assert!(parse_bytes("+f0301d1", Endianness::Little).is_err());
+        assert!(parse_bytes("a\u{e9}f", Endianness::Little).is_err());
+    }
+
+    #[test]
+    fn decode_test() {
+        let args = DecodeArgs::parse_from(["decode", "--address=0x1000"]);
+        let mut out = Vec::new();
+        decode(&[0xd101_03ff, 0x1700_0000], &args, &mut out).unwrap();
+
+        let expected = "1000: d10103ff\tsub sp, sp, #64
+    Main encoding
+        op0    31     1
+        op1    28:25  1000 (0x8)
+    Data processing - immediate
+        op0    25:23  010 (0x2)
+    Add/subtract (immediate)
+        sf     31     1
+        op     30     1
+        S      29     0
+        sh     22     0
+        imm12  21:10  000001000000 (0x40)
+        Rn     9:5    11111 (0x1f)
+        Rd     4:0    11111 (0x1f)
+1004: 17000000\tb 0xfffffffffc001004
+";
+        assert!(String::from_utf8(out).unwrap().starts_with(expected));
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use cancer::{
+    AArch64Inst, AArch64Parser, ByteKind, CodeMap, CodeRegion, Discovery, Literal, Program,
+    RawLayout, TargetKind, XrefKind, Xrefs,
+};
+use clap::{Parser, ValueEnum};
+
+use crate::input::{parse_address, Format, Input};
+use crate::json::{self, Labels};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    Recursive,
+    Gaps,
+}
+
+impl From<Mode> for Discovery {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Recursive => Discovery::Recursive,
+            Mode::Gaps => Discovery::FillGaps,
+        }
+    }
+}
+
+// Flags follow objdump where it has an equivalent.
+#[derive(Parser, Debug)]
+pub struct DisasmArgs {
+    #[arg(help = "ELF, Mach-O, PE, Intel HEX, S-record or raw binary file")]
+    file: PathBuf,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format, detected from the file header when omitted"
+    )]
+    format: Option<Format>,
+
+    #[arg(
+        short = 'j',
+        long = "section",
+        value_name = "NAME",
+        help = "Only disassemble the named sections"
+    )]
+    sections: Vec<String>,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, help = "Start disassembling at this address")]
+    start_address: Option<u64>,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, help = "Stop disassembling before this address")]
+    stop_address: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SYMBOL",
+        help = "Stop at the first address of the named symbol"
+    )]
+    stop_at: Option<String>,
+
+    #[arg(long, help = "Do not print the instruction bytes")]
+    no_show_raw_insn: bool,
+
+    #[arg(
+        long,
+        help = "Omit function headers, `<symbol>` annotations on branch targets and reference comments"
+    )]
+    no_labels: bool,
+
+    #[arg(
+        long,
+        help = "Print the underlying instruction instead of its preferred alias"
+    )]
+    no_aliases: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        help = "Follow control flow from the entry points and print what it does not reach as data, optionally filling gaps by linear sweep"
+    )]
+    discover: Option<Mode>,
+
+    #[arg(long, help = "Print one JSON object per instruction (JSON Lines)")]
+    json: bool,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, default_value = "0", help = "Load address of a raw binary")]
+    base: u64,
+
+    #[arg(long = "entry", value_name = "ADDR", value_parser = parse_address, help = "Known entry points of a raw binary, labelled as functions")]
+    entries: Vec<u64>,
+}
+
+pub fn run(args: &DisasmArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let input = Input::read(&args.file, args.format)?;
+    let layout = RawLayout {
+        base_address: args.base,
+        entry_points: args.entries.clone(),
+        ..Default::default()
+    };
+    let program = input.program(&layout)?;
+
+    disassemble(&program, args, out)
+}
+
+fn disassemble(
+    program: &Program,
+    args: &DisasmArgs,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let stop_at = match &args.stop_at {
+        Some(name) => match program.symbols().by_name(name) {
+            Some(symbol) => Some(symbol.address),
+            None => return Err(format!("no symbol named `{}`", name).into()),
+        },
+        None => None,
+    };
+    let start = args.start_address.unwrap_or(0);
+    let stop = args.stop_address.into_iter().chain(stop_at).min();
+
+    let parser = AArch64Parser::new();
+    let map = args
+        .discover
+        .map(|mode| program.discover(&parser, mode.into()));
+    // Comments show the strings and literals instructions refer to, and who refers to
+    // each instruction or literal pool entry.
+    let references = (!args.json && !args.no_labels).then(|| References {
+        xrefs: program.xrefs(&parser),
+        literals: program
+            .literals(&parser)
+            .into_iter()
+            .map(|literal| (literal.address, literal))
+            .collect(),
+    });
+    let regions = program.regions().iter().filter(|region| {
+        args.sections.is_empty()
+            || args
+                .sections
+                .iter()
+                .any(|section| region.name.as_deref() == Some(section))
+    });
+    for region in regions {
+        let region_end = region.address + region.data.len() as u64;
+        let end = stop.map_or(region_end, |stop| stop.min(region_end));
+        // Instructions are word aligned relative to the start of their region.
+        let first = region.address + start.saturating_sub(region.address).next_multiple_of(4);
+        if first >= end {
+            continue;
+        }
+
+        let mut decoder = region.instructions(&parser);
+        decoder.seek(first)?;
+        let instructions = decoder.take_while(|(address, _, _)| *address < end);
+
+        if args.json {
+            for (address, _, result) in instructions {
+                let labels = match args.no_labels {
+                    true => Labels::default(),
+                    false => Labels {
+                        symbol: label(program, map.as_ref(), address),
+                        target: result
+                            .as_ref()
+                            .ok()
+                            .and_then(|inst| target_label(program, map.as_ref(), address, inst)),
+                    },
+                };
+                let bytes = region_bytes(region, address);
+                json::write_line(out, address, bytes, &result, !args.no_aliases, labels)?;
+            }
+            continue;
+        }
+
+        match &region.name {
+            Some(name) => writeln!(out, "\nDisassembly of section {}:", name)?,
+            None => writeln!(out, "\nDisassembly of {:#x}:", region.address)?,
+        }
+
+        for (address, raw, result) in instructions {
+            let is_start = program.symbols().at(address).is_some()
+                || program.plt_stub(address).is_some()
+                || map.as_ref().is_some_and(|map| map.is_function(address));
+            if !args.no_labels && (is_start || address == first) {
+                if let Some(label) = label(program, map.as_ref(), address) {
+                    writeln!(out, "\n{:016x} <{}>:", address, label)?;
+                }
+            }
+
+            write!(out, "{:>8x}:", address)?;
+            if !args.no_show_raw_insn {
+                write!(out, " {:<12}", raw_bytes(region, address))?;
+            }
+
+            let is_pool = references
+                .as_ref()
+                .is_some_and(|references| references.pool_entry(address).is_some());
+            let is_code = !is_pool
+                && map
+                    .as_ref()
+                    .is_none_or(|map| map.kind_at(address) == Some(ByteKind::Code));
+            let inst = result.as_ref().ok().filter(|_| is_code);
+            let comment = references
+                .as_ref()
+                .and_then(|references| references.comment(program, map.as_ref(), address, inst));
+            match result {
+                Ok(_) | Err(cancer::Error::UnknownInstructionError(_)) if !is_code => {
+                    write!(out, "\t.word\t{:#010x}", raw)?
+                }
+                Ok(inst) => {
+                    let disassembly = inst.display(address).aliases(!args.no_aliases);
+                    write!(out, "\t{}", disassembly.mnemonic())?;
+
+                    let operands = disassembly.operands().to_string();
+                    if !operands.is_empty() {
+                        write!(out, "\t{}", operands)?;
+                    }
+
+                    let target = match args.no_labels {
+                        true => None,
+                        false => target_label(program, map.as_ref(), address, &inst),
+                    };
+                    if let Some(target) = target {
+                        write!(out, " <{}>", target)?;
+                    }
+                }
+                Err(_) => write!(out, "\t<unknown>")?,
+            }
+            if let Some(comment) = comment {
+                write!(out, " // {}", comment)?;
+            }
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn label(program: &Program, map: Option<&CodeMap>, address: u64) -> Option<String> {
+    match map {
+        Some(map) if map.is_function(address) => Some(program.function_label(address)),
+        _ => program.label(address),
+    }
+}
+
+fn target_label(
+    program: &Program,
+    map: Option<&CodeMap>,
+    address: u64,
+    inst: &AArch64Inst,
+) -> Option<String> {
+    match inst.branch_target(address) {
+        Some(target) if program.relocation_at(address).is_none() => label(program, map, target),
+        _ => program.target_label(address, inst),
+    }
+}
+
+struct References {
+    xrefs: Xrefs,
+    literals: BTreeMap<u64, Literal>,
+}
+
+impl References {
+    fn pool_entry(&self, address: u64) -> Option<&Literal> {
+        let (_, literal) = self.literals.range(..=address).next_back()?;
+        (address < literal.address + literal.size as u64).then_some(literal)
+    }
+
+    fn comment(
+        &self,
+        program: &Program,
+        map: Option<&CodeMap>,
+        address: u64,
+        inst: Option<&AArch64Inst>,
+    ) -> Option<String> {
+        let mut parts = Vec::new();
+
+        let strings: Vec<_> = self
+            .xrefs
+            .refs_from(address)
+            .filter(|xref| xref.target == TargetKind::String)
+            .filter_map(|xref| program.string_at(xref.to))
+            .map(|string| format!("{:?}", string.text))
+            .collect();
+        let literal = inst.and_then(|inst| program.literal(address, inst));
+        match literal {
+            Some(literal) if strings.is_empty() => parts.push(format!("={:#x}", literal.value)),
+            _ => parts.extend(strings),
+        }
+
+        let mut referrers: Vec<_> = self
+            .xrefs
+            .refs_to(address)
+            .filter(|xref| !matches!(xref.kind, XrefKind::Call | XrefKind::Jump))
+            .map(|xref| xref.from)
+            .collect();
+        referrers.dedup();
+        if !referrers.is_empty() {
+            let labels: Vec<_> = referrers
+                .into_iter()
+                .map(|from| label(program, map, from).unwrap_or_else(|| format!("{:#x}", from)))
+                .collect();
+            parts.push(format!("xref: {}", labels.join(", ")));
+        }
+
+        (!parts.is_empty()).then(|| parts.join("; "))
+    }
+}
+
+// Bytes in file order; a truncated trailing word has fewer than four.
+fn region_bytes<'data>(region: &CodeRegion<'data>, address: u64) -> &'data [u8] {
+    let offset = (address - region.address) as usize;
+    let len = (region.data.len() - offset).min(4);
+
+    &region.data[offset..offset + len]
+}
+
+fn raw_bytes(region: &CodeRegion, address: u64) -> String {
+    region_bytes(region, address)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cancer::test_utils::raw_program;
+
+    fn disassemble_raw(program: &Program, args: &[&str]) -> String {
+        let args = DisasmArgs::parse_from(["disasm", "-"].iter().chain(args));
+
+        let mut out = Vec::new();
+        disassemble(program, &args, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn disassemble_test() {
+        // sub_1000: mov x29, sp; bl sub_100c; ret
+        // sub_100c: cmp x1, x2; ret
+        let program = raw_program(
+            &[
+                0x9100_03fd,
+                0x9400_0002,
+                0xd65f_03c0,
+                0xeb02_003f,
+                0xd65f_03c0,
+            ],
+            &[0x1000, 0x100c],
+        );
+
+        let expected = "
+Disassembly of 0x1000:
+
+0000000000001000 <sub_1000>:
+    1000: fd 03 00 91 \tmov\tx29, sp
+    1004: 02 00 00 94 \tbl\t0x100c <sub_100c>
+    1008: c0 03 5f d6 \tret
+
+000000000000100c <sub_100c>:
+    100c: 3f 00 02 eb \tcmp\tx1, x2
+    1010: c0 03 5f d6 \tret
+";
+        assert_eq!(disassemble_raw(&program, &[]), expected);
+
+        let expected = "
+Disassembly of 0x1000:
+    1004:\tbl\t0x100c
+    1008:\tret\tx30
+";
+        let args = [
+            "--start-address=0x1002",
+            "--stop-at=sub_100c",
+            "--no-show-raw-insn",
+            "--no-labels",
+            "--no-aliases",
+        ];
+        assert_eq!(disassemble_raw(&program, &args), expected);
+
+        let output = disassemble_raw(&program, &["--json", "--stop-address=0x1008"]);
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["symbol"], "sub_1000+0x4");
+        assert_eq!(lines[1]["target_label"], "sub_100c");
+
+        // bl 0x1010; b sub_100c; .word; ret; ret
+        let discovered = raw_program(
+            &[
+                0x9400_0004,
+                0x1400_0002,
+                0x1234_5678,
+                0xd65f_03c0,
+                0xd65f_03c0,
+            ],
+            &[0x1000, 0x100c],
+        );
+        let expected = "
+Disassembly of 0x1000:
+
+0000000000001000 <sub_1000>:
+    1000:\tbl\t0x1010 <sub_1010>
+    1004:\tb\t0x100c <sub_100c>
+    1008:\t.word\t0x12345678
+
+000000000000100c <sub_100c>:
+    100c:\tret
+
+0000000000001010 <sub_1010>:
+    1010:\tret
+";
+        let args = ["--discover=recursive", "--no-show-raw-insn"];
+        assert_eq!(disassemble_raw(&discovered, &args), expected);
+
+        // ldr w0, 0x1008; ret; .word 42; ret
+        let pool = raw_program(
+            &[0x1800_0040, 0xd65f_03c0, 0x2a, 0xd65f_03c0],
+            &[0x1000, 0x100c],
+        );
+        let expected = "
+Disassembly of 0x1000:
+
+0000000000001000 <sub_1000>:
+    1000:\tldr\tw0, 0x1008 // =0x2a
+    1004:\tret
+    1008:\t.word\t0x0000002a // xref: sub_1000
+
+000000000000100c <sub_100c>:
+    100c:\tret
+";
+        assert_eq!(disassemble_raw(&pool, &["--no-show-raw-insn"]), expected);
+        assert!(!disassemble_raw(&pool, &["--no-labels"]).contains("//"));
+
+        // A trailing partial word.
+        let layout = RawLayout {
+            base_address: 0x1000,
+            ..Default::default()
+        };
+        let partial = Program::from_raw(&[0xfd, 0x03, 0x00, 0x91, 0x02, 0x00], &layout);
+        let output = disassemble_raw(&partial, &["--no-labels"]);
+        assert!(output.ends_with("    1004: 02 00       \t<unknown>\n"));
+    }
+}
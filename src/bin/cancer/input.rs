@@ -0,0 +1,92 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use cancer::{MemoryImage, Program, RawLayout};
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Elf,
+    Macho,
+    Pe,
+    Ihex,
+    Srec,
+    Raw,
+}
+
+impl Format {
+    // Anything without a recognisable header is treated as a flat binary.
+    pub fn detect(data: &[u8]) -> Self {
+        match data {
+            [0x7f, b'E', b'L', b'F', ..] => Format::Elf,
+            [0xcf, 0xfa, 0xed, 0xfe, ..] | [0xca, 0xfe, 0xba, 0xbe, ..] => Format::Macho,
+            [b'M', b'Z', ..] => Format::Pe,
+            [b':', ..] => Format::Ihex,
+            [b'S', b'0'..=b'9', ..] => Format::Srec,
+            _ => Format::Raw,
+        }
+    }
+}
+
+// Owns the file contents that a `Program` borrows from.
+pub enum Input {
+    Bytes(Format, Vec<u8>),
+    Image(MemoryImage),
+}
+
+impl Input {
+    pub fn read(path: &Path, format: Option<Format>) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let format = format.unwrap_or_else(|| Format::detect(&data));
+
+        Ok(match format {
+            Format::Ihex => Input::Image(MemoryImage::from_ihex(std::str::from_utf8(&data)?)?),
+            Format::Srec => Input::Image(MemoryImage::from_srec(std::str::from_utf8(&data)?)?),
+            format => Input::Bytes(format, data),
+        })
+    }
+
+    pub fn program(&self, layout: &RawLayout) -> Result<Program<'_>, cancer::Error> {
+        match self {
+            Input::Bytes(Format::Elf, data) => Program::from_elf(data),
+            Input::Bytes(Format::Macho, data) => Program::from_macho(data),
+            Input::Bytes(Format::Pe, data) => Program::from_pe(data),
+            Input::Bytes(_, data) => Ok(Program::from_raw(data, layout)),
+            Input::Image(image) => Ok(Program::from_image(image)),
+        }
+    }
+}
+
+pub fn parse_address(text: &str) -> Result<u64, String> {
+    let result = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+
+    result.map_err(|err| format!("invalid address `{}`: {}", text, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_test() {
+        let elf = fs::read("a64_example").unwrap();
+        assert_eq!(Format::detect(&elf), Format::Elf);
+        let macho = fs::read("a64_macho_example.o").unwrap();
+        assert_eq!(Format::detect(&macho), Format::Macho);
+
+        assert_eq!(Format::detect(b":0400000000"), Format::Ihex);
+        assert_eq!(Format::detect(b"S00600004844521B"), Format::Srec);
+        assert_eq!(Format::detect(&[0xc0, 0x03, 0x5f, 0xd6]), Format::Raw);
+    }
+
+    #[test]
+    fn parse_address_test() {
+        assert_eq!(parse_address("0x400078"), Ok(0x400078));
+        assert_eq!(parse_address("4096"), Ok(4096));
+        assert!(parse_address("0xzz").is_err());
+    }
+}
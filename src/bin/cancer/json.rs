@@ -0,0 +1,110 @@
+use std::io::{self, Write};
+
+use cancer::{AArch64Inst, Error, RegisterSet};
+use serde::Serialize;
+
+// One JSON Lines record. Keys are always present, with `null` for what does not apply, so
+// that every line has the same shape.
+#[derive(Serialize)]
+struct Line<'a> {
+    address: u64,
+    bytes: String,
+    symbol: Option<String>,
+    mnemonic: Option<String>,
+    operands: Option<String>,
+    reads: RegisterSet,
+    writes: RegisterSet,
+    target: Option<u64>,
+    target_label: Option<String>,
+    inst: Option<&'a AArch64Inst>,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Labels {
+    pub symbol: Option<String>,
+    pub target: Option<String>,
+}
+
+pub fn write_line(
+    out: &mut impl Write,
+    address: u64,
+    bytes: &[u8],
+    result: &Result<AArch64Inst, Error>,
+    aliases: bool,
+    labels: Labels,
+) -> io::Result<()> {
+    let mut line = Line {
+        address,
+        bytes: bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        symbol: labels.symbol,
+        mnemonic: None,
+        operands: None,
+        reads: RegisterSet::default(),
+        writes: RegisterSet::default(),
+        target: None,
+        target_label: labels.target,
+        inst: None,
+        error: None,
+    };
+
+    match result {
+        Ok(inst) => {
+            let disassembly = inst.display(address).aliases(aliases);
+            line.mnemonic = Some(disassembly.mnemonic().to_string());
+            line.operands = Some(disassembly.operands().to_string());
+            line.reads = inst.reads();
+            line.writes = inst.writes();
+            line.target = inst.branch_target(address);
+            line.inst = Some(inst);
+        }
+        Err(err) => line.error = Some(err.to_string()),
+    }
+
+    serde_json::to_writer(&mut *out, &line)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cancer::decode;
+
+    #[test]
+    fn write_line_test() {
+        let mut out = Vec::new();
+        let labels = Labels {
+            symbol: Some("main".to_string()),
+            target: Some("helper".to_string()),
+        };
+        write_line(
+            &mut out,
+            0x1000,
+            &[2, 0, 0, 0x94],
+            &decode(0x9400_0002),
+            true,
+            labels,
+        )
+        .unwrap();
+
+        let line: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(line["address"], 0x1000);
+        assert_eq!(line["bytes"], "02000094");
+        assert_eq!(line["mnemonic"], "bl");
+        assert_eq!(line["operands"], "0x1008");
+        assert_eq!(line["reads"], serde_json::json!([]));
+        assert_eq!(line["writes"], serde_json::json!(["x30"]));
+        assert_eq!(line["target"], 0x1008);
+        assert_eq!(line["target_label"], "helper");
+        assert_eq!(line["inst"]["BL"]["imm26"], 2);
+        assert!(line["error"].is_null());
+        assert!(out.ends_with(b"}\n"));
+
+        let mut out = Vec::new();
+        let labels = Labels::default();
+        write_line(&mut out, 0, &[0; 4], &decode(0x5e20_0400), true, labels).unwrap();
+        let line: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert!(line["mnemonic"].is_null());
+        assert_eq!(line["error"], "Unknown instruction: 0x5e200400");
+    }
+}
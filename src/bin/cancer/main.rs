@@ -0,0 +1,81 @@
+mod calls;
+mod cfg;
+mod decode;
+mod disasm;
+mod input;
+mod json;
+mod map;
+mod strings;
+mod xrefs;
+
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+use crate::calls::CallsArgs;
+use crate::cfg::CfgArgs;
+use crate::decode::DecodeArgs;
+use crate::disasm::DisasmArgs;
+use crate::map::MapArgs;
+use crate::strings::StringsArgs;
+use crate::xrefs::XrefsArgs;
+
+#[derive(Parser, Debug)]
+#[command(name = "cancer", version, about = "AArch64 disassembler")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(visible_alias = "d", about = "Disassemble the code in a binary")]
+    Disasm(DisasmArgs),
+    #[command(about = "Decode instruction words given in hex")]
+    Decode(DecodeArgs),
+    #[command(about = "Print the control flow graph of a function in Graphviz DOT")]
+    Cfg(CfgArgs),
+    #[command(about = "Classify the bytes of the code regions as code or data")]
+    Map(MapArgs),
+    #[command(
+        about = "Print the call graph in Graphviz DOT or JSON, or query callers and callees"
+    )]
+    Calls(CallsArgs),
+    #[command(about = "List the references from code to functions and data")]
+    Xrefs(XrefsArgs),
+    #[command(about = "List the strings code refers to and the functions referring to them")]
+    Strings(StringsArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let result = match &cli.command {
+        Command::Disasm(args) => disasm::run(args, &mut out),
+        Command::Decode(args) => decode::run(args, &mut out),
+        Command::Cfg(args) => cfg::run(args, &mut out),
+        Command::Map(args) => map::run(args, &mut out),
+        Command::Calls(args) => calls::run(args, &mut out),
+        Command::Xrefs(args) => xrefs::run(args, &mut out),
+        Command::Strings(args) => strings::run(args, &mut out),
+    };
+    let result = result.and_then(|_| Ok(out.flush()?));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // Piping into `head` is not an error.
+        Err(err)
+            if err
+                .downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == ErrorKind::BrokenPipe) =>
+        {
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("cancer: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use cancer::{AArch64Parser, ByteKind, Discovery, Program, RawLayout};
+use clap::Parser;
+
+use crate::input::{parse_address, Format, Input};
+
+#[derive(Parser, Debug)]
+pub struct MapArgs {
+    #[arg(help = "ELF, Mach-O, PE, Intel HEX, S-record or raw binary file")]
+    file: PathBuf,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format, detected from the file header when omitted"
+    )]
+    format: Option<Format>,
+
+    #[arg(
+        long,
+        help = "Sweep linearly over what control flow does not reach instead of leaving it unknown"
+    )]
+    gaps: bool,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, default_value = "0", help = "Load address of a raw binary")]
+    base: u64,
+
+    #[arg(long = "entry", value_name = "ADDR", value_parser = parse_address, help = "Known entry points of a raw binary, labelled as functions")]
+    entries: Vec<u64>,
+}
+
+pub fn run(args: &MapArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let input = Input::read(&args.file, args.format)?;
+    let layout = RawLayout {
+        base_address: args.base,
+        entry_points: args.entries.clone(),
+        ..Default::default()
+    };
+    let program = input.program(&layout)?;
+
+    let mode = match args.gaps {
+        true => Discovery::FillGaps,
+        false => Discovery::Recursive,
+    };
+    write_map(&program, mode, out)
+}
+
+// One line per run of code, data or unknown bytes, then the functions that were found.
+fn write_map(
+    program: &Program,
+    mode: Discovery,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let parser = AArch64Parser::new();
+    let map = program.discover(&parser, mode);
+
+    for (range, kind) in map.ranges() {
+        let kind = match kind {
+            ByteKind::Code => "code",
+            ByteKind::Data => "data",
+            ByteKind::Unknown => "unknown",
+        };
+        writeln!(out, "{:016x}-{:016x} {}", range.start, range.end, kind)?;
+    }
+
+    writeln!(out)?;
+    for address in map.functions() {
+        let named = program.symbols().at(address).is_some() || program.plt_stub(address).is_some();
+        let label = match named {
+            true => program.label(address),
+            false => None,
+        };
+        let label = label.unwrap_or_else(|| format!("sub_{:x}", address));
+        writeln!(out, "{:016x} <{}>", address, label)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cancer::test_utils::raw_program;
+
+    #[test]
+    fn write_map_test() {
+        // bl 0x100c; ret; .word 0; ret
+        let program = raw_program(&[0x9400_0003, 0xd65f_03c0, 0, 0xd65f_03c0], &[0x1000]);
+
+        let mut out = Vec::new();
+        write_map(&program, Discovery::FillGaps, &mut out).unwrap();
+        let expected = "\
+0000000000001000-0000000000001008 code
+0000000000001008-000000000000100c data
+000000000000100c-0000000000001010 code
+
+0000000000001000 <sub_1000>
+000000000000100c <sub_100c>
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use cancer::{AArch64Parser, Program, RawLayout, StringKind, Xrefs};
+use clap::Parser;
+use serde::Serialize;
+
+use crate::input::{parse_address, Format, Input};
+
+#[derive(Parser, Debug)]
+pub struct StringsArgs {
+    #[arg(help = "ELF, Mach-O, PE, Intel HEX, S-record or raw binary file")]
+    file: PathBuf,
+
+    #[arg(long, help = "Print one JSON object per string (JSON Lines)")]
+    json: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format, detected from the file header when omitted"
+    )]
+    format: Option<Format>,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, default_value = "0", help = "Load address of a raw binary")]
+    base: u64,
+
+    #[arg(long = "entry", value_name = "ADDR", value_parser = parse_address, help = "Known entry points of a raw binary, labelled as functions")]
+    entries: Vec<u64>,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    address: u64,
+    text_address: u64,
+    text: &'a str,
+    kind: StringKind,
+    references: Vec<u64>,
+    functions: Vec<String>,
+}
+
+pub fn run(args: &StringsArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let input = Input::read(&args.file, args.format)?;
+    let layout = RawLayout {
+        base_address: args.base,
+        entry_points: args.entries.clone(),
+        ..Default::default()
+    };
+    let program = input.program(&layout)?;
+    let xrefs = program.xrefs(&AArch64Parser::new());
+
+    write_strings(&program, &xrefs, args.json, out)
+}
+
+// One line per referenced string with the functions referring to it.
+fn write_strings(
+    program: &Program,
+    xrefs: &Xrefs,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    for string in program.strings(xrefs) {
+        let references: Vec<_> = xrefs
+            .refs_to(string.address)
+            .map(|xref| xref.from)
+            .collect();
+        let functions: BTreeSet<_> = xrefs
+            .refs_to(string.address)
+            .map(|xref| match xref.function {
+                Some(function) => program.label(function),
+                None => program.label(xref.from),
+            })
+            .map(|label| label.unwrap_or_default())
+            .collect();
+
+        if json {
+            let line = Line {
+                address: string.address,
+                text_address: string.text_address,
+                text: &string.text,
+                kind: string.kind,
+                references,
+                functions: functions.into_iter().collect(),
+            };
+            serde_json::to_writer(&mut *out, &line)?;
+            writeln!(out)?;
+            continue;
+        }
+
+        write!(out, "{:016x} {:?}", string.address, string.text)?;
+        if !functions.is_empty() {
+            write!(
+                out,
+                " <- {}",
+                functions.into_iter().collect::<Vec<_>>().join(", ")
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        let xrefs = program.xrefs(&AArch64Parser::new());
+
+        let mut out = Vec::new();
+        write_strings(&program, &xrefs, false, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.lines().any(|line| line
+            == "00000000002001f0 \"there is no such thing as a relaxed compiler fence\" \
+                <- core::sync::atomic::compiler_fence"));
+
+        let mut out = Vec::new();
+        write_strings(&program, &xrefs, true, &mut out).unwrap();
+        let line: serde_json::Value = out
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .find(|line: &serde_json::Value| line["address"] == 0x20_02a0)
+            .unwrap();
+        assert_eq!(line["text"], "attempt to multiply with overflow");
+        assert_eq!(line["kind"], "Text");
+        assert_eq!(line["references"], serde_json::json!([0x21_052c]));
+        assert_eq!(line["functions"], serde_json::json!(["_start"]));
+    }
+}
@@ -0,0 +1,161 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+
+use cancer::{AArch64Parser, Program, RawLayout, TargetKind, Xref, XrefKind};
+use clap::Parser;
+
+use crate::calls::resolve;
+use crate::input::{parse_address, Format, Input};
+
+#[derive(Parser, Debug)]
+pub struct XrefsArgs {
+    #[arg(help = "ELF, Mach-O, PE, Intel HEX, S-record or raw binary file")]
+    file: PathBuf,
+
+    #[arg(long, help = "Print one JSON object per reference instead of text")]
+    json: bool,
+
+    #[arg(
+        long,
+        value_name = "ADDR",
+        conflicts_with = "from",
+        help = "Only list the references to ADDR (symbol name or address)"
+    )]
+    to: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "ADDR",
+        help = "Only list the references made by the instruction at ADDR (symbol name or address)"
+    )]
+    from: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Input format, detected from the file header when omitted"
+    )]
+    format: Option<Format>,
+
+    #[arg(long, value_name = "ADDR", value_parser = parse_address, default_value = "0", help = "Load address of a raw binary")]
+    base: u64,
+
+    #[arg(long = "entry", value_name = "ADDR", value_parser = parse_address, help = "Known entry points of a raw binary, labelled as functions")]
+    entries: Vec<u64>,
+}
+
+pub fn run(args: &XrefsArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let input = Input::read(&args.file, args.format)?;
+    let layout = RawLayout {
+        base_address: args.base,
+        entry_points: args.entries.clone(),
+        ..Default::default()
+    };
+    let program = input.program(&layout)?;
+    let xrefs = program.xrefs(&AArch64Parser::new());
+
+    let selected: Vec<_> = match (&args.to, &args.from) {
+        (Some(to), _) => xrefs.refs_to(resolve(&program, to)?).collect(),
+        (_, Some(from)) => xrefs.refs_from(resolve(&program, from)?).collect(),
+        _ => xrefs.iter().collect(),
+    };
+    write_xrefs(&program, &selected, args.json, out)
+}
+
+fn write_xrefs(
+    program: &Program,
+    xrefs: &[&Xref],
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    for xref in xrefs {
+        if json {
+            serde_json::to_writer(&mut *out, xref)?;
+            writeln!(out)?;
+            continue;
+        }
+
+        let kind = match xref.kind {
+            XrefKind::Call => "call",
+            XrefKind::Jump => "jump",
+            XrefKind::Address => "address",
+            XrefKind::Read => "read",
+            XrefKind::Write => "write",
+        };
+        // Data rarely has a symbol of its own, so show the text of strings and say where
+        // anything else lies instead.
+        let target = match (program.symbols().at(xref.to), xref.target) {
+            (_, TargetKind::String) => program
+                .string_at(xref.to)
+                .map_or_else(String::new, |string| format!("{:?}", string.text)),
+            (Some(_), _) | (None, TargetKind::Function) => {
+                format!("<{}>", program.label(xref.to).unwrap_or_default())
+            }
+            (None, TargetKind::Code) => "(code)".to_string(),
+            (None, TargetKind::Data) => "(data)".to_string(),
+            (None, TargetKind::Unmapped) => "(unmapped)".to_string(),
+        };
+
+        write!(out, "{:016x}", xref.from)?;
+        if let Some(label) = program.label(xref.from) {
+            write!(out, " <{}>", label)?;
+        }
+        writeln!(out, " {} {:#x} {}", kind, xref.to, target)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cancer::test_utils::raw_program;
+
+    #[test]
+    fn xrefs_test() {
+        // sub_1000: adr x0, 0x100c; bl sub_1010; b sub_1010
+        // 100c: ret
+        // sub_1010: ret
+        let program = raw_program(
+            &[
+                0x1000_0060,
+                0x9400_0003,
+                0x1400_0002,
+                0xd65f_03c0,
+                0xd65f_03c0,
+            ],
+            &[0x1000, 0x1010],
+        );
+        let xrefs = program.xrefs(&AArch64Parser::new());
+
+        let mut out = Vec::new();
+        write_xrefs(&program, &xrefs.iter().collect::<Vec<_>>(), false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0000000000001000 <sub_1000> address 0x100c (code)\n\
+             0000000000001004 <sub_1000+0x4> call 0x1010 <sub_1010>\n\
+             0000000000001008 <sub_1000+0x8> jump 0x1010 <sub_1010>\n"
+        );
+
+        let mut out = Vec::new();
+        let to = resolve(&program, "sub_1010").unwrap();
+        write_xrefs(
+            &program,
+            &xrefs.refs_to(to).collect::<Vec<_>>(),
+            true,
+            &mut out,
+        )
+        .unwrap();
+        let lines: Vec<serde_json::Value> = out
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["from"], 0x1004);
+        assert_eq!(lines[0]["kind"], "Call");
+        assert_eq!(lines[1]["target"], "Function");
+        assert_eq!(lines[1]["function"], 0x1000);
+    }
+}
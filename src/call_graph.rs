@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+
+use crate::aarc64_parser::AArch64Parser;
+use crate::program::Program;
+use crate::AArch64Inst;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CallKind {
+    Call,
+    // A `B` to the start of another function.
+    TailCall,
+    // A `BLR`, whose callee is only known at run time.
+    Indirect,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Call {
+    pub caller: u64,
+    // Address of the branch instruction.
+    pub site: u64,
+    pub callee: Option<u64>,
+    pub kind: CallKind,
+}
+
+// Functions are keyed by their start address. PLT stubs are functions of their own, so
+// calls into shared libraries end at the stub of the import.
+pub struct CallGraph {
+    functions: BTreeSet<u64>,
+    imports: BTreeSet<u64>,
+    calls: Vec<Call>,
+}
+
+impl CallGraph {
+    pub fn functions(&self) -> impl Iterator<Item = u64> + '_ {
+        self.functions.iter().copied()
+    }
+
+    pub fn is_import(&self, function: u64) -> bool {
+        self.imports.contains(&function)
+    }
+
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    // Distinct direct callees, in address order.
+    pub fn callees(&self, function: u64) -> impl Iterator<Item = u64> {
+        self.calls
+            .iter()
+            .filter(|call| call.caller == function)
+            .filter_map(|call| call.callee)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+    }
+
+    // Distinct functions calling or tail calling `function`, in address order.
+    pub fn callers(&self, function: u64) -> impl Iterator<Item = u64> {
+        self.calls
+            .iter()
+            .filter(|call| call.callee == Some(function))
+            .map(|call| call.caller)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+    }
+
+    // Graphviz source with one node per function and one edge per distinct caller, callee
+    // and kind.
+    pub fn to_dot(&self, program: &Program) -> String {
+        let mut dot =
+            String::from("digraph callgraph {\n    node [shape=box, fontname=monospace];\n");
+
+        for function in self.functions() {
+            let name = program.function_label(function);
+            let shape = match self.is_import(function) {
+                true => ", shape=ellipse",
+                false => "",
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{:#x}\" [label=\"{}\"{}];",
+                function,
+                name.replace('"', "\\\""),
+                shape
+            );
+        }
+
+        let mut seen = HashSet::new();
+        for call in &self.calls {
+            if !seen.insert((call.caller, call.callee, call.kind)) {
+                continue;
+            }
+
+            let to = match call.callee {
+                Some(callee) => format!("{:#x}", callee),
+                // Each caller gets one node standing for all of its indirect callees.
+                None => {
+                    let _ = writeln!(
+                        dot,
+                        "    \"blr{:#x}\" [label=\"?\", shape=plaintext];",
+                        call.caller
+                    );
+                    format!("blr{:#x}", call.caller)
+                }
+            };
+            let style = match call.kind {
+                CallKind::Call => "color=blue",
+                CallKind::TailCall => "color=blue, style=dashed",
+                CallKind::Indirect => "color=red",
+            };
+            let _ = writeln!(dot, "    \"{:#x}\" -> \"{}\" [{}];", call.caller, to, style);
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Program<'_> {
+    // Built from the function symbols, which include exception table starts; a call to an
+    // address without a symbol adds that address as a function.
+    pub fn call_graph(&self, parser: &AArch64Parser) -> CallGraph {
+        let imports: BTreeSet<_> = self.plt_stubs().map(|stub| stub.address).collect();
+        let functions: BTreeMap<_, _> = self
+            .functions()
+            .into_iter()
+            .map(|function| (function.code.address, function))
+            .collect();
+        let is_function = |address| functions.contains_key(&address) || imports.contains(&address);
+
+        let mut calls = Vec::new();
+        for (caller, function) in &functions {
+            for (site, _, result) in function.code.instructions(parser) {
+                let Ok(inst) = result else {
+                    continue;
+                };
+
+                let target = inst.branch_target(site);
+                let kind = match inst {
+                    AArch64Inst::BL(_) => CallKind::Call,
+                    AArch64Inst::BLR(_) => CallKind::Indirect,
+                    AArch64Inst::B(_)
+                        if target.is_some_and(|target| {
+                            is_function(target) && !function.code.contains(target)
+                        }) =>
+                    {
+                        CallKind::TailCall
+                    }
+                    _ => continue,
+                };
+                calls.push(Call {
+                    caller: *caller,
+                    site,
+                    callee: target,
+                    kind,
+                });
+            }
+        }
+
+        let mut nodes: BTreeSet<_> = functions.keys().copied().collect();
+        nodes.extend(imports.iter().copied());
+        nodes.extend(calls.iter().filter_map(|call| call.callee));
+
+        CallGraph {
+            functions: nodes,
+            imports,
+            calls,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::raw_program;
+
+    #[test]
+    fn call_graph_test() {
+        // sub_1000: bl sub_100c; blr x8; b sub_1010
+        // sub_100c: ret
+        // sub_1010: bl 0x1018; ret
+        // 1018: b 0x1018
+        let program = raw_program(
+            &[
+                0x9400_0003,
+                0xd63f_0100,
+                0x1400_0002,
+                0xd65f_03c0,
+                0x9400_0002,
+                0xd65f_03c0,
+                0x1400_0000,
+            ],
+            &[0x1000, 0x100c, 0x1010],
+        );
+        let graph = program.call_graph(&AArch64Parser::new());
+
+        let call = |caller, site, callee, kind| Call {
+            caller,
+            site,
+            callee,
+            kind,
+        };
+        assert_eq!(
+            graph.calls(),
+            [
+                call(0x1000, 0x1000, Some(0x100c), CallKind::Call),
+                call(0x1000, 0x1004, None, CallKind::Indirect),
+                call(0x1000, 0x1008, Some(0x1010), CallKind::TailCall),
+                call(0x1010, 0x1010, Some(0x1018), CallKind::Call),
+            ]
+        );
+        assert_eq!(
+            graph.functions().collect::<Vec<_>>(),
+            [0x1000, 0x100c, 0x1010, 0x1018]
+        );
+        assert_eq!(graph.callees(0x1000).collect::<Vec<_>>(), [0x100c, 0x1010]);
+        assert_eq!(graph.callers(0x1010).collect::<Vec<_>>(), [0x1000]);
+        assert_eq!(graph.callers(0x1000).count(), 0);
+
+        let dot = graph.to_dot(&program);
+        assert!(dot.starts_with("digraph callgraph {"));
+        assert!(dot.contains("\"0x100c\" [label=\"sub_100c\"];"));
+        assert!(dot.contains("\"0x1018\" [label=\"sub_1018\"];"));
+        assert!(dot.contains("\"0x1000\" -> \"0x1010\" [color=blue, style=dashed];"));
+        assert!(dot.contains("\"0x1000\" -> \"blr0x1000\" [color=red];"));
+    }
+
+    #[test]
+    fn call_graph_elf_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        let graph = program.call_graph(&AArch64Parser::new());
+
+        let address = |name| program.symbols().by_name(name).unwrap().address;
+        let fence = address("core::sync::atomic::compiler_fence");
+        assert!(graph
+            .callees(fence)
+            .any(|callee| callee == address("core::panicking::panic_fmt")));
+        assert!(graph.callers(fence).count() > 0);
+    }
+}
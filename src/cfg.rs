@@ -0,0 +1,421 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::aarc64_parser::AArch64Parser;
+use crate::jump_table::JumpTable;
+use crate::program::{CodeRegion, Function, Program};
+use crate::AArch64Inst;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    Fallthrough,
+    Taken,
+    Call,
+    Indirect,
+}
+
+// `to` is `None` when the destination is only known at run time (BR, BLR). A BR through a
+// recovered jump table gets an `Indirect` edge to each distinct target instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub from: u64,
+    pub to: Option<u64>,
+    pub kind: EdgeKind,
+}
+
+pub struct BasicBlock<'data> {
+    pub code: CodeRegion<'data>,
+}
+
+impl BasicBlock<'_> {
+    pub fn start(&self) -> u64 {
+        self.code.address
+    }
+
+    pub fn end(&self) -> u64 {
+        self.code.address + self.code.data.len() as u64
+    }
+}
+
+// Blocks are keyed by their start address, and edges leave from the start of their block.
+// Calls do not end a block; their edges are recorded alongside the block's successors.
+pub struct Cfg<'data> {
+    entry: u64,
+    blocks: BTreeMap<u64, BasicBlock<'data>>,
+    edges: Vec<Edge>,
+}
+
+fn is_terminator(inst: &AArch64Inst) -> bool {
+    matches!(
+        inst,
+        AArch64Inst::B(_)
+            | AArch64Inst::BCond(_)
+            | AArch64Inst::BcCond(_)
+            | AArch64Inst::CBZ32(_)
+            | AArch64Inst::CBNZ32(_)
+            | AArch64Inst::CBZ64(_)
+            | AArch64Inst::CBNZ64(_)
+            | AArch64Inst::TBZ(_)
+            | AArch64Inst::TBNZ(_)
+            | AArch64Inst::BR(_)
+            | AArch64Inst::RET(_)
+    )
+}
+
+impl<'data> Cfg<'data> {
+    // Words that fail to decode are treated as straight-line code, so gaps in the decoder
+    // do not split blocks.
+    pub fn new(parser: &AArch64Parser, code: &CodeRegion<'data>) -> Self {
+        Self::with_jump_tables(parser, code, &[])
+    }
+
+    pub fn with_jump_tables(
+        parser: &AArch64Parser,
+        code: &CodeRegion<'data>,
+        tables: &[JumpTable],
+    ) -> Self {
+        let insts: Vec<_> = code
+            .instructions(parser)
+            .filter_map(|(address, _, result)| Some((address, result.ok()?)))
+            .collect();
+        let end = code.address + code.data.len() as u64;
+        let inside = |address: u64| (code.address..end).contains(&address);
+
+        let mut leaders = BTreeSet::from([code.address]);
+        for (address, inst) in &insts {
+            if !is_terminator(inst) {
+                continue;
+            }
+
+            leaders.insert(address + 4);
+            if let Some(target) = inst
+                .branch_target(*address)
+                .filter(|target| inside(*target))
+            {
+                leaders.insert(target);
+            }
+        }
+        for table in tables.iter().filter(|table| inside(table.branch)) {
+            leaders.extend(
+                table
+                    .targets
+                    .iter()
+                    .copied()
+                    .filter(|target| inside(*target)),
+            );
+        }
+        leaders.retain(|leader| inside(*leader) && (leader - code.address).is_multiple_of(4));
+
+        let mut blocks = BTreeMap::new();
+        let bounds: Vec<_> = leaders.iter().copied().chain([end]).collect();
+        for window in bounds.windows(2) {
+            let offset = (window[0] - code.address) as usize;
+            let len = (window[1] - window[0]) as usize;
+            blocks.insert(
+                window[0],
+                BasicBlock {
+                    code: CodeRegion {
+                        name: None,
+                        address: window[0],
+                        data: &code.data[offset..offset + len],
+                    },
+                },
+            );
+        }
+
+        let mut edges = Vec::new();
+        let insts: BTreeMap<_, _> = insts.into_iter().collect();
+        for block in blocks.values() {
+            let from = block.start();
+            let block_insts = insts.range(block.start()..block.end());
+
+            for (address, inst) in block_insts.clone() {
+                match inst {
+                    AArch64Inst::BL(_) => edges.push(Edge {
+                        from,
+                        to: inst.branch_target(*address),
+                        kind: EdgeKind::Call,
+                    }),
+                    AArch64Inst::BLR(_) => edges.push(Edge {
+                        from,
+                        to: None,
+                        kind: EdgeKind::Call,
+                    }),
+                    _ => {}
+                }
+            }
+
+            let fallthrough = Edge {
+                from,
+                to: Some(block.end()).filter(|next| *next < end),
+                kind: EdgeKind::Fallthrough,
+            };
+            let last = block_insts
+                .last()
+                .filter(|(address, _)| **address + 4 == block.end());
+            match last.map(|(address, inst)| (inst, inst.branch_target(*address))) {
+                Some((AArch64Inst::RET(_), _)) => {}
+                Some((AArch64Inst::BR(_), _)) => {
+                    let address = block.end() - 4;
+                    match tables.iter().find(|table| table.branch == address) {
+                        Some(table) => {
+                            let targets: BTreeSet<_> = table.targets.iter().copied().collect();
+                            edges.extend(targets.into_iter().map(|target| Edge {
+                                from,
+                                to: Some(target),
+                                kind: EdgeKind::Indirect,
+                            }));
+                        }
+                        None => edges.push(Edge {
+                            from,
+                            to: None,
+                            kind: EdgeKind::Indirect,
+                        }),
+                    }
+                }
+                Some((AArch64Inst::B(_), target)) => edges.push(Edge {
+                    from,
+                    to: target,
+                    kind: EdgeKind::Taken,
+                }),
+                Some((inst, target)) if is_terminator(inst) => {
+                    edges.push(Edge {
+                        from,
+                        to: target,
+                        kind: EdgeKind::Taken,
+                    });
+                    if fallthrough.to.is_some() {
+                        edges.push(fallthrough);
+                    }
+                }
+                _ if fallthrough.to.is_some() => edges.push(fallthrough),
+                _ => {}
+            }
+        }
+
+        Self {
+            entry: code.address,
+            blocks,
+            edges,
+        }
+    }
+
+    pub fn entry(&self) -> u64 {
+        self.entry
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = &BasicBlock<'data>> {
+        self.blocks.values()
+    }
+
+    pub fn block_at(&self, address: u64) -> Option<&BasicBlock<'data>> {
+        self.blocks
+            .range(..=address)
+            .next_back()
+            .map(|(_, block)| block)
+            .filter(|block| address < block.end())
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    // Intra-procedural successors; calls and edges leaving the function are left out.
+    pub fn successors(&self, block: u64) -> impl Iterator<Item = u64> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.from == block && edge.kind != EdgeKind::Call)
+            .filter_map(|edge| edge.to)
+            .filter(|to| self.blocks.contains_key(to))
+    }
+
+    pub fn predecessors(&self, block: u64) -> impl Iterator<Item = u64> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.to == Some(block) && edge.kind != EdgeKind::Call)
+            .map(|edge| edge.from)
+    }
+
+    // Graphviz source with each block's disassembly; `label` names call and branch targets
+    // outside the function, typically `Program::label`.
+    pub fn to_dot(&self, parser: &AArch64Parser, label: impl Fn(u64) -> Option<String>) -> String {
+        let mut dot = String::from("digraph cfg {\n    node [shape=box, fontname=monospace];\n");
+
+        for block in self.blocks() {
+            let mut text = String::new();
+            for (address, _, result) in block.code.instructions(parser) {
+                let _ = match result {
+                    Ok(inst) => write!(text, "{:x}: {}\\l", address, inst.display(address)),
+                    Err(_) => write!(text, "{:x}: <unknown>\\l", address),
+                };
+            }
+            let _ = writeln!(
+                dot,
+                "    \"{:#x}\" [label=\"{}\"];",
+                block.start(),
+                escape(&text)
+            );
+        }
+
+        for (idx, edge) in self.edges.iter().enumerate() {
+            let to = match edge.to {
+                Some(to) if self.blocks.contains_key(&to) => format!("{:#x}", to),
+                // Destinations outside the function get a plain text node of their own.
+                Some(to) => {
+                    let name = label(to).unwrap_or_else(|| format!("{:#x}", to));
+                    let _ = writeln!(
+                        dot,
+                        "    \"ext{}\" [label=\"{}\", shape=plaintext];",
+                        idx,
+                        escape(&name)
+                    );
+                    format!("ext{}", idx)
+                }
+                None => {
+                    let _ = writeln!(dot, "    \"ext{}\" [label=\"?\", shape=plaintext];", idx);
+                    format!("ext{}", idx)
+                }
+            };
+            let style = match edge.kind {
+                EdgeKind::Fallthrough => "style=dashed",
+                EdgeKind::Taken => "color=darkgreen",
+                EdgeKind::Call => "color=blue",
+                EdgeKind::Indirect => "color=red",
+            };
+            let _ = writeln!(dot, "    \"{:#x}\" -> \"{}\" [{}];", edge.from, to, style);
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('"', "\\\"")
+}
+
+impl<'data> Function<'_, 'data> {
+    pub fn cfg(&self, parser: &AArch64Parser) -> Cfg<'data> {
+        Cfg::new(parser, &self.code)
+    }
+}
+
+impl<'data> Program<'data> {
+    // Unlike `Cfg::new`, follows BRs through the jump tables found in `code`, whose
+    // entries may live in the program's data.
+    pub fn cfg(&self, parser: &AArch64Parser, code: &CodeRegion<'data>) -> Cfg<'data> {
+        Cfg::with_jump_tables(parser, code, &self.jump_tables(parser, code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{raw_program, switch_code};
+
+    #[test]
+    fn cfg_test() {
+        // 1000: cbz x0, 0x100c
+        // 1004: bl 0x2000
+        // 1008: b 0x1010
+        // 100c: sub sp, sp, #16
+        // 1010: cmp x1, x2
+        // 1014: b.eq 0x1000
+        // 1018: blr x8
+        // 101c: br x17
+        // 1020: ret
+        let program = raw_program(
+            &[
+                0xb400_0060,
+                0x9400_03ff,
+                0x1400_0002,
+                0xd100_43ff,
+                0xeb02_003f,
+                0x54ff_ff60,
+                0xd63f_0100,
+                0xd61f_0220,
+                0xd65f_03c0,
+            ],
+            &[],
+        );
+        let code = &program.regions()[0];
+        let parser = AArch64Parser::new();
+        let cfg = Cfg::new(&parser, code);
+
+        let starts: Vec<_> = cfg.blocks().map(|block| block.start()).collect();
+        assert_eq!(starts, [0x1000, 0x1004, 0x100c, 0x1010, 0x1018, 0x1020]);
+        assert_eq!(cfg.block_at(0x1008).unwrap().start(), 0x1004);
+        assert!(cfg.block_at(0x1024).is_none());
+
+        let edge = |from, to, kind| Edge { from, to, kind };
+        assert_eq!(
+            cfg.edges(),
+            [
+                edge(0x1000, Some(0x100c), EdgeKind::Taken),
+                edge(0x1000, Some(0x1004), EdgeKind::Fallthrough),
+                edge(0x1004, Some(0x2000), EdgeKind::Call),
+                edge(0x1004, Some(0x1010), EdgeKind::Taken),
+                edge(0x100c, Some(0x1010), EdgeKind::Fallthrough),
+                edge(0x1010, Some(0x1000), EdgeKind::Taken),
+                edge(0x1010, Some(0x1018), EdgeKind::Fallthrough),
+                edge(0x1018, None, EdgeKind::Call),
+                edge(0x1018, None, EdgeKind::Indirect),
+            ]
+        );
+
+        assert_eq!(cfg.successors(0x1004).collect::<Vec<_>>(), [0x1010]);
+        let mut predecessors: Vec<_> = cfg.predecessors(0x1010).collect();
+        predecessors.sort();
+        assert_eq!(predecessors, [0x1004, 0x100c]);
+
+        let dot = cfg.to_dot(&parser, |address| {
+            (address == 0x2000).then(|| "helper".to_string())
+        });
+        assert!(dot.starts_with("digraph cfg {"));
+        assert!(dot.contains("\"0x1000\" [label=\"1000: cbz x0, 0x100c\\l\"];"));
+        assert!(dot.contains("\"ext2\" [label=\"helper\", shape=plaintext];"));
+        assert!(dot.contains("\"0x1004\" -> \"0x1010\" [color=darkgreen];"));
+    }
+
+    #[test]
+    fn jump_table_cfg_test() {
+        let program = raw_program(&switch_code(), &[]);
+        let parser = AArch64Parser::new();
+        let cfg = program.cfg(&parser, &program.regions()[0]);
+
+        for case in [0x1020, 0x1024, 0x1028] {
+            assert!(cfg
+                .block_at(case)
+                .is_some_and(|block| block.start() == case));
+        }
+        let mut successors: Vec<_> = cfg.successors(0x1008).collect();
+        successors.sort();
+        assert_eq!(successors, [0x1020, 0x1024, 0x1028]);
+        assert!(cfg
+            .edges()
+            .iter()
+            .all(|edge| edge.kind != EdgeKind::Indirect || edge.to.is_some()));
+
+        // Without the program's help the branch stays unresolved.
+        let cfg = Cfg::new(&parser, &program.regions()[0]);
+        assert_eq!(cfg.successors(0x1008).count(), 0);
+    }
+
+    #[test]
+    fn function_cfg_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = crate::Program::from_elf(&file_data).unwrap();
+        let parser = AArch64Parser::new();
+
+        for function in program.functions() {
+            let cfg = function.cfg(&parser);
+            let end = function.code.address + function.code.data.len() as u64;
+
+            assert_eq!(cfg.entry(), function.symbol.address);
+            assert_eq!(cfg.blocks().last().map(|block| block.end()), Some(end));
+            for edge in cfg.edges() {
+                assert!(cfg.block_at(edge.from).is_some());
+            }
+        }
+    }
+}
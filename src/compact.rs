@@ -0,0 +1,146 @@
+use crate::aarc64_parser::{decode, DECODER};
+use crate::error::Error;
+use crate::instructions::Unpredictable;
+use crate::record::{InstRecord, Opcode};
+use crate::AArch64Inst;
+
+// `InstRecord` doubles as the compact form of a decoded instruction: it keeps the word
+// itself, and operands are read back out of it through the decoder's encoding tables.
+impl InstRecord {
+    pub fn new(raw: u32) -> Self {
+        let opcode = match decode(raw) {
+            Ok(inst) => inst.opcode(),
+            Err(_) => Opcode::Unknown,
+        };
+
+        InstRecord { raw, opcode }
+    }
+
+    pub fn unpredictable(&self) -> Option<Unpredictable> {
+        match self.to_inst() {
+            Ok(AArch64Inst::UNPREDICTABLE(_, reason)) => Some(reason),
+            _ => None,
+        }
+    }
+
+    pub fn rd(&self) -> Option<u32> {
+        self.register("Rd")
+    }
+
+    pub fn rn(&self) -> Option<u32> {
+        self.register("Rn")
+    }
+
+    pub fn rt(&self) -> Option<u32> {
+        self.register("Rt")
+    }
+
+    pub fn branch_target(&self, address: u64) -> Option<u64> {
+        self.to_inst().ok()?.branch_target(address)
+    }
+
+    // Words that stand for no instruction have no operands either.
+    fn register(&self, name: &str) -> Option<u32> {
+        if matches!(
+            self.opcode,
+            Opcode::UNALLOCATED
+                | Opcode::RESERVED
+                | Opcode::UNDEFINED
+                | Opcode::UNPREDICTABLE
+                | Opcode::Unknown
+        ) {
+            return None;
+        }
+
+        DECODER
+            .fields(self.raw)
+            .filter(|field| field.name == name)
+            .last()
+            .map(|field| field.value)
+    }
+
+    // Fails like the parser does for the one opcode that stands for no instruction.
+    pub fn to_inst(&self) -> Result<AArch64Inst, Error> {
+        match self.opcode {
+            Opcode::Unknown => Err(Error::UnknownInstructionError(self.raw)),
+            _ => decode(self.raw),
+        }
+    }
+}
+
+impl TryFrom<InstRecord> for AArch64Inst {
+    type Error = Error;
+
+    fn try_from(record: InstRecord) -> Result<Self, Error> {
+        record.to_inst()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{AArch64Parser, Program};
+
+    #[test]
+    fn compact_inst_test() {
+        assert!(std::mem::size_of::<InstRecord>() <= 16);
+
+        // Every instruction the decoder knows in the example binary survives a round trip.
+        let parser = AArch64Parser::new();
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        for (_, raw, inst) in program.instructions(&parser) {
+            let Ok(inst) = inst else {
+                continue;
+            };
+            let record = InstRecord::new(raw);
+            assert_eq!(record.raw, raw);
+            assert_eq!(record.opcode, inst.opcode());
+            assert_eq!(
+                format!("{:?}", AArch64Inst::try_from(record).unwrap()),
+                format!("{:?}", inst)
+            );
+        }
+
+        // ldr x17, [x16, #0x18]; b.ne -0x8; str w1, [x2, #-4]!; str x1, [x1, #-8]! (unpredictable,
+        // Rn == Rt); ldr x1, -0x4; ldrsw x2, 0x40
+        for raw in [
+            0xf940_0e11,
+            0x54ff_ffc1,
+            0xb81f_cc41,
+            0xf81f_8c21,
+            0x58ff_ffe1,
+            0x9800_0202,
+        ] {
+            let inst = parser.parse(raw).unwrap();
+            let record = InstRecord::new(raw);
+            assert_eq!(
+                format!("{:?}", record.to_inst().unwrap()),
+                format!("{:?}", inst)
+            );
+        }
+
+        let ldr = InstRecord::new(0xf940_0e11);
+        assert_eq!((ldr.rn(), ldr.rt(), ldr.rd()), (Some(16), Some(17), None));
+        // add x0, x1, x2; br x3; cbz w4, 0
+        assert_eq!(InstRecord::new(0x8b02_0020).rd(), Some(0));
+        assert_eq!(InstRecord::new(0xd61f_0060).rn(), Some(3));
+        assert_eq!(InstRecord::new(0x3400_0004).rt(), Some(4));
+
+        let overlap = InstRecord::new(0xf81f_8c21);
+        assert_eq!(
+            overlap.unpredictable(),
+            Some(Unpredictable::WritebackOverlap)
+        );
+        assert_eq!(overlap.rn(), None);
+
+        // nop, which the decoder does not cover yet
+        let unknown = InstRecord::new(0xd503_201f);
+        assert_eq!(unknown.opcode, Opcode::Unknown);
+        assert!(matches!(
+            unknown.to_inst(),
+            Err(Error::UnknownInstructionError(0xd503_201f))
+        ));
+        assert_eq!(unknown.branch_target(0), None);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::aarc64_parser::AArch64Parser;
+use crate::error::Error;
+use crate::AArch64Inst;
+
+// Decodes a little-endian instruction buffer in place. Indices are in words; a trailing
+// partial word is reported as a `TruncatedInstructionError` in both directions.
+pub struct SliceDecoder<'a, 'data> {
+    parser: &'a AArch64Parser,
+    data: &'data [u8],
+    base: u64,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, 'data> SliceDecoder<'a, 'data> {
+    pub(crate) fn new(parser: &'a AArch64Parser, data: &'data [u8], base: u64) -> Self {
+        Self {
+            parser,
+            data,
+            base,
+            front: 0,
+            back: data.len().div_ceil(4),
+        }
+    }
+
+    pub fn address(&self) -> u64 {
+        self.base.wrapping_add(self.front as u64 * 4)
+    }
+
+    // Moves the front of the iterator to `address`, leaving the back where it was.
+    pub fn seek(&mut self, address: u64) -> Result<(), Error> {
+        if address % 4 != self.base % 4 {
+            return Err(Error::UnalignedAddressError(address));
+        }
+
+        let idx = address
+            .checked_sub(self.base)
+            .map(|offset| (offset / 4) as usize)
+            .filter(|idx| *idx <= self.data.len().div_ceil(4))
+            .ok_or(Error::AddressOutOfRangeError(address))?;
+        self.front = idx;
+
+        Ok(())
+    }
+
+    fn decode(&self, idx: usize) -> (u64, u32, Result<AArch64Inst, Error>) {
+        let address = self.base.wrapping_add(idx as u64 * 4);
+
+        match self.data.get(idx * 4..idx * 4 + 4) {
+            Some(bytes) => {
+                let raw = u32::from_le_bytes(bytes.try_into().unwrap());
+                (address, raw, self.parser.parse(raw))
+            }
+            None => {
+                let len = self.data.len() - idx * 4;
+                (
+                    address,
+                    0,
+                    Err(Error::TruncatedInstructionError(address, len)),
+                )
+            }
+        }
+    }
+}
+
+impl<'a, 'data> Iterator for SliceDecoder<'a, 'data> {
+    type Item = (u64, u32, Result<AArch64Inst, Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.front += 1;
+        Some(self.decode(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back.saturating_sub(self.front);
+        (len, Some(len))
+    }
+}
+
+impl<'a, 'data> DoubleEndedIterator for SliceDecoder<'a, 'data> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(self.decode(self.back))
+    }
+}
+
+impl<'a, 'data> ExactSizeIterator for SliceDecoder<'a, 'data> {}
+
+impl AArch64Parser {
+    pub fn decode_slice<'a, 'data>(
+        &'a self,
+        data: &'data [u8],
+        base_address: u64,
+    ) -> Result<SliceDecoder<'a, 'data>, Error> {
+        if !base_address.is_multiple_of(4) {
+            return Err(Error::UnalignedAddressError(base_address));
+        }
+
+        Ok(SliceDecoder::new(self, data, base_address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_slice_test() {
+        // sub sp, sp, #0x10; b 0x1000; ret; and half of another word
+        let data = [
+            0xff, 0x43, 0x00, 0xd1, 0xff, 0xff, 0xff, 0x17, 0xc0, 0x03, 0x5f, 0xd6, 0xc0, 0x03,
+        ];
+        let parser = AArch64Parser::new();
+
+        let mut decoder = parser.decode_slice(&data, 0x1000).unwrap();
+        assert_eq!(decoder.len(), 4);
+        let (address, raw, inst) = decoder.next().unwrap();
+        assert_eq!((address, raw), (0x1000, 0xd100_43ff));
+        assert!(matches!(inst, Ok(AArch64Inst::SubImmediate64(_))));
+
+        let (address, _, inst) = decoder.next_back().unwrap();
+        assert_eq!(address, 0x100c);
+        assert!(matches!(
+            inst,
+            Err(Error::TruncatedInstructionError(0x100c, 2))
+        ));
+
+        let addresses: Vec<_> = decoder.rev().map(|(address, _, _)| address).collect();
+        assert_eq!(addresses, [0x1008, 0x1004]);
+
+        let mut decoder = parser.decode_slice(&data, 0x1000).unwrap();
+        decoder.seek(0x1008).unwrap();
+        assert_eq!(decoder.address(), 0x1008);
+        assert!(matches!(
+            decoder.next(),
+            Some((0x1008, 0xd65f_03c0, Ok(AArch64Inst::RET(_))))
+        ));
+        assert!(matches!(
+            decoder.seek(0x1006),
+            Err(Error::UnalignedAddressError(0x1006))
+        ));
+        assert!(matches!(
+            decoder.seek(0x2000),
+            Err(Error::AddressOutOfRangeError(0x2000))
+        ));
+
+        assert!(matches!(
+            parser.decode_slice(&data, 0x1002),
+            Err(Error::UnalignedAddressError(0x1002))
+        ));
+    }
+}
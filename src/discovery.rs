@@ -0,0 +1,332 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::aarc64_parser::AArch64Parser;
+use crate::error::Error;
+use crate::program::Program;
+use crate::AArch64Inst;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByteKind {
+    Code,
+    Data,
+    // Not reached by recursive traversal; only produced without gap filling.
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Discovery {
+    // Only follow control flow from known entry points.
+    #[default]
+    Recursive,
+    // Afterwards, sweep linearly over what was not reached and start a new function at
+    // every gap and after invalid words in it, which are data.
+    FillGaps,
+}
+
+// Classification of every byte of the program's code regions.
+pub struct CodeMap {
+    functions: BTreeSet<u64>,
+    regions: Vec<(u64, Vec<ByteKind>)>,
+}
+
+impl CodeMap {
+    pub fn functions(&self) -> impl Iterator<Item = u64> + '_ {
+        self.functions.iter().copied()
+    }
+
+    pub fn is_function(&self, address: u64) -> bool {
+        self.functions.contains(&address)
+    }
+
+    pub fn kind_at(&self, address: u64) -> Option<ByteKind> {
+        self.regions.iter().find_map(|(start, kinds)| {
+            let offset = address.checked_sub(*start)?;
+            kinds.get(offset as usize).copied()
+        })
+    }
+
+    // Maximal runs of bytes of the same kind, in region order.
+    pub fn ranges(&self) -> impl Iterator<Item = (Range<u64>, ByteKind)> + '_ {
+        self.regions.iter().flat_map(|(start, kinds)| {
+            kinds.chunk_by(|a, b| a == b).scan(*start, |next, run| {
+                let range = *next..*next + run.len() as u64;
+                *next = range.end;
+                Some((range, run[0]))
+            })
+        })
+    }
+
+    fn mark(&mut self, address: u64, kind: ByteKind) {
+        for (start, kinds) in &mut self.regions {
+            let Some(offset) = address.checked_sub(*start) else {
+                continue;
+            };
+            let offset = offset as usize;
+            if offset < kinds.len() {
+                let end = (offset + 4).min(kinds.len());
+                kinds[offset..end].fill(kind);
+                return;
+            }
+        }
+    }
+}
+
+impl Program<'_> {
+    // Functions are seeded from the entry point and function symbols, which include
+    // exception table starts, and grow through BL targets.
+    pub fn discover(&self, parser: &AArch64Parser, mode: Discovery) -> CodeMap {
+        let mut map = CodeMap {
+            functions: BTreeSet::new(),
+            regions: self
+                .regions()
+                .iter()
+                .map(|region| (region.address, vec![ByteKind::Unknown; region.data.len()]))
+                .collect(),
+        };
+
+        let seeds = [self.entry()]
+            .into_iter()
+            .chain(self.symbols().functions().map(|symbol| symbol.address))
+            .chain(self.plt_stubs().map(|stub| stub.address));
+        let mut pending: Vec<_> = seeds
+            .filter(|address| self.region_at(*address).is_some())
+            .collect();
+        map.functions.extend(pending.iter().copied());
+        self.traverse(parser, &mut map, &mut pending);
+
+        if mode == Discovery::FillGaps {
+            for region in self.regions() {
+                // Functions start at the beginning of a gap or right after an invalid word
+                // in it, such as padding; anything else is the rest of the preceding one.
+                let mut boundary = true;
+                for (address, _, result) in region.instructions(parser) {
+                    if map.kind_at(address) != Some(ByteKind::Unknown) {
+                        boundary = true;
+                        continue;
+                    }
+
+                    match result {
+                        Ok(inst) if inst.is_invalid() => {
+                            map.mark(address, ByteKind::Data);
+                            boundary = true;
+                        }
+                        Ok(_) | Err(Error::UnknownInstructionError(_)) if boundary => {
+                            map.functions.insert(address);
+                            self.traverse(parser, &mut map, &mut vec![address]);
+                            boundary = false;
+                        }
+                        Ok(_) | Err(Error::UnknownInstructionError(_)) => {
+                            map.mark(address, ByteKind::Code)
+                        }
+                        Err(_) => {
+                            map.mark(address, ByteKind::Data);
+                            boundary = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        map
+    }
+
+    // Words the decoder does not know yet are still code when control flow reaches them;
+    // invalid encodings and leaving the code regions end a path. BRs are followed through
+    // the jump tables they dispatch to, and literal loads mark their pool entry as data.
+    fn traverse(&self, parser: &AArch64Parser, map: &mut CodeMap, pending: &mut Vec<u64>) {
+        while let Some(start) = pending.pop() {
+            let Some(region) = self.region_at(start) else {
+                continue;
+            };
+            let mut decoder = region.instructions(parser);
+            if decoder.seek(start).is_err() {
+                continue;
+            }
+
+            for (address, _, result) in decoder {
+                if map.kind_at(address) == Some(ByteKind::Code) {
+                    break;
+                }
+
+                let inst = match result {
+                    Ok(inst) if inst.is_invalid() => break,
+                    Ok(inst) => inst,
+                    Err(Error::UnknownInstructionError(_)) => {
+                        map.mark(address, ByteKind::Code);
+                        continue;
+                    }
+                    Err(_) => break,
+                };
+                map.mark(address, ByteKind::Code);
+
+                let target = inst
+                    .branch_target(address)
+                    .filter(|target| self.region_at(*target).is_some());
+                if let Some(target) = target {
+                    if matches!(inst, AArch64Inst::BL(_)) {
+                        map.functions.insert(target);
+                    }
+                    pending.push(target);
+                }
+
+                // Literal pools sit between functions and may well decode as instructions.
+                if let Some(literal) = self.literal(address, &inst) {
+                    for word in (literal.address..literal.address + literal.size as u64).step_by(4)
+                    {
+                        if map.kind_at(word) == Some(ByteKind::Unknown) {
+                            map.mark(word, ByteKind::Data);
+                        }
+                    }
+                }
+
+                if let AArch64Inst::BR(_) = inst {
+                    if let Some(table) = self.jump_table(parser, region, address) {
+                        pending.extend(table.targets);
+                    }
+                }
+
+                if matches!(
+                    inst,
+                    AArch64Inst::B(_) | AArch64Inst::BR(_) | AArch64Inst::RET(_)
+                ) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{raw_program, switch_code};
+
+    #[test]
+    fn discover_test() {
+        // 1000: bl 0x1010
+        // 1004: b 0x100c
+        // 1008: .word 0x1234 (literal pool)
+        // 100c: ret
+        // 1010: cbz x0, 0x1018
+        // 1014: ret
+        // 1018: ret
+        // 101c: .word 0
+        // 1020: sub sp, sp, #16 (unreachable)
+        // 1024: ret
+        let program = raw_program(
+            &[
+                0x9400_0004,
+                0x1400_0002,
+                0x0000_1234,
+                0xd65f_03c0,
+                0xb400_0040,
+                0xd65f_03c0,
+                0xd65f_03c0,
+                0x0000_0000,
+                0xd100_43ff,
+                0xd65f_03c0,
+            ],
+            &[0x1000],
+        );
+        let parser = AArch64Parser::new();
+
+        let map = program.discover(&parser, Discovery::Recursive);
+        assert_eq!(map.functions().collect::<Vec<_>>(), [0x1000, 0x1010]);
+        assert_eq!(
+            map.ranges().collect::<Vec<_>>(),
+            [
+                (0x1000..0x1008, ByteKind::Code),
+                (0x1008..0x100c, ByteKind::Unknown),
+                (0x100c..0x101c, ByteKind::Code),
+                (0x101c..0x1028, ByteKind::Unknown),
+            ]
+        );
+        assert_eq!(map.kind_at(0x100a), Some(ByteKind::Unknown));
+        assert_eq!(map.kind_at(0x1028), None);
+
+        let map = program.discover(&parser, Discovery::FillGaps);
+        assert_eq!(
+            map.functions().collect::<Vec<_>>(),
+            [0x1000, 0x1010, 0x1020]
+        );
+        assert_eq!(
+            map.ranges().collect::<Vec<_>>(),
+            [
+                (0x1000..0x1008, ByteKind::Code),
+                (0x1008..0x100c, ByteKind::Data),
+                (0x100c..0x101c, ByteKind::Code),
+                (0x101c..0x1020, ByteKind::Data),
+                (0x1020..0x1028, ByteKind::Code),
+            ]
+        );
+    }
+
+    #[test]
+    fn discover_gaps_unknown_test() {
+        // 1000: ret
+        // 1004: stp x29, x30, [sp, #-16]! (not decoded yet)
+        // 1008: mov x29, sp
+        // 100c: ret
+        let program = raw_program(
+            &[0xd65f_03c0, 0xa9bf_7bfd, 0x9100_03fd, 0xd65f_03c0],
+            &[0x1000],
+        );
+        let map = program.discover(&AArch64Parser::new(), Discovery::FillGaps);
+
+        assert_eq!(map.functions().collect::<Vec<_>>(), [0x1000, 0x1004]);
+        assert_eq!(
+            map.ranges().collect::<Vec<_>>(),
+            [(0x1000..0x1010, ByteKind::Code)]
+        );
+    }
+
+    #[test]
+    fn discover_jump_table_test() {
+        let program = raw_program(&switch_code(), &[0x1000]);
+        let map = program.discover(&AArch64Parser::new(), Discovery::Recursive);
+
+        assert_eq!(map.functions().collect::<Vec<_>>(), [0x1000]);
+        assert_eq!(
+            map.ranges().collect::<Vec<_>>(),
+            [
+                (0x1000..0x101c, ByteKind::Code),
+                (0x101c..0x1020, ByteKind::Unknown),
+                (0x1020..0x1030, ByteKind::Code),
+                (0x1030..0x1034, ByteKind::Unknown),
+            ]
+        );
+    }
+
+    #[test]
+    fn discover_literal_pool_test() {
+        // 1000: ldr w0, 0x1008
+        // 1004: ret
+        // 1008: .word 0xd65f03c0 (a `ret` as data)
+        let program = raw_program(&[0x1800_0040, 0xd65f_03c0, 0xd65f_03c0], &[0x1000]);
+        let map = program.discover(&AArch64Parser::new(), Discovery::FillGaps);
+
+        assert_eq!(map.functions().collect::<Vec<_>>(), [0x1000]);
+        assert_eq!(
+            map.ranges().collect::<Vec<_>>(),
+            [
+                (0x1000..0x1008, ByteKind::Code),
+                (0x1008..0x100c, ByteKind::Data),
+            ]
+        );
+    }
+
+    #[test]
+    fn discover_elf_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        let parser = AArch64Parser::new();
+        let map = program.discover(&parser, Discovery::FillGaps);
+
+        assert!(map.is_function(program.entry()));
+        for function in program.functions() {
+            assert_eq!(map.kind_at(function.code.address), Some(ByteKind::Code));
+        }
+        assert!(map.ranges().all(|(_, kind)| kind != ByteKind::Unknown));
+    }
+}
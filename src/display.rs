@@ -0,0 +1,471 @@
+use core::fmt;
+
+use crate::utils::sign_extend;
+use crate::AArch64Inst;
+
+const CONDITIONS: [&str; 16] = [
+    "eq", "ne", "hs", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "al", "nv",
+];
+
+const SHIFTS: [&str; 4] = ["lsl", "lsr", "asr", "ror"];
+
+const EXTENDS: [&str; 8] = [
+    "uxtb", "uxth", "uxtw", "uxtx", "sxtb", "sxth", "sxtw", "sxtx",
+];
+
+// Assembly text for one instruction in LLVM syntax. Branch and PC-relative operands are
+// printed as absolute addresses, which is why the instruction's own address is needed.
+pub struct Disassembly<'a> {
+    inst: &'a AArch64Inst,
+    address: u64,
+    aliases: bool,
+}
+
+pub struct Mnemonic<'a>(&'a Disassembly<'a>);
+
+pub struct Operands<'a>(&'a Disassembly<'a>);
+
+impl AArch64Inst {
+    pub fn display(&self, address: u64) -> Disassembly<'_> {
+        Disassembly {
+            inst: self,
+            address,
+            aliases: true,
+        }
+    }
+}
+
+impl<'a> Disassembly<'a> {
+    // Preferred aliases (`mov`, `cmp`, `negs`, a bare `ret`) are used unless turned off.
+    pub fn aliases(mut self, aliases: bool) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    pub fn mnemonic(&'a self) -> Mnemonic<'a> {
+        Mnemonic(self)
+    }
+
+    pub fn operands(&'a self) -> Operands<'a> {
+        Operands(self)
+    }
+
+    fn alias(&self) -> Option<&'static str> {
+        if !self.aliases {
+            return None;
+        }
+
+        match self.inst {
+            AArch64Inst::AddImmediate64(data)
+                if data.imm12 == 0 && data.sh == 0 && (data.rd == 31 || data.rn == 31) =>
+            {
+                Some("mov")
+            }
+            AArch64Inst::SUBSImmediate32(data) | AArch64Inst::SUBSImmediate64(data)
+                if data.rd == 31 =>
+            {
+                Some("cmp")
+            }
+            // A shifted zero has no MOV spelling, nor has an all-ones W register.
+            AArch64Inst::MOVZ32(data) | AArch64Inst::MOVZ64(data) | AArch64Inst::MOVN64(data)
+                if data.imm16 != 0 || data.hw == 0 =>
+            {
+                Some("mov")
+            }
+            AArch64Inst::MOVN32(data)
+                if (data.imm16 != 0 || data.hw == 0) && data.imm16 != 0xffff =>
+            {
+                Some("mov")
+            }
+            AArch64Inst::SUBSShiftedRegister64(data) if data.rd == 31 => Some("cmp"),
+            AArch64Inst::SUBSShiftedRegister64(data) if data.rn == 31 => Some("negs"),
+            _ => None,
+        }
+    }
+
+    fn write_mnemonic(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(alias) = self.alias() {
+            return f.write_str(alias);
+        }
+
+        let mnemonic = match self.inst {
+            AArch64Inst::ADR(_) => "adr",
+            AArch64Inst::ADRP(_) => "adrp",
+            AArch64Inst::AddImmediate64(_) => "add",
+            AArch64Inst::SubImmediate64(_) => "sub",
+            AArch64Inst::SUBSImmediate32(_) | AArch64Inst::SUBSImmediate64(_) => "subs",
+            AArch64Inst::MOVN32(_) | AArch64Inst::MOVN64(_) => "movn",
+            AArch64Inst::MOVZ32(_) | AArch64Inst::MOVZ64(_) => "movz",
+            AArch64Inst::MOVK32(_) | AArch64Inst::MOVK64(_) => "movk",
+            AArch64Inst::BCond(data) => return write!(f, "b.{}", CONDITIONS[data.cond as usize]),
+            AArch64Inst::BcCond(data) => return write!(f, "bc.{}", CONDITIONS[data.cond as usize]),
+            AArch64Inst::B(_) => "b",
+            AArch64Inst::BL(_) => "bl",
+            AArch64Inst::CBZ32(_) | AArch64Inst::CBZ64(_) => "cbz",
+            AArch64Inst::CBNZ32(_) | AArch64Inst::CBNZ64(_) => "cbnz",
+            AArch64Inst::TBZ(_) => "tbz",
+            AArch64Inst::TBNZ(_) => "tbnz",
+            AArch64Inst::BR(_) => "br",
+            AArch64Inst::BLR(_) => "blr",
+            AArch64Inst::RET(_) => "ret",
+            AArch64Inst::STRImmediate64(_)
+            | AArch64Inst::STRImmediatePreIndexed32(_)
+            | AArch64Inst::STRImmediatePreIndexed64(_)
+            | AArch64Inst::STRImmediatePostIndexed32(_)
+            | AArch64Inst::STRImmediatePostIndexed64(_) => "str",
+            AArch64Inst::LDRImmediate64(_)
+            | AArch64Inst::LDRImmediatePreIndexed32(_)
+            | AArch64Inst::LDRImmediatePreIndexed64(_)
+            | AArch64Inst::LDRImmediatePostIndexed32(_)
+            | AArch64Inst::LDRImmediatePostIndexed64(_)
+            | AArch64Inst::LDRRegister32(_)
+            | AArch64Inst::LDRRegister64(_)
+            | AArch64Inst::LDRLiteral32(_)
+            | AArch64Inst::LDRLiteral64(_) => "ldr",
+            AArch64Inst::LDRBRegister(_) => "ldrb",
+            AArch64Inst::LDRHRegister(_) => "ldrh",
+            AArch64Inst::LDRSWRegister(_) | AArch64Inst::LDRSWLiteral(_) => "ldrsw",
+            AArch64Inst::ADDShiftedRegister64(_) | AArch64Inst::ADDExtendedRegister64(_) => "add",
+            AArch64Inst::SUBSShiftedRegister64(_) => "subs",
+            AArch64Inst::UNDEFINED(raw) if raw >> 16 == 0 => "udf",
+            AArch64Inst::UNALLOCATED(_)
+            | AArch64Inst::RESERVED(_)
+            | AArch64Inst::UNDEFINED(_)
+            | AArch64Inst::UNPREDICTABLE(..) => ".inst",
+        };
+
+        f.write_str(mnemonic)
+    }
+
+    fn write_operands(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let alias = self.alias();
+
+        match self.inst {
+            AArch64Inst::ADR(data) | AArch64Inst::ADRP(data) => {
+                let target = self.inst.pc_rel_address(self.address).unwrap();
+                write!(f, "{}, {:#x}", reg(data.rd, true, false), target)
+            }
+            AArch64Inst::LDRLiteral32(data)
+            | AArch64Inst::LDRLiteral64(data)
+            | AArch64Inst::LDRSWLiteral(data) => {
+                let wide = !matches!(self.inst, AArch64Inst::LDRLiteral32(_));
+                let target = self.inst.pc_rel_address(self.address).unwrap();
+                write!(f, "{}, {:#x}", reg(data.rt, wide, false), target)
+            }
+            AArch64Inst::AddImmediate64(data) | AArch64Inst::SubImmediate64(data) => {
+                let (rd, rn) = (reg(data.rd, true, true), reg(data.rn, true, true));
+                match (alias, data.sh) {
+                    (Some(_), _) => write!(f, "{}, {}", rd, rn),
+                    (None, 0) => write!(f, "{}, {}, #{}", rd, rn, data.imm12),
+                    (None, _) => write!(f, "{}, {}, #{}, lsl #12", rd, rn, data.imm12),
+                }
+            }
+            AArch64Inst::SUBSImmediate32(data) | AArch64Inst::SUBSImmediate64(data) => {
+                let wide = data.sf == 1;
+                let (rd, rn) = (reg(data.rd, wide, false), reg(data.rn, wide, true));
+                match alias {
+                    Some(_) => write!(f, "{}, #{}", rn, data.imm12)?,
+                    None => write!(f, "{}, {}, #{}", rd, rn, data.imm12)?,
+                }
+                match data.sh {
+                    0 => Ok(()),
+                    _ => f.write_str(", lsl #12"),
+                }
+            }
+            AArch64Inst::MOVN32(data)
+            | AArch64Inst::MOVZ32(data)
+            | AArch64Inst::MOVK32(data)
+            | AArch64Inst::MOVN64(data)
+            | AArch64Inst::MOVZ64(data)
+            | AArch64Inst::MOVK64(data) => {
+                let rd = reg(data.rd, data.sf == 1, false);
+                // The MOV alias shows the value the register ends up with.
+                let value = match self.inst {
+                    AArch64Inst::MOVN32(_) | AArch64Inst::MOVN64(_) => !data.imm(),
+                    _ => data.imm(),
+                };
+                match (alias, data.sf) {
+                    (Some(_), 0) => write!(f, "{}, #{}", rd, value as u32 as i32),
+                    (Some(_), _) => write!(f, "{}, #{}", rd, value as i64),
+                    (None, _) if data.hw == 0 => write!(f, "{}, #{}", rd, data.imm16),
+                    (None, _) => write!(f, "{}, #{}, lsl #{}", rd, data.imm16, data.shift()),
+                }
+            }
+            AArch64Inst::BCond(_)
+            | AArch64Inst::BcCond(_)
+            | AArch64Inst::B(_)
+            | AArch64Inst::BL(_) => write!(f, "{:#x}", self.target()),
+            AArch64Inst::CBZ32(data) | AArch64Inst::CBNZ32(data) => {
+                write!(f, "{}, {:#x}", reg(data.rt, false, false), self.target())
+            }
+            AArch64Inst::CBZ64(data) | AArch64Inst::CBNZ64(data) => {
+                write!(f, "{}, {:#x}", reg(data.rt, true, false), self.target())
+            }
+            AArch64Inst::TBZ(data) | AArch64Inst::TBNZ(data) => write!(
+                f,
+                "{}, #{}, {:#x}",
+                reg(data.rt, data.b5 == 1, false),
+                data.b5 << 5 | data.b40,
+                self.target()
+            ),
+            AArch64Inst::BR(data) | AArch64Inst::BLR(data) => {
+                write!(f, "{}", reg(data.rn, true, false))
+            }
+            AArch64Inst::RET(data) if data.rn == 30 && self.aliases => Ok(()),
+            AArch64Inst::RET(data) => write!(f, "{}", reg(data.rn, true, false)),
+            AArch64Inst::STRImmediate64(data) | AArch64Inst::LDRImmediate64(data) => {
+                let (rt, rn) = (reg(data.rt, true, false), reg(data.rn, true, true));
+                match data.offset() {
+                    0 => write!(f, "{}, [{}]", rt, rn),
+                    offset => write!(f, "{}, [{}, #{}]", rt, rn, offset),
+                }
+            }
+            AArch64Inst::STRImmediatePreIndexed32(data)
+            | AArch64Inst::LDRImmediatePreIndexed32(data) => write!(
+                f,
+                "{}, [{}, #{}]!",
+                reg(data.rt, false, false),
+                reg(data.rn, true, true),
+                sign_extend(data.imm9, 9)
+            ),
+            AArch64Inst::STRImmediatePreIndexed64(data)
+            | AArch64Inst::LDRImmediatePreIndexed64(data) => write!(
+                f,
+                "{}, [{}, #{}]!",
+                reg(data.rt, true, false),
+                reg(data.rn, true, true),
+                sign_extend(data.imm9, 9)
+            ),
+            AArch64Inst::STRImmediatePostIndexed32(data)
+            | AArch64Inst::LDRImmediatePostIndexed32(data) => write!(
+                f,
+                "{}, [{}], #{}",
+                reg(data.rt, false, false),
+                reg(data.rn, true, true),
+                sign_extend(data.imm9, 9)
+            ),
+            AArch64Inst::STRImmediatePostIndexed64(data)
+            | AArch64Inst::LDRImmediatePostIndexed64(data) => write!(
+                f,
+                "{}, [{}], #{}",
+                reg(data.rt, true, false),
+                reg(data.rn, true, true),
+                sign_extend(data.imm9, 9)
+            ),
+            AArch64Inst::LDRBRegister(data)
+            | AArch64Inst::LDRHRegister(data)
+            | AArch64Inst::LDRRegister32(data)
+            | AArch64Inst::LDRRegister64(data)
+            | AArch64Inst::LDRSWRegister(data) => {
+                let wide = matches!(
+                    self.inst,
+                    AArch64Inst::LDRRegister64(_) | AArch64Inst::LDRSWRegister(_)
+                );
+                write!(
+                    f,
+                    "{}, [{}, {}",
+                    reg(data.rt, wide, false),
+                    reg(data.rn, true, true),
+                    reg(data.rm, data.option & 1 == 1, false)
+                )?;
+                match (data.option, data.s) {
+                    (0b011, 0) => {}
+                    (0b011, _) => write!(f, ", lsl #{}", data.shift())?,
+                    (option, 0) => write!(f, ", {}", EXTENDS[option as usize])?,
+                    (option, _) => write!(f, ", {} #{}", EXTENDS[option as usize], data.shift())?,
+                }
+                f.write_str("]")
+            }
+            AArch64Inst::ADDShiftedRegister64(data) => {
+                write!(
+                    f,
+                    "{}, {}, {}",
+                    reg(data.rd, true, false),
+                    reg(data.rn, true, false),
+                    reg(data.rm, true, false)
+                )?;
+                match (data.shift, data.imm6) {
+                    (0, 0) => Ok(()),
+                    (shift, amount) => write!(f, ", {} #{}", SHIFTS[shift as usize], amount),
+                }
+            }
+            AArch64Inst::ADDExtendedRegister64(data) => {
+                write!(
+                    f,
+                    "{}, {}, {}",
+                    reg(data.rd, true, true),
+                    reg(data.rn, true, true),
+                    reg(data.rm, data.option & 0b011 == 0b011, false)
+                )?;
+                // UXTX next to the stack pointer is written as LSL.
+                let lsl = data.option == 0b011 && (data.rd == 31 || data.rn == 31);
+                match (lsl, data.imm3) {
+                    (true, 0) => Ok(()),
+                    (true, amount) => write!(f, ", lsl #{}", amount),
+                    (false, 0) => write!(f, ", {}", EXTENDS[data.option as usize]),
+                    (false, amount) => {
+                        write!(f, ", {} #{}", EXTENDS[data.option as usize], amount)
+                    }
+                }
+            }
+            AArch64Inst::SUBSShiftedRegister64(data) => {
+                let (rd, rn, rm) = (
+                    reg(data.rd, true, false),
+                    reg(data.rn, true, false),
+                    reg(data.rm, true, false),
+                );
+                match alias {
+                    Some("cmp") => write!(f, "{}, {}", rn, rm)?,
+                    Some(_) => write!(f, "{}, {}", rd, rm)?,
+                    None => write!(f, "{}, {}, {}", rd, rn, rm)?,
+                }
+                match (data.shift, data.imm6) {
+                    (0, 0) => Ok(()),
+                    (shift, amount) => write!(f, ", {} #{}", SHIFTS[shift as usize], amount),
+                }
+            }
+            AArch64Inst::UNDEFINED(raw) if raw >> 16 == 0 => write!(f, "#{}", raw),
+            AArch64Inst::UNALLOCATED(raw)
+            | AArch64Inst::RESERVED(raw)
+            | AArch64Inst::UNDEFINED(raw)
+            | AArch64Inst::UNPREDICTABLE(raw, _) => write!(f, "{:#010x}", raw),
+        }
+    }
+
+    fn target(&self) -> u64 {
+        self.inst.branch_target(self.address).unwrap()
+    }
+}
+
+impl fmt::Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_mnemonic(f)?;
+
+        match self.inst {
+            AArch64Inst::RET(data) if data.rn == 30 && self.aliases => Ok(()),
+            _ => {
+                f.write_str(" ")?;
+                self.write_operands(f)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Mnemonic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_mnemonic(f)
+    }
+}
+
+impl fmt::Display for Operands<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_operands(f)
+    }
+}
+
+struct Reg {
+    num: u32,
+    wide: bool,
+    sp: bool,
+}
+
+// Register 31 is the stack pointer or the zero register depending on the operand.
+fn reg(num: u32, wide: bool, sp: bool) -> Reg {
+    Reg { num, wide, sp }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.num, self.wide, self.sp) {
+            (31, true, true) => f.write_str("sp"),
+            (31, false, true) => f.write_str("wsp"),
+            (31, true, false) => f.write_str("xzr"),
+            (31, false, false) => f.write_str("wzr"),
+            (num, true, _) => write!(f, "x{}", num),
+            (num, false, _) => write!(f, "w{}", num),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aarc64_parser::decode;
+
+    // Expected text from llvm-objdump, except that ADR targets are absolute.
+    #[test]
+    fn display_test() {
+        let cases = [
+            (0x1000_0080, 0x0, "adr x0, 0x10"),
+            (0x9000_0001, 0x1004, "adrp x1, 0x1000"),
+            (0x9140_0420, 0xc, "add x0, x1, #1, lsl #12"),
+            (0x9100_003f, 0x10, "mov sp, x1"),
+            (0xd100_43ff, 0x18, "sub sp, sp, #16"),
+            (0x5400_0042, 0x20, "b.hs 0x28"),
+            (0x97ff_ffff, 0x2c, "bl 0x28"),
+            (0x3400_0040, 0x30, "cbz w0, 0x38"),
+            (0xb740_0042, 0x3c, "tbnz x2, #40, 0x44"),
+            (0xd63f_0100, 0x44, "blr x8"),
+            (0xd65f_03c0, 0x48, "ret"),
+            (0xd65f_0020, 0x4c, "ret x1"),
+            (0xf900_03e0, 0x54, "str x0, [sp]"),
+            (0xf81f_0ffd, 0x58, "str x29, [sp, #-16]!"),
+            (0xb85f_c420, 0x60, "ldr w0, [x1], #-4"),
+            (0xeb02_0c20, 0x6c, "subs x0, x1, x2, lsl #3"),
+            (0xeb82_083f, 0x70, "cmp x1, x2, asr #2"),
+            (0xeb02_03e0, 0x74, "negs x0, x2"),
+            (0x7100_111f, 0x78, "cmp w8, #4"),
+            (0x7100_1020, 0x78, "subs w0, w1, #4"),
+            (0xf140_041f, 0x78, "cmp x0, #1, lsl #12"),
+            (0xf100_23e0, 0x78, "subs x0, sp, #8"),
+            (0x8b09_0108, 0x78, "add x8, x8, x9"),
+            (0x8b0b_094a, 0x78, "add x10, x10, x11, lsl #2"),
+            (0x8b20_8820, 0x78, "add x0, x1, w0, sxtb #2"),
+            (0x8b21_63ff, 0x78, "add sp, sp, x1"),
+            (0x8b22_4020, 0x78, "add x0, x1, w2, uxtw"),
+            (0x8b24_67e3, 0x78, "add x3, sp, x4, lsl #1"),
+            (0x3868_692b, 0x78, "ldrb w11, [x9, x8]"),
+            (0x7862_7820, 0x78, "ldrh w0, [x1, x2, lsl #1]"),
+            (0xb8ab_7949, 0x78, "ldrsw x9, [x10, x11, lsl #2]"),
+            (0xb862_5820, 0x78, "ldr w0, [x1, w2, uxtw #2]"),
+            (0xf862_7820, 0x78, "ldr x0, [x1, x2, lsl #3]"),
+            (0x3862_c820, 0x78, "ldrb w0, [x1, w2, sxtw]"),
+            (0x3862_7820, 0x78, "ldrb w0, [x1, x2, lsl #0]"),
+            (0x7862_4be0, 0x78, "ldrh w0, [sp, w2, uxtw]"),
+            (0x1800_0040, 0x7c, "ldr w0, 0x84"),
+            (0x58ff_ffe1, 0x7c, "ldr x1, 0x78"),
+            (0x9800_0202, 0x7c, "ldrsw x2, 0xbc"),
+            (0xd282_4680, 0x7c, "mov x0, #4660"),
+            (0xd2a2_4680, 0x7c, "mov x0, #305397760"),
+            (0x52a0_0001, 0x7c, "movz w1, #0, lsl #16"),
+            (0xf2aa_cf00, 0x7c, "movk x0, #22136, lsl #16"),
+            (0x7280_0202, 0x7c, "movk w2, #16"),
+            (0x9280_0003, 0x7c, "mov x3, #-1"),
+            (0x12a0_00a4, 0x7c, "mov w4, #-327681"),
+            (0x92bf_ffe5, 0x7c, "mov x5, #-4294901761"),
+            (0x129f_ffe7, 0x7c, "movn w7, #65535"),
+            (0x52b0_0000, 0x7c, "mov w0, #-2147483648"),
+            (0x92f0_0000, 0x7c, "mov x0, #9223372036854775807"),
+            (0x0000_1234, 0x7c, "udf #4660"),
+            (0x0200_0000, 0x80, ".inst 0x02000000"),
+        ];
+
+        for (raw, address, expected) in cases {
+            let inst = decode(raw).unwrap();
+            assert_eq!(inst.display(address).to_string(), expected);
+        }
+
+        let inst = decode(0x9100_003f).unwrap();
+        let display = inst.display(0).aliases(false);
+        assert_eq!(display.to_string(), "add sp, x1, #0");
+        assert_eq!(display.mnemonic().to_string(), "add");
+        assert_eq!(display.operands().to_string(), "sp, x1, #0");
+        assert_eq!(
+            decode(0xd65f_03c0)
+                .unwrap()
+                .display(0)
+                .aliases(false)
+                .to_string(),
+            "ret x30"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use crate::utils::{read_sleb128, read_uleb128};
+
+// DW_EH_PE_* pointer encodings: the low nibble is the format, the high one what it is
+// relative to.
+const DW_EH_PE_OMIT: u8 = 0xff;
+const DW_EH_PE_PCREL: u8 = 0x10;
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> Cursor<'a> {
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.data.get(self.pos..self.pos + N)?.try_into().ok()?;
+        self.pos += N;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes::<1>().map(|[byte]| byte)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.bytes()?;
+        Some(match self.big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.bytes()?;
+        Some(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let bytes = self.bytes()?;
+        Some(match self.big_endian {
+            true => u64::from_be_bytes(bytes),
+            false => u64::from_le_bytes(bytes),
+        })
+    }
+
+    fn uleb128(&mut self) -> Option<u64> {
+        let (value, len) = read_uleb128(self.data.get(self.pos..)?)?;
+        self.pos += len;
+        Some(value)
+    }
+
+    fn sleb128(&mut self) -> Option<i64> {
+        let (value, len) = read_sleb128(self.data.get(self.pos..)?)?;
+        self.pos += len;
+        Some(value)
+    }
+
+    fn string(&mut self) -> Option<&'a [u8]> {
+        let len = self
+            .data
+            .get(self.pos..)?
+            .iter()
+            .position(|byte| *byte == 0)?;
+        let string = &self.data[self.pos..self.pos + len];
+        self.pos += len + 1;
+        Some(string)
+    }
+
+    // `base` is the address of the section start, for pc-relative pointers.
+    fn pointer(&mut self, encoding: u8, base: u64) -> Option<u64> {
+        let address = base + self.pos as u64;
+        let value = match encoding & 0x0f {
+            0x0 | 0x4 | 0xc => self.u64()?,
+            0x1 => self.uleb128()?,
+            0x2 => self.u16()? as u64,
+            0x3 => self.u32()? as u64,
+            0x9 => self.sleb128()? as u64,
+            0xa => self.u16()? as i16 as u64,
+            0xb => self.u32()? as i32 as u64,
+            _ => return None,
+        };
+
+        match encoding & 0x70 {
+            0 => Some(value),
+            DW_EH_PE_PCREL => Some(address.wrapping_add(value)),
+            // Text- and data-relative bases are not known from the section alone.
+            _ => None,
+        }
+    }
+}
+
+// Start address and length of every function an FDE in `.eh_frame` covers.
+pub(crate) fn fde_ranges(data: &[u8], address: u64, big_endian: bool) -> Vec<(u64, u64)> {
+    let mut cursor = Cursor {
+        data,
+        pos: 0,
+        big_endian,
+    };
+    let mut encodings = HashMap::new();
+    let mut ranges = Vec::new();
+
+    while cursor.pos < data.len() {
+        let start = cursor.pos;
+        let Some(len) = cursor.u32() else {
+            break;
+        };
+        let len = match len {
+            0 => break,
+            0xffff_ffff => match cursor.u64() {
+                Some(len) => len as usize,
+                None => break,
+            },
+            len => len as usize,
+        };
+        let Some(end) = cursor
+            .pos
+            .checked_add(len)
+            .filter(|end| *end > start && *end <= data.len())
+        else {
+            break;
+        };
+
+        let id_pos = cursor.pos;
+        match cursor.u32() {
+            Some(0) => {
+                if let Some(encoding) = cie_pointer_encoding(&mut cursor, address) {
+                    encodings.insert(start, encoding);
+                }
+            }
+            // The CIE pointer counts back from its own position.
+            Some(cie) => {
+                let encoding = id_pos
+                    .checked_sub(cie as usize)
+                    .and_then(|cie| encodings.get(&cie));
+                if let Some(encoding) = encoding {
+                    let begin = cursor.pointer(*encoding, address);
+                    let len = cursor.pointer(*encoding & 0x0f, address);
+                    if let (Some(begin), Some(len)) = (begin, len) {
+                        ranges.push((begin, len));
+                    }
+                }
+            }
+            None => break,
+        }
+
+        cursor.pos = end;
+    }
+
+    ranges
+}
+
+// The FDE pointer encoding from a CIE's augmentation, after the id has been read.
+fn cie_pointer_encoding(cursor: &mut Cursor, address: u64) -> Option<u8> {
+    let version = cursor.u8()?;
+    let augmentation = cursor.string()?;
+    if augmentation.starts_with(b"eh") {
+        cursor.u64()?;
+    }
+    if version >= 4 {
+        cursor.u8()?;
+        cursor.u8()?;
+    }
+    cursor.uleb128()?;
+    cursor.sleb128()?;
+    match version {
+        1 => cursor.u8().map(u64::from)?,
+        _ => cursor.uleb128()?,
+    };
+
+    let mut encoding = 0;
+    if augmentation.first() != Some(&b'z') {
+        return Some(encoding);
+    }
+    cursor.uleb128()?;
+    for byte in &augmentation[1..] {
+        match byte {
+            b'R' => encoding = cursor.u8()?,
+            b'L' => {
+                cursor.u8()?;
+            }
+            b'P' => {
+                let personality = cursor.u8()?;
+                if personality != DW_EH_PE_OMIT {
+                    // Indirect or data-relative personalities still have a size to skip.
+                    cursor.pointer(personality & 0x0f, address)?;
+                }
+            }
+            b'S' | b'B' => {}
+            _ => return None,
+        }
+    }
+
+    Some(encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::eh_frame;
+
+    #[test]
+    fn fde_ranges_test() {
+        let data = eh_frame(0x2000, &[(0x1000, 0x10), (0x1010, 0x8)]);
+        assert_eq!(
+            fde_ranges(&data, 0x2000, false),
+            [(0x1000, 0x10), (0x1010, 0x8)]
+        );
+
+        // A truncated FDE is dropped without losing the ones before it.
+        assert_eq!(fde_ranges(&data[..50], 0x2000, false), [(0x1000, 0x10)]);
+        assert!(fde_ranges(&[], 0, false).is_empty());
+
+        // A 64-bit length running past the end of the address space.
+        let mut huge = data[..data.len() - 4].to_vec();
+        huge.extend([0xff; 12]);
+        assert_eq!(
+            fde_ranges(&huge, 0x2000, false),
+            [(0x1000, 0x10), (0x1010, 0x8)]
+        );
+    }
+}
@@ -2,6 +2,35 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("Unknown instruction")]
-    UnknownInstructionError,
+    #[error("Unknown instruction: {0:#010x}")]
+    UnknownInstructionError(u32),
+
+    #[error("Truncated instruction at {0:#x}: only {1} of 4 bytes present")]
+    TruncatedInstructionError(u64, usize),
+
+    #[error("Address {0:#x} is not word aligned")]
+    UnalignedAddressError(u64),
+
+    #[error("Address {0:#x} is out of range")]
+    AddressOutOfRangeError(u64),
+
+    #[cfg(feature = "std")]
+    #[error("Failed to parse ELF file: {0}")]
+    ElfParseError(#[from] elf::ParseError),
+
+    #[error("Unsupported machine type: {0}")]
+    UnsupportedMachineError(u16),
+
+    #[cfg(feature = "std")]
+    #[error("Failed to parse object file: {0}")]
+    ObjectParseError(#[from] goblin::error::Error),
+
+    #[error("Unsupported CPU type: {0:#x}")]
+    UnsupportedCpuTypeError(u32),
+
+    #[error("Invalid record on line {0}")]
+    InvalidRecordError(usize),
+
+    #[error("Checksum mismatch on line {0}")]
+    ChecksumError(usize),
 }
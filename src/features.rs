@@ -0,0 +1,120 @@
+use core::ops::BitOr;
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Feature {
+    ArmV8_0,
+    FEAT_FlagM,
+    FEAT_HBC,
+    FEAT_LRCPC2,
+    FEAT_LSE,
+    FEAT_MOPS,
+    FEAT_MTE,
+    FEAT_PAuth,
+    FEAT_SME,
+    FEAT_SVE,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 10] = [
+        Feature::ArmV8_0,
+        Feature::FEAT_FlagM,
+        Feature::FEAT_HBC,
+        Feature::FEAT_LRCPC2,
+        Feature::FEAT_LSE,
+        Feature::FEAT_MOPS,
+        Feature::FEAT_MTE,
+        Feature::FEAT_PAuth,
+        Feature::FEAT_SME,
+        Feature::FEAT_SVE,
+    ];
+
+    const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FeatureSet(u32);
+
+impl FeatureSet {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn all() -> Self {
+        let mut bits = 0;
+        let mut idx = 0;
+        while idx < Feature::ALL.len() {
+            bits |= Feature::ALL[idx].bit();
+            idx += 1;
+        }
+
+        Self(bits)
+    }
+
+    pub const fn contains(&self, feature: Feature) -> bool {
+        self.0 & feature.bit() != 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Feature> + '_ {
+        Feature::ALL.into_iter().filter(|feat| self.contains(*feat))
+    }
+}
+
+impl Default for FeatureSet {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl From<Feature> for FeatureSet {
+    fn from(feature: Feature) -> Self {
+        Self(feature.bit())
+    }
+}
+
+impl BitOr for FeatureSet {
+    type Output = FeatureSet;
+
+    fn bitor(self, rhs: FeatureSet) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOr<Feature> for FeatureSet {
+    type Output = FeatureSet;
+
+    fn bitor(self, rhs: Feature) -> Self::Output {
+        Self(self.0 | rhs.bit())
+    }
+}
+
+impl BitOr for Feature {
+    type Output = FeatureSet;
+
+    fn bitor(self, rhs: Feature) -> Self::Output {
+        FeatureSet::from(self) | rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Feature::*;
+    use super::*;
+
+    #[test]
+    fn feature_set_test() {
+        let set = ArmV8_0 | FEAT_LSE;
+
+        assert!(set.contains(ArmV8_0));
+        assert!(set.contains(FEAT_LSE));
+        assert!(!set.contains(FEAT_MOPS));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![ArmV8_0, FEAT_LSE]);
+
+        assert!(Feature::ALL
+            .iter()
+            .all(|feat| FeatureSet::all().contains(*feat)));
+        assert!(!FeatureSet::empty().contains(ArmV8_0));
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::Error;
+use crate::program::{CodeRegion, Program};
+use crate::symbols::{Symbol, SymbolKind, SymbolTable};
+
+// Sparse memory contents reconstructed from a record based firmware format.
+#[derive(Debug, Default)]
+pub struct MemoryImage {
+    chunks: BTreeMap<u64, Vec<u8>>,
+    start_address: Option<u64>,
+}
+
+impl MemoryImage {
+    pub fn from_ihex(text: &str) -> Result<Self, Error> {
+        let mut image = Self::default();
+        let mut base = 0u64;
+
+        for (line, record) in records(text) {
+            let bytes = record
+                .strip_prefix(':')
+                .and_then(parse_hex)
+                .ok_or(Error::InvalidRecordError(line))?;
+            if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+                return Err(Error::InvalidRecordError(line));
+            }
+            if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+                return Err(Error::ChecksumError(line));
+            }
+
+            let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as u64;
+            let data = &bytes[4..bytes.len() - 1];
+            match (bytes[3], data.len()) {
+                (0x00, _) => image.write(base + offset, data),
+                (0x01, _) => break,
+                (0x02, 2) => base = (be(data) as u64) << 4,
+                (0x03, 4) => {
+                    let (cs, ip) = (be(&data[..2]) as u64, be(&data[2..]) as u64);
+                    image.start_address = Some((cs << 4) + ip);
+                }
+                (0x04, 2) => base = (be(data) as u64) << 16,
+                (0x05, 4) => image.start_address = Some(be(data) as u64),
+                _ => return Err(Error::InvalidRecordError(line)),
+            }
+        }
+
+        Ok(image)
+    }
+
+    pub fn from_srec(text: &str) -> Result<Self, Error> {
+        let mut image = Self::default();
+
+        for (line, record) in records(text) {
+            let (kind, bytes) = match record
+                .strip_prefix('S')
+                .and_then(|rest| rest.split_at_checked(1))
+            {
+                Some((kind, hex)) if !hex.is_empty() => (kind, parse_hex(hex)),
+                _ => (record, None),
+            };
+            let bytes = bytes.ok_or(Error::InvalidRecordError(line))?;
+            if bytes.is_empty() || bytes.len() != bytes[0] as usize + 1 {
+                return Err(Error::InvalidRecordError(line));
+            }
+            let (checksum, rest) = bytes.split_last().unwrap();
+            if !rest.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != *checksum {
+                return Err(Error::ChecksumError(line));
+            }
+
+            let address_len = match kind {
+                "0" | "1" | "5" | "9" => 2,
+                "2" | "6" | "8" => 3,
+                "3" | "7" => 4,
+                _ => return Err(Error::InvalidRecordError(line)),
+            };
+            if rest.len() < address_len + 1 {
+                return Err(Error::InvalidRecordError(line));
+            }
+            let address = be(&rest[1..address_len + 1]) as u64;
+            let data = &rest[address_len + 1..];
+
+            match kind {
+                "1" | "2" | "3" => image.write(address, data),
+                "7" | "8" | "9" => image.start_address = Some(address),
+                // S0 header and S5/S6 record counts carry nothing to load.
+                _ => {}
+            }
+        }
+
+        Ok(image)
+    }
+
+    pub fn start_address(&self) -> Option<u64> {
+        self.start_address
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.chunks
+            .iter()
+            .map(|(address, data)| (*address, data.as_slice()))
+    }
+
+    // Records usually arrive in order, so consecutive ones are appended to the same chunk.
+    // Chunks a record overlaps or touches are merged with it, and the later data wins.
+    fn write(&mut self, address: u64, data: &[u8]) {
+        let end = address + data.len() as u64;
+        let touching: Vec<_> = self
+            .chunks
+            .range(..=end)
+            .rev()
+            .take_while(|(start, chunk)| **start + chunk.len() as u64 >= address)
+            .map(|(start, _)| *start)
+            .collect();
+
+        let (start, mut merged) = match touching.last() {
+            Some(first) if *first <= address => (*first, self.chunks.remove(first).unwrap()),
+            _ => (address, Vec::new()),
+        };
+        let mut put = |at: u64, bytes: &[u8]| {
+            let offset = (at - start) as usize;
+            if merged.len() < offset + bytes.len() {
+                merged.resize(offset + bytes.len(), 0);
+            }
+            merged[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        for other in touching.iter().filter(|other| **other > start) {
+            put(*other, &self.chunks.remove(other).unwrap());
+        }
+        put(address, data);
+
+        self.chunks.insert(start, merged);
+    }
+}
+
+impl<'data> Program<'data> {
+    // Every contiguous chunk becomes a region; the start address, if any, is the entry.
+    pub fn from_image(image: &'data MemoryImage) -> Self {
+        let regions = image
+            .chunks()
+            .map(|(address, data)| CodeRegion {
+                name: None,
+                address,
+                data,
+            })
+            .collect();
+
+        let symbols = image
+            .start_address
+            .map(|address| {
+                Symbol::new(
+                    &format!("sub_{:x}", address),
+                    address,
+                    0,
+                    SymbolKind::Function,
+                )
+            })
+            .into_iter()
+            .collect();
+
+        Self::from_parts(
+            image.start_address.unwrap_or_default(),
+            regions,
+            Vec::new(),
+            SymbolTable::new(symbols),
+            HashMap::new(),
+        )
+    }
+}
+
+// Non-empty lines numbered from 1.
+fn records(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16).ok())
+        .collect()
+}
+
+fn be(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |val, byte| (val << 8) | *byte as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AArch64Parser;
+
+    #[test]
+    fn ihex_test() {
+        // sub sp, sp, #0x10; ret at 0x8000_0000, then one more ret 0x10 bytes further on.
+        let text = "\
+:0200000480007A
+:08000000FF4300D1C0035FD6ED
+:04001000C0035FD6F4
+:040000058000000077
+:00000001FF
+";
+        let image = MemoryImage::from_ihex(text).unwrap();
+        let chunks: Vec<_> = image
+            .chunks()
+            .map(|(address, data)| (address, data.len()))
+            .collect();
+        assert_eq!(chunks, [(0x8000_0000, 8), (0x8000_0010, 4)]);
+        assert_eq!(image.start_address(), Some(0x8000_0000));
+
+        let program = Program::from_image(&image);
+        let parser = AArch64Parser::new();
+        let decoded: Vec<_> = program
+            .instructions(&parser)
+            .map(|(address, raw, _)| (address, raw))
+            .collect();
+        assert_eq!(
+            decoded,
+            [
+                (0x8000_0000, 0xd100_43ff),
+                (0x8000_0004, 0xd65f_03c0),
+                (0x8000_0010, 0xd65f_03c0)
+            ]
+        );
+        assert_eq!(program.entry(), 0x8000_0000);
+
+        assert!(matches!(
+            MemoryImage::from_ihex(":08000000FF4300D1C0035FD6EE\n"),
+            Err(Error::ChecksumError(1))
+        ));
+        assert!(matches!(
+            MemoryImage::from_ihex(":0200000480007A\nFF4300D1\n"),
+            Err(Error::InvalidRecordError(2))
+        ));
+
+        // A record at the start of a chunk and one overlapping two chunks.
+        let text = "\
+:0400000011111111B8
+:040008003333333328
+:020000002222BA
+:060003004444444444445F
+";
+        let image = MemoryImage::from_ihex(text).unwrap();
+        let chunks: Vec<_> = image.chunks().collect();
+        assert_eq!(
+            chunks,
+            [(
+                0,
+                &[0x22, 0x22, 0x11, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x33, 0x33, 0x33][..]
+            )]
+        );
+    }
+
+    #[test]
+    fn srec_test() {
+        let text = "\
+S00600004844521B
+S30D80000000FF4300D1C0035FD667
+S30980000008C0035FD676
+S705800000007A
+";
+        let image = MemoryImage::from_srec(text).unwrap();
+        let chunks: Vec<_> = image
+            .chunks()
+            .map(|(address, data)| (address, data.len()))
+            .collect();
+        assert_eq!(chunks, [(0x8000_0000, 12)]);
+        assert_eq!(image.start_address(), Some(0x8000_0000));
+
+        assert!(matches!(
+            MemoryImage::from_srec("S30980000008C0035FD677\n"),
+            Err(Error::ChecksumError(1))
+        ));
+        assert!(matches!(
+            MemoryImage::from_srec("S4030000FC\n"),
+            Err(Error::InvalidRecordError(1))
+        ));
+        for record in ["S\n", "S\u{e9}00\n", "S1+3000000FC\n"] {
+            assert!(matches!(
+                MemoryImage::from_srec(record),
+                Err(Error::InvalidRecordError(1))
+            ));
+        }
+    }
+}
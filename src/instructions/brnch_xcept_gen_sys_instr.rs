@@ -1,3 +1,5 @@
+use crate::features::Feature;
+
 #[derive(Clone, Copy)]
 pub enum BranchesExceptionGenNSysInstr {
     ConditionalBranchImmediate,
@@ -5,7 +7,7 @@ pub enum BranchesExceptionGenNSysInstr {
     SystemInstructionsWithRegisterArgument,
     Hints,
     Barriers,
-    PSTATE,
+    Pstate,
     SystemWithResult,
     SystemInstructions,
     SystemRegisterMove,
@@ -22,10 +24,85 @@ pub enum ConditionalBranchImmediate {
     Unallocated,
 }
 
+impl ConditionalBranchImmediate {
+    pub(crate) fn feature(&self) -> Feature {
+        match self {
+            ConditionalBranchImmediate::BcCond => Feature::FEAT_HBC,
+            _ => Feature::ArmV8_0,
+        }
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConditionalBranchImmediateData {
     pub o1: u32,
     pub o0: u32,
     pub imm19: u32,
     pub cond: u32,
 }
+
+#[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum UnconditionalBranchRegister {
+    BR,
+    BLR,
+    RET,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnconditionalBranchRegisterData {
+    pub opc: u32,
+    pub op2: u32,
+    pub op3: u32,
+    pub rn: u32,
+    pub op4: u32,
+}
+
+#[derive(Clone, Copy)]
+pub enum UnconditionalBranchImmediate {
+    B,
+    BL,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnconditionalBranchImmediateData {
+    pub op: u32,
+    pub imm26: u32,
+}
+
+#[derive(Clone, Copy)]
+pub enum CompareAndBranchImmediate {
+    CBZ32,
+    CBNZ32,
+    CBZ64,
+    CBNZ64,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompareAndBranchImmediateData {
+    pub sf: u32,
+    pub op: u32,
+    pub imm19: u32,
+    pub rt: u32,
+}
+
+#[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum TestAndBranchImmediate {
+    TBZ,
+    TBNZ,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TestAndBranchImmediateData {
+    pub b5: u32,
+    pub op: u32,
+    pub b40: u32,
+    pub imm14: u32,
+    pub rt: u32,
+}
@@ -1,3 +1,6 @@
+use crate::features::Feature;
+use crate::utils::sign_extend;
+
 #[derive(Clone, Copy)]
 pub enum DataProcessingImmediate {
     PCrelAddressing,
@@ -9,6 +12,37 @@ pub enum DataProcessingImmediate {
     Extract,
 }
 
+impl DataProcessingImmediate {
+    pub(crate) fn feature(&self) -> Feature {
+        match self {
+            DataProcessingImmediate::AddSubtractImmediateWithTags => Feature::FEAT_MTE,
+            _ => Feature::ArmV8_0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum PCrelAddressing {
+    ADR,
+    ADRP,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PCrelAddressingData {
+    pub op: u32,
+    pub immlo: u32,
+    pub immhi: u32,
+    pub rd: u32,
+}
+
+impl PCrelAddressingData {
+    pub fn imm(&self) -> i64 {
+        sign_extend(self.immhi << 2 | self.immlo, 21)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum AddSubtractImmediate {
     ADDImmediate32,
@@ -22,6 +56,7 @@ pub enum AddSubtractImmediate {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddSubtractImmediateData {
     pub sf: u32,
     pub op: u32,
@@ -31,3 +66,42 @@ pub struct AddSubtractImmediateData {
     pub rn: u32,
     pub rd: u32,
 }
+
+impl AddSubtractImmediateData {
+    pub fn imm(&self) -> u64 {
+        (self.imm12 as u64) << (self.sh * 12)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum MoveWideImmediate {
+    Unallocated0,
+    Unallocated1,
+    MOVN32,
+    MOVZ32,
+    MOVK32,
+    MOVN64,
+    MOVZ64,
+    MOVK64,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveWideImmediateData {
+    pub sf: u32,
+    pub opc: u32,
+    pub hw: u32,
+    pub imm16: u32,
+    pub rd: u32,
+}
+
+impl MoveWideImmediateData {
+    pub fn shift(&self) -> u32 {
+        self.hw * 16
+    }
+
+    // The immediate in its place; MOVN inverts it and MOVK only replaces these 16 bits.
+    pub fn imm(&self) -> u64 {
+        (self.imm16 as u64) << self.shift()
+    }
+}
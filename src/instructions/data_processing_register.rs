@@ -1,3 +1,5 @@
+use crate::features::Feature;
+
 #[derive(Clone, Copy)]
 pub enum DataProcessingRegister {
     DataProcessing2Source,
@@ -14,12 +16,22 @@ pub enum DataProcessingRegister {
     DataProcessing3Source,
 }
 
+impl DataProcessingRegister {
+    pub(crate) fn feature(&self) -> Feature {
+        match self {
+            DataProcessingRegister::RotateRightIntoFlags
+            | DataProcessingRegister::EvaluateInfoFlags => Feature::FEAT_FlagM,
+            _ => Feature::ArmV8_0,
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum AddSubtractShiftedRegister {
     Unallocated0,
     Unallocated1,
     ADDShiftedRegister32,
-    ADDSShiftedReigster32,
+    ADDSShiftedRegister32,
     SUBShiftedRegister32,
     SUBSShiftedRegister32,
     ADDShiftedRegister64,
@@ -29,6 +41,7 @@ pub enum AddSubtractShiftedRegister {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddSubtractShiftedRegisterData {
     pub sf: u32,
     pub op: u32,
@@ -39,3 +52,33 @@ pub struct AddSubtractShiftedRegisterData {
     pub rn: u32,
     pub rd: u32,
 }
+
+#[derive(Clone, Copy)]
+pub enum AddSubtractExtendedRegister {
+    Unallocated0,
+    Unallocated1,
+    Unallocated2,
+    Unallocated3,
+    ADDExtendedRegister32,
+    ADDSExtendedRegister32,
+    SUBExtendedRegister32,
+    SUBSExtendedRegister32,
+    ADDExtendedRegister64,
+    ADDSExtendedRegister64,
+    SUBExtendedRegister64,
+    SUBSExtendedRegister64,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddSubtractExtendedRegisterData {
+    pub sf: u32,
+    pub op: u32,
+    pub s: u32,
+    pub opt: u32,
+    pub rm: u32,
+    pub option: u32,
+    pub imm3: u32,
+    pub rn: u32,
+    pub rd: u32,
+}
@@ -1,3 +1,6 @@
+use crate::features::Feature;
+use crate::utils::sign_extend;
+
 #[derive(Clone, Copy)]
 pub enum LoadsAndStores {
     CompareAndSwapPair,
@@ -34,6 +37,21 @@ pub enum LoadsAndStores {
     LoadStoreRegisterUnsignedImmediate,
 }
 
+impl LoadsAndStores {
+    pub(crate) fn feature(&self) -> Feature {
+        match self {
+            LoadsAndStores::CompareAndSwapPair
+            | LoadsAndStores::CompareAndSwap
+            | LoadsAndStores::AtomicMemoryOperations => Feature::FEAT_LSE,
+            LoadsAndStores::LoadStoreMemoryTags => Feature::FEAT_MTE,
+            LoadsAndStores::LDAPRSTLRUnscalaedImmediate => Feature::FEAT_LRCPC2,
+            LoadsAndStores::MemoryCopyAndMemorySet => Feature::FEAT_MOPS,
+            LoadsAndStores::LoadStoreRegisterPac => Feature::FEAT_PAuth,
+            _ => Feature::ArmV8_0,
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum LoadStoreRegisterUnsignedImmediate {
     Unallocated0,
@@ -66,6 +84,7 @@ pub enum LoadStoreRegisterUnsignedImmediate {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LoadStoreRegisterUnsignedImmediateData {
     pub size: u32,
     pub v: u32,
@@ -74,3 +93,129 @@ pub struct LoadStoreRegisterUnsignedImmediateData {
     pub rn: u32,
     pub rt: u32,
 }
+
+impl LoadStoreRegisterUnsignedImmediateData {
+    pub fn offset(&self) -> u64 {
+        (self.imm12 as u64) << self.size
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum LoadRegisterLiteral {
+    LDRLiteral32,
+    LDRLiteralSIMDFP32,
+    LDRLiteral64,
+    LDRLiteralSIMDFP64,
+    LDRSWLiteral,
+    LDRLiteralSIMDFP128,
+    PRFMLiteral,
+    Unallocated0,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoadRegisterLiteralData {
+    pub opc: u32,
+    pub v: u32,
+    pub imm19: u32,
+    pub rt: u32,
+}
+
+impl LoadRegisterLiteralData {
+    pub fn offset(&self) -> i64 {
+        sign_extend(self.imm19, 19) << 2
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum LoadStoreRegisterImmediateIndexed {
+    STRBImmediate,
+    LDRBImmediate,
+    LDRSBImmediate64,
+    LDRSBImmediate32,
+    STRImmediateSIMDFP8,
+    LDRImmediateSIMDFP8,
+    STRImmediateSIMDFP128,
+    LDRImmediateSIMDFP128,
+    STRHImmediate,
+    LDRHImmediate,
+    LDRSHImmediate64,
+    LDRSHImmediate32,
+    STRImmediateSIMDFP16,
+    LDRImmediateSIMDFP16,
+    Unallocated0,
+    Unallocated1,
+    Unallocated2,
+    STRImmediate32,
+    LDRImmediate32,
+    LDRSWImmediate,
+    STRImmediateSIMDFP32,
+    LDRImmediateSIMDFP32,
+    STRImmediate64,
+    LDRImmediate64,
+    Unallocated3,
+    STRImmediateSIMDFP64,
+    LDRImmediateSIMDFP64,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoadStoreRegisterImmediateIndexedData {
+    pub size: u32,
+    pub v: u32,
+    pub opc: u32,
+    pub imm9: u32,
+    pub rn: u32,
+    pub rt: u32,
+}
+
+#[derive(Clone, Copy)]
+pub enum LoadStoreRegisterRegisterOffset {
+    STRBRegister,
+    LDRBRegister,
+    LDRSBRegister64,
+    LDRSBRegister32,
+    STRRegisterSIMDFP8,
+    LDRRegisterSIMDFP8,
+    STRRegisterSIMDFP128,
+    LDRRegisterSIMDFP128,
+    STRHRegister,
+    LDRHRegister,
+    LDRSHRegister64,
+    LDRSHRegister32,
+    STRRegisterSIMDFP16,
+    LDRRegisterSIMDFP16,
+    Unallocated0,
+    Unallocated1,
+    Unallocated2,
+    STRRegister32,
+    LDRRegister32,
+    LDRSWRegister,
+    STRRegisterSIMDFP32,
+    LDRRegisterSIMDFP32,
+    STRRegister64,
+    LDRRegister64,
+    PRFMRegister,
+    STRRegisterSIMDFP64,
+    LDRRegisterSIMDFP64,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoadStoreRegisterRegisterOffsetData {
+    pub size: u32,
+    pub v: u32,
+    pub opc: u32,
+    pub rm: u32,
+    pub option: u32,
+    pub s: u32,
+    pub rn: u32,
+    pub rt: u32,
+}
+
+impl LoadStoreRegisterRegisterOffsetData {
+    // The index register is scaled by the access size when S is set.
+    pub fn shift(&self) -> u32 {
+        self.s * self.size
+    }
+}
@@ -1,8 +1,21 @@
+use crate::features::Feature;
+
 pub(crate) mod brnch_xcept_gen_sys_instr;
 pub(crate) mod data_processing_immediate;
 pub(crate) mod data_processing_register;
 pub(crate) mod loads_and_stores;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unpredictable {
+    WritebackOverlap,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Reserved {
+    PermanentlyUndefined,
+}
+
 #[derive(Clone, Copy)]
 pub(crate) enum MainEncodingTable {
     Reserved,
@@ -16,3 +29,13 @@ pub(crate) enum MainEncodingTable {
     DataProcessingRegister,
     DataProcessingScalarFloatingPointAndAdvancedSIMD,
 }
+
+impl MainEncodingTable {
+    pub(crate) fn feature(&self) -> Feature {
+        match self {
+            MainEncodingTable::SmeEncodings => Feature::FEAT_SME,
+            MainEncodingTable::SveEncodings => Feature::FEAT_SVE,
+            _ => Feature::ArmV8_0,
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use crate::aarc64_parser::AArch64Parser;
+use crate::program::{CodeRegion, Program};
+use crate::registers::Register;
+use crate::AArch64Inst;
+
+// How many instructions before the indirect branch the idiom may be spread over.
+const MAX_DISTANCE: usize = 32;
+// Entries read from a table whose bounds check was not found.
+const MAX_ENTRIES: usize = 1024;
+
+const COND_HS: u32 = 0b0010;
+const COND_HI: u32 = 0b1000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JumpTable {
+    // The BR that dispatches through the table.
+    pub branch: u64,
+    pub table: u64,
+    pub entry_size: u8,
+    // One per entry in table order, so cases sharing code repeat their target.
+    pub targets: Vec<u64>,
+    // Whether the entry count comes from a bounds check rather than from scanning for
+    // entries that land in the code.
+    pub bounded: bool,
+}
+
+// How a loaded entry turns into a branch target.
+enum Entry {
+    Absolute,
+    // `base + (entry << shift)`, with the entry optionally narrowed by an extend.
+    Relative {
+        base: u64,
+        shift: u32,
+        extend: Option<u32>,
+    },
+}
+
+impl<'data> Program<'data> {
+    // Recognizes `BR xT` fed by a table load, either directly from a table of absolute
+    // addresses or through `ADD xT, xBase, xEntry` for tables of offsets. Table and base
+    // addresses must come from ADR or ADRP+ADD; targets must lie within `code`.
+    pub fn jump_table(
+        &self,
+        parser: &AArch64Parser,
+        code: &CodeRegion,
+        branch: u64,
+    ) -> Option<JumpTable> {
+        let AArch64Inst::BR(br) = parser.parse(word_at(code, branch)?).ok()? else {
+            return None;
+        };
+        let insts = preceding(parser, code, branch);
+
+        let def = definition(&insts, 0, br.rn)?;
+        let (load, entry) = match &insts[def].1 {
+            AArch64Inst::LDRRegister64(_) => (def, Entry::Absolute),
+            AArch64Inst::ADDShiftedRegister64(add) if add.shift == 0 => {
+                // Either operand may be the loaded entry.
+                let (load, base) = [(add.rm, add.rn), (add.rn, add.rm)].into_iter().find_map(
+                    |(entry, base)| {
+                        let load = definition(&insts, def + 1, entry)?;
+                        is_table_load(&insts[load].1).then_some((load, base))
+                    },
+                )?;
+                let base = address_in(&insts, def + 1, base)?;
+                let entry = Entry::Relative {
+                    base,
+                    shift: add.imm6,
+                    extend: None,
+                };
+                (load, entry)
+            }
+            AArch64Inst::ADDExtendedRegister64(add) => {
+                let load = definition(&insts, def + 1, add.rm)?;
+                let base = address_in(&insts, def + 1, add.rn)?;
+                let entry = Entry::Relative {
+                    base,
+                    shift: add.imm3,
+                    extend: Some(add.option),
+                };
+                (load, entry)
+            }
+            _ => return None,
+        };
+
+        let (data, entry_size, signed) = match &insts[load].1 {
+            AArch64Inst::LDRBRegister(data) => (data, 1, false),
+            AArch64Inst::LDRHRegister(data) => (data, 2, false),
+            AArch64Inst::LDRRegister32(data) => (data, 4, false),
+            AArch64Inst::LDRSWRegister(data) => (data, 4, true),
+            AArch64Inst::LDRRegister64(data) => (data, 8, false),
+            _ => return None,
+        };
+        // An unscaled index into a table of wider entries is not a table walk.
+        if data.s == 0 && entry_size > 1 {
+            return None;
+        }
+        let table = address_in(&insts, load + 1, data.rn)?;
+        let count = bounds_check(&insts, load + 1, data.rm);
+
+        let mut targets = Vec::new();
+        for idx in 0..count.unwrap_or(MAX_ENTRIES as u64) {
+            let bytes = self.read(table + idx * entry_size, entry_size as usize);
+            let Some(value) = bytes.map(|bytes| load_value(bytes, signed)) else {
+                break;
+            };
+            let target = match entry {
+                Entry::Absolute => value,
+                Entry::Relative {
+                    base,
+                    shift,
+                    extend,
+                } => {
+                    let offset = extend.map_or(value, |option| extended(value, option));
+                    base.wrapping_add(offset << shift)
+                }
+            };
+
+            if !code.contains(target) || !(target - code.address).is_multiple_of(4) {
+                match count {
+                    Some(_) => return None,
+                    None => break,
+                }
+            }
+            targets.push(target);
+        }
+
+        if targets.is_empty() || count.is_some_and(|count| targets.len() as u64 != count) {
+            return None;
+        }
+
+        Some(JumpTable {
+            branch,
+            table,
+            entry_size: entry_size as u8,
+            targets,
+            bounded: count.is_some(),
+        })
+    }
+
+    // Every jump table dispatched from within `code`.
+    pub fn jump_tables(&self, parser: &AArch64Parser, code: &CodeRegion) -> Vec<JumpTable> {
+        code.instructions(parser)
+            .filter(|(_, _, result)| matches!(result, Ok(AArch64Inst::BR(_))))
+            .filter_map(|(address, _, _)| self.jump_table(parser, code, address))
+            .collect()
+    }
+}
+
+fn word_at(code: &CodeRegion, address: u64) -> Option<u32> {
+    let offset = address.checked_sub(code.address)? as usize;
+    let bytes = code.data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+// The straight-line instructions leading up to `branch`, nearest first. Conditional
+// branches are stepped over since the bounds check sits right before the table lookup.
+// Words the decoder does not know are skipped and assumed to leave the registers alone.
+fn preceding(parser: &AArch64Parser, code: &CodeRegion, branch: u64) -> Vec<(u64, AArch64Inst)> {
+    let mut insts = Vec::new();
+    let mut address = branch;
+
+    while address > code.address && insts.len() < MAX_DISTANCE {
+        address -= 4;
+        let Some(word) = word_at(code, address) else {
+            break;
+        };
+        match parser.parse(word) {
+            Ok(inst) if inst.is_invalid() => break,
+            Ok(
+                AArch64Inst::B(_)
+                | AArch64Inst::BL(_)
+                | AArch64Inst::BR(_)
+                | AArch64Inst::BLR(_)
+                | AArch64Inst::RET(_),
+            ) => break,
+            Ok(inst) => insts.push((address, inst)),
+            Err(_) => {}
+        }
+    }
+
+    insts
+}
+
+// Index of the nearest instruction from `from` on that writes `reg`.
+fn definition(insts: &[(u64, AArch64Inst)], from: usize, reg: u32) -> Option<usize> {
+    if reg == 31 {
+        return None;
+    }
+
+    (from..insts.len()).find(|idx| insts[*idx].1.writes().contains(Register::X(reg as u8)))
+}
+
+fn is_table_load(inst: &AArch64Inst) -> bool {
+    matches!(
+        inst,
+        AArch64Inst::LDRBRegister(_)
+            | AArch64Inst::LDRHRegister(_)
+            | AArch64Inst::LDRRegister32(_)
+            | AArch64Inst::LDRSWRegister(_)
+    )
+}
+
+// The address `reg` holds when it was set by ADR, or by ADRP and an ADD of the low bits.
+fn address_in(insts: &[(u64, AArch64Inst)], from: usize, reg: u32) -> Option<u64> {
+    let def = definition(insts, from, reg)?;
+    match &insts[def] {
+        (address, inst @ AArch64Inst::ADR(_)) => inst.pc_rel_address(*address),
+        (_, AArch64Inst::AddImmediate64(add)) => {
+            let page = definition(insts, def + 1, add.rn)?;
+            match &insts[page] {
+                (address, inst @ AArch64Inst::ADRP(_)) => {
+                    let offset = (add.imm12 as u64) << (12 * add.sh);
+                    Some(inst.pc_rel_address(*address)? + offset)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Number of entries allowed by a `CMP index, #imm` and the `B.HI`/`B.HS` to the default
+// case that follows it, if nothing in between changes the index or the flags.
+fn bounds_check(insts: &[(u64, AArch64Inst)], from: usize, index: u32) -> Option<u64> {
+    let mut cond = None;
+
+    for (_, inst) in &insts[from..] {
+        match inst {
+            AArch64Inst::BCond(data) if cond.is_none() => match data.cond {
+                COND_HI | COND_HS => cond = Some(data.cond),
+                _ => return None,
+            },
+            AArch64Inst::SUBSImmediate32(data) | AArch64Inst::SUBSImmediate64(data)
+                if data.rd == 31 && data.rn == index && data.sh == 0 =>
+            {
+                let limit = data.imm12 as u64;
+                return match cond? {
+                    COND_HI => Some(limit + 1),
+                    _ => Some(limit),
+                };
+            }
+            inst => {
+                let writes = inst.writes();
+                if writes.contains(Register::Nzcv) || writes.contains(Register::X(index as u8)) {
+                    return None;
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn load_value(bytes: &[u8], signed: bool) -> u64 {
+    let mut raw = [0; 8];
+    raw[..bytes.len()].copy_from_slice(bytes);
+    let value = u64::from_le_bytes(raw);
+
+    match signed {
+        true => extended(value, 0b110),
+        false => value,
+    }
+}
+
+// Applies the UXT*/SXT* extend in an extended register operand's `option` field.
+fn extended(value: u64, option: u32) -> u64 {
+    let bits = 8 << (option & 0b11);
+    if bits == 64 {
+        return value;
+    }
+
+    let shift = 64 - bits;
+    match option & 0b100 {
+        0 => value << shift >> shift,
+        _ => ((value << shift) as i64 >> shift) as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{raw_program, switch_code};
+
+    #[test]
+    fn jump_table_test() {
+        let mut words = switch_code();
+        let program = raw_program(&words, &[]);
+        let parser = AArch64Parser::new();
+        let code = &program.regions()[0];
+
+        let table = program.jump_table(&parser, code, 0x1018).unwrap();
+        assert_eq!(table.table, 0x1030);
+        assert_eq!(table.entry_size, 1);
+        assert_eq!(table.targets, [0x1020, 0x1028, 0x1024]);
+        assert!(table.bounded);
+        assert!(program.jump_table(&parser, code, 0x1014).is_none());
+        assert_eq!(program.jump_tables(&parser, code), [table]);
+
+        // Without the bounds check, entries are taken for as long as they land in the code.
+        words[0] = 0xd503_201f;
+        let program = raw_program(&words, &[]);
+        let code = &program.regions()[0];
+        let table = program.jump_table(&parser, code, 0x1018).unwrap();
+        assert_eq!(table.targets, [0x1020, 0x1028, 0x1024, 0x102c]);
+        assert!(!table.bounded);
+    }
+
+    #[test]
+    fn jump_table_elf_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        let parser = AArch64Parser::new();
+        let function = program
+            .functions()
+            .into_iter()
+            .find(|function| function.symbol.demangled == "core::sync::atomic::compiler_fence")
+            .unwrap();
+
+        // adr x10, <.rodata>; adr x8, 0x210438; ldrsw x9, [x10, x11, lsl #2]; add x8, x8, x9
+        let table = program
+            .jump_table(&parser, &function.code, 0x21_0444)
+            .unwrap();
+        assert_eq!(table.table, 0x20_01a8);
+        assert_eq!(table.entry_size, 4);
+        assert_eq!(
+            table.targets,
+            [0x21_044c, 0x21_0488, 0x21_048c, 0x21_0490, 0x21_0494]
+        );
+    }
+}
@@ -1,69 +1,238 @@
-use std::convert::TryInto;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 mod aarc64_parser;
+#[cfg(feature = "std")]
+mod call_graph;
+#[cfg(feature = "std")]
+mod cfg;
+mod compact;
+mod decoder;
+#[cfg(feature = "std")]
+mod discovery;
+mod display;
+#[cfg(feature = "std")]
+mod eh_frame;
 mod error;
+mod features;
+#[cfg(feature = "std")]
+mod image;
 mod instructions;
+#[cfg(feature = "std")]
+mod jump_table;
+#[cfg(feature = "std")]
+mod macho;
 mod pattern_matcher;
+#[cfg(feature = "std")]
+mod pe;
+#[cfg(feature = "std")]
+mod plt;
+#[cfg(feature = "std")]
+mod program;
+#[cfg(feature = "std")]
+mod raw;
+mod record;
+mod registers;
+#[cfg(feature = "std")]
+mod relocations;
+#[cfg(feature = "std")]
+mod strings;
+#[cfg(feature = "std")]
+mod symbols;
+#[cfg(all(any(test, feature = "test-utils"), feature = "std"))]
+#[doc(hidden)]
+pub mod test_utils;
 mod utils;
+#[cfg(feature = "std")]
+mod xrefs;
 
-use instructions::data_processing_immediate::*;
 use instructions::brnch_xcept_gen_sys_instr::*;
+use instructions::data_processing_immediate::*;
 use instructions::data_processing_register::*;
 use instructions::loads_and_stores::*;
 
-use crate::aarc64_parser::AArch64Parser;
-use crate::error::Error;
-
+pub use crate::aarc64_parser::{decode, AArch64Parser};
+#[cfg(feature = "std")]
+pub use crate::call_graph::{Call, CallGraph, CallKind};
+#[cfg(feature = "std")]
+pub use crate::cfg::{BasicBlock, Cfg, Edge, EdgeKind};
+pub use crate::decoder::SliceDecoder;
+#[cfg(feature = "std")]
+pub use crate::discovery::{ByteKind, CodeMap, Discovery};
+pub use crate::display::{Disassembly, Mnemonic, Operands};
+pub use crate::error::Error;
+pub use crate::features::{Feature, FeatureSet};
+#[cfg(feature = "std")]
+pub use crate::image::MemoryImage;
+pub use crate::instructions::Unpredictable;
+#[cfg(feature = "std")]
+pub use crate::jump_table::JumpTable;
+pub use crate::pattern_matcher::Field;
+#[cfg(feature = "std")]
+pub use crate::plt::PltStub;
+#[cfg(feature = "std")]
+pub use crate::program::{CodeRegion, Function, Program};
+#[cfg(feature = "std")]
+pub use crate::raw::RawLayout;
+pub use crate::record::{InstRecord, Opcode};
+pub use crate::registers::{Register, RegisterSet};
+#[cfg(feature = "std")]
+pub use crate::relocations::Relocation;
+#[cfg(feature = "std")]
+pub use crate::strings::{Literal, StringKind, StringRef};
+#[cfg(feature = "std")]
+pub use crate::symbols::{demangle, Symbol, SymbolKind, SymbolTable};
+pub use crate::utils::{Endianness, InstReader};
+#[cfg(feature = "std")]
+pub use crate::xrefs::{TargetKind, Xref, XrefKind, Xrefs};
+
+use crate::utils::sign_extend;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AArch64Inst {
+    ADR(PCrelAddressingData),
+    ADRP(PCrelAddressingData),
+
+    AddImmediate64(AddSubtractImmediateData),
     SubImmediate64(AddSubtractImmediateData),
+    SUBSImmediate32(AddSubtractImmediateData),
+    SUBSImmediate64(AddSubtractImmediateData),
+
+    MOVN32(MoveWideImmediateData),
+    MOVZ32(MoveWideImmediateData),
+    MOVK32(MoveWideImmediateData),
+    MOVN64(MoveWideImmediateData),
+    MOVZ64(MoveWideImmediateData),
+    MOVK64(MoveWideImmediateData),
 
     BCond(ConditionalBranchImmediateData),
+    BcCond(ConditionalBranchImmediateData),
 
-    STRImmediate64(LoadStoreRegisterUnsignedImmediateData),
+    B(UnconditionalBranchImmediateData),
+    BL(UnconditionalBranchImmediateData),
 
+    CBZ32(CompareAndBranchImmediateData),
+    CBNZ32(CompareAndBranchImmediateData),
+    CBZ64(CompareAndBranchImmediateData),
+    CBNZ64(CompareAndBranchImmediateData),
+
+    TBZ(TestAndBranchImmediateData),
+    TBNZ(TestAndBranchImmediateData),
+
+    BR(UnconditionalBranchRegisterData),
+    BLR(UnconditionalBranchRegisterData),
+    RET(UnconditionalBranchRegisterData),
+
+    STRImmediate64(LoadStoreRegisterUnsignedImmediateData),
+    LDRImmediate64(LoadStoreRegisterUnsignedImmediateData),
+    STRImmediatePreIndexed32(LoadStoreRegisterImmediateIndexedData),
+    STRImmediatePreIndexed64(LoadStoreRegisterImmediateIndexedData),
+    STRImmediatePostIndexed32(LoadStoreRegisterImmediateIndexedData),
+    STRImmediatePostIndexed64(LoadStoreRegisterImmediateIndexedData),
+    LDRImmediatePreIndexed32(LoadStoreRegisterImmediateIndexedData),
+    LDRImmediatePreIndexed64(LoadStoreRegisterImmediateIndexedData),
+    LDRImmediatePostIndexed32(LoadStoreRegisterImmediateIndexedData),
+    LDRImmediatePostIndexed64(LoadStoreRegisterImmediateIndexedData),
+    LDRBRegister(LoadStoreRegisterRegisterOffsetData),
+    LDRHRegister(LoadStoreRegisterRegisterOffsetData),
+    LDRRegister32(LoadStoreRegisterRegisterOffsetData),
+    LDRRegister64(LoadStoreRegisterRegisterOffsetData),
+    LDRSWRegister(LoadStoreRegisterRegisterOffsetData),
+    LDRLiteral32(LoadRegisterLiteralData),
+    LDRLiteral64(LoadRegisterLiteralData),
+    LDRSWLiteral(LoadRegisterLiteralData),
+
+    ADDShiftedRegister64(AddSubtractShiftedRegisterData),
     SUBSShiftedRegister64(AddSubtractShiftedRegisterData),
+    ADDExtendedRegister64(AddSubtractExtendedRegisterData),
 
-    UNDEFINED,
+    UNALLOCATED(u32),
+    RESERVED(u32),
+    UNDEFINED(u32),
+    UNPREDICTABLE(u32, Unpredictable),
 }
 
-#[cfg(test)]
-mod tests {
-    use core::panic;
+impl AArch64Inst {
+    pub fn is_invalid(&self) -> bool {
+        matches!(
+            self,
+            AArch64Inst::UNALLOCATED(_)
+                | AArch64Inst::RESERVED(_)
+                | AArch64Inst::UNDEFINED(_)
+                | AArch64Inst::UNPREDICTABLE(..)
+        )
+    }
 
-    use crate::utils::InstReader;
+    pub fn feature(&self) -> Feature {
+        match self {
+            AArch64Inst::BcCond(_) => Feature::FEAT_HBC,
+            _ => Feature::ArmV8_0,
+        }
+    }
+
+    pub fn branch_target(&self, address: u64) -> Option<u64> {
+        let offset = match self {
+            AArch64Inst::BCond(data) | AArch64Inst::BcCond(data) => sign_extend(data.imm19, 19),
+            AArch64Inst::B(data) | AArch64Inst::BL(data) => sign_extend(data.imm26, 26),
+            AArch64Inst::CBZ32(data)
+            | AArch64Inst::CBNZ32(data)
+            | AArch64Inst::CBZ64(data)
+            | AArch64Inst::CBNZ64(data) => sign_extend(data.imm19, 19),
+            AArch64Inst::TBZ(data) | AArch64Inst::TBNZ(data) => sign_extend(data.imm14, 14),
+            _ => return None,
+        };
+
+        Some(address.wrapping_add((offset << 2) as u64))
+    }
+
+    pub fn pc_rel_address(&self, address: u64) -> Option<u64> {
+        match self {
+            AArch64Inst::ADR(data) => Some(address.wrapping_add(data.imm() as u64)),
+            AArch64Inst::ADRP(data) => {
+                Some((address & !0xfff).wrapping_add((data.imm() << 12) as u64))
+            }
+            AArch64Inst::LDRLiteral32(data)
+            | AArch64Inst::LDRLiteral64(data)
+            | AArch64Inst::LDRSWLiteral(data) => Some(address.wrapping_add(data.offset() as u64)),
+            _ => None,
+        }
+    }
+}
 
+#[cfg(all(test, feature = "std"))]
+mod tests {
     use super::*;
-    use elf::endian::AnyEndian;
-    use elf::section::SectionHeader;
-    use elf::ElfBytes;
 
     #[test]
     fn it_works() {
         let path = std::path::PathBuf::from("a64_example");
         let file_data = std::fs::read(path).unwrap();
 
-        let slice = file_data.as_slice();
-        let file = ElfBytes::<AnyEndian>::minimal_parse(slice).unwrap();
-
-        let text_section = file
-            .section_header_by_name(".text")
-            .expect("section table should be parseable")
-            .expect("file should have a .text section");
-
-        let ep_offset = text_section.sh_offset as usize;
-        let ep_size = text_section.sh_size as usize;
-
-        let mut inst_reader =
-            InstReader::new(file_data[ep_offset..(ep_offset + ep_size)].iter().cloned());
-
+        let program = Program::from_elf(&file_data).unwrap();
         let parser = AArch64Parser::new();
 
-        while let Some(instr) = inst_reader.next() {
-            let result = parser.parse(instr);
-
-            println!("{:#?}", result);
+        for function in program.functions() {
+            println!(
+                "{:016x} <{}>:",
+                function.code.address, function.symbol.demangled
+            );
+
+            for (address, raw, result) in function.code.instructions(&parser) {
+                let target = result
+                    .as_ref()
+                    .ok()
+                    .and_then(|inst| program.target_label(address, inst));
+
+                match target {
+                    Some(target) => {
+                        println!("{:#x}: {:08x} {:?} <{}>", address, raw, result, target)
+                    }
+                    None => println!("{:#x}: {:08x} {:?}", address, raw, result),
+                }
+            }
         }
     }
 }
@@ -0,0 +1,244 @@
+use std::collections::{BTreeSet, HashMap};
+
+use goblin::mach::constants::cputype::CPU_TYPE_ARM64;
+use goblin::mach::constants::{S_ATTR_PURE_INSTRUCTIONS, S_ATTR_SOME_INSTRUCTIONS};
+use goblin::mach::load_command::CommandVariant;
+use goblin::mach::symbols::N_SECT;
+use goblin::mach::{Mach, MachO};
+
+use crate::error::Error;
+use crate::program::{CodeRegion, Program};
+use crate::relocations::Relocation;
+use crate::symbols::{Symbol, SymbolKind, SymbolTable};
+use crate::utils::read_uleb128;
+
+impl<'data> Program<'data> {
+    // Universal binaries are narrowed down to their first arm64 (or arm64e) slice.
+    pub fn from_macho(data: &'data [u8]) -> Result<Self, Error> {
+        let data = match Mach::parse(data)? {
+            Mach::Binary(_) => data,
+            Mach::Fat(fat) => {
+                let arches = fat.arches()?;
+                match arches.iter().find(|arch| arch.cputype == CPU_TYPE_ARM64) {
+                    Some(arch) => arch.slice(data),
+                    None => {
+                        let cputype = arches.first().map_or(0, |arch| arch.cputype);
+                        return Err(Error::UnsupportedCpuTypeError(cputype));
+                    }
+                }
+            }
+        };
+
+        let macho = MachO::parse(data, 0)?;
+        if macho.header.cputype != CPU_TYPE_ARM64 {
+            return Err(Error::UnsupportedCpuTypeError(macho.header.cputype));
+        }
+
+        let mut regions = Vec::new();
+        let mut data_regions = Vec::new();
+        for segment in macho.segments.iter() {
+            for (section, section_data) in segment.sections()? {
+                if section_data.is_empty() {
+                    continue;
+                }
+
+                let region = CodeRegion {
+                    name: Some(format!("{},{}", section.segname()?, section.name()?)),
+                    address: section.addr,
+                    data: section_data,
+                };
+                match section.flags & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS) {
+                    0 => data_regions.push(region),
+                    _ => regions.push(region),
+                }
+            }
+        }
+
+        let starts = function_starts(&macho, data);
+        let is_code = |address| regions.iter().any(|region| region.contains(address));
+
+        let mut symbols = Vec::new();
+        for symbol in macho.symbols() {
+            let (name, nlist) = symbol?;
+            if nlist.is_stab() || nlist.get_type() != N_SECT {
+                continue;
+            }
+            // `L` and `l` prefixed names are assembler temporaries and linker-private labels.
+            if name.is_empty() || name.starts_with(['L', 'l']) {
+                continue;
+            }
+
+            let kind = match is_code(nlist.n_value) {
+                true => SymbolKind::Function,
+                false => SymbolKind::Object,
+            };
+            // C-level names carry a leading underscore on Darwin.
+            let name = name.strip_prefix('_').unwrap_or(name);
+            symbols.push(Symbol::new(name, nlist.n_value, 0, kind));
+        }
+
+        // Stripped binaries still list every function start; name the unlabelled ones.
+        let named: BTreeSet<_> = symbols.iter().map(|sym| sym.address).collect();
+        for start in starts.into_iter().filter(|start| !named.contains(start)) {
+            let name = format!("sub_{:x}", start);
+            symbols.push(Symbol::new(&name, start, 0, SymbolKind::Function));
+        }
+
+        let relocations = Relocation::from_macho(&macho, data)?;
+        Ok(Self::from_parts(
+            macho.entry,
+            regions,
+            data_regions,
+            SymbolTable::new(symbols),
+            HashMap::new(),
+        )
+        .with_relocations(relocations))
+    }
+}
+
+// LC_FUNCTION_STARTS holds ULEB128 deltas, the first relative to the start of __TEXT.
+fn function_starts(macho: &MachO, data: &[u8]) -> Vec<u64> {
+    let text_base = macho
+        .segments
+        .iter()
+        .find(|segment| segment.name().ok() == Some("__TEXT"))
+        .map_or(0, |segment| segment.vmaddr);
+
+    let mut starts = Vec::new();
+    for command in &macho.load_commands {
+        let CommandVariant::FunctionStarts(cmd) = &command.command else {
+            continue;
+        };
+        let start = cmd.dataoff as usize;
+        let Some(mut encoded) = data.get(start..start + cmd.datasize as usize) else {
+            continue;
+        };
+
+        let mut address = text_base;
+        while let Some((delta, len)) = read_uleb128(encoded) {
+            if delta == 0 {
+                break;
+            }
+            // A crafted delta can run past the end of the address space.
+            let Some(next) = address.checked_add(delta) else {
+                break;
+            };
+            address = next;
+            starts.push(address);
+            encoded = &encoded[len..];
+        }
+    }
+
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use goblin::mach::constants::cputype::{CPU_SUBTYPE_ARM64_E, CPU_TYPE_X86_64};
+
+    use super::*;
+    use crate::test_utils::{fat, MachOBuilder};
+    use crate::{AArch64Inst, AArch64Parser};
+
+    #[test]
+    fn from_macho_test() {
+        let file_data = std::fs::read("a64_macho_example.o").unwrap();
+        let program = Program::from_macho(&file_data).unwrap();
+
+        assert_eq!(program.regions().len(), 1);
+        assert_eq!(program.regions()[0].name.as_deref(), Some("__TEXT,__text"));
+
+        let names: Vec<_> = program
+            .functions()
+            .iter()
+            .map(|func| (func.symbol.name.clone(), func.code.data.len()))
+            .collect();
+        assert_eq!(
+            names,
+            [("main".to_string(), 0x20), ("helper".to_string(), 0xc)]
+        );
+        assert_eq!(
+            program.symbols().by_name("message").unwrap().kind,
+            SymbolKind::Object
+        );
+
+        // bl helper
+        let parser = AArch64Parser::new();
+        let (address, _, bl) = program.instructions(&parser).nth(2).unwrap();
+        let bl = bl.unwrap();
+        assert!(matches!(bl, AArch64Inst::BL(_)));
+        assert_eq!(
+            program.target_label(address, &bl).as_deref(),
+            Some("helper")
+        );
+
+        // adrp x0, message; add x0, x0, message; bl puts
+        let labels: Vec<_> = program
+            .instructions(&parser)
+            .skip(3)
+            .take(3)
+            .map(|(address, _, inst)| program.target_label(address, &inst.unwrap()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                Some("message".to_string()),
+                Some(":lo12:message".to_string()),
+                Some("puts".to_string())
+            ]
+        );
+        assert_eq!(program.relocation_at(0x14).unwrap().symbol_value, None);
+    }
+
+    #[test]
+    fn function_starts_test() {
+        // sub sp, sp, #0x10; ret; ret
+        let file_data = MachOBuilder::new(CPU_TYPE_ARM64, 0)
+            .text(&[0xd100_43ff, 0xd65f_03c0, 0xd65f_03c0])
+            .symbol("_main", 0)
+            .function_starts(&[0, 8])
+            .entry(0)
+            .build();
+        let program = Program::from_macho(&file_data).unwrap();
+        let text = &program.regions()[0];
+
+        assert_eq!(program.entry(), text.address);
+        assert_eq!(program.label(text.address).as_deref(), Some("main"));
+        assert_eq!(
+            program.label(text.address + 8),
+            Some(format!("sub_{:x}", text.address + 8))
+        );
+        assert_eq!(program.functions().len(), 2);
+
+        // A start before the previous one wraps around to a delta that overflows.
+        let file_data = MachOBuilder::new(CPU_TYPE_ARM64, 0)
+            .text(&[0xd100_43ff, 0xd65f_03c0, 0xd65f_03c0])
+            .function_starts(&[0, 8, 4])
+            .build();
+        let program = Program::from_macho(&file_data).unwrap();
+        assert_eq!(program.functions().len(), 2);
+    }
+
+    #[test]
+    fn fat_test() {
+        let arm64e = MachOBuilder::new(CPU_TYPE_ARM64, CPU_SUBTYPE_ARM64_E)
+            .text(&[0xd65f_03c0])
+            .build();
+        let x86_64 = MachOBuilder::new(CPU_TYPE_X86_64, 3).build();
+
+        let file_data = fat(&[
+            (CPU_TYPE_X86_64, 3, &x86_64),
+            (CPU_TYPE_ARM64, CPU_SUBTYPE_ARM64_E, &arm64e),
+        ]);
+        let program = Program::from_macho(&file_data).unwrap();
+        let parser = AArch64Parser::new();
+        let (_, raw, _) = program.instructions(&parser).next().unwrap();
+        assert_eq!(raw, 0xd65f_03c0);
+
+        let file_data = fat(&[(CPU_TYPE_X86_64, 3, &x86_64)]);
+        assert!(matches!(
+            Program::from_macho(&file_data),
+            Err(Error::UnsupportedCpuTypeError(CPU_TYPE_X86_64))
+        ));
+    }
+}
@@ -1,26 +1,82 @@
-use std::{marker::PhantomData, ops::Range};
+use core::marker::PhantomData;
+use core::ops::Range;
 
 use crate::utils::{GetBits, TestBits};
 
+// Capacities of the fixed tables, sized for the largest encoding group. Exceeding one is
+// reported when the matcher is built, which for `const` matchers means at compile time.
+const MAX_ARGS: usize = 10;
+const MAX_PATTERNS: usize = 32;
+
 #[derive(Clone, Copy)]
 struct Pattern {
     pattern: u32,
     mask: u32,
 }
 
+#[derive(Clone, Copy)]
+struct Arg {
+    name: &'static str,
+    start: u8,
+    end: u8,
+}
+
+// One named argument of a matched word, spanning bits `start..end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub table: &'static str,
+    pub name: &'static str,
+    pub start: u8,
+    pub end: u8,
+    pub value: u32,
+}
+
+// The arguments of one matcher applied to a word; not generic over the matched type so
+// that the fields of different tables can be chained.
+pub struct Fields<'a> {
+    table: &'static str,
+    args: core::slice::Iter<'a, Arg>,
+    val: u32,
+}
+
+impl Iterator for Fields<'_> {
+    type Item = Field;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arg = self.args.next()?;
+
+        Some(Field {
+            table: self.table,
+            name: arg.name,
+            start: arg.start,
+            end: arg.end,
+            value: self.val.get_bits(&(arg.start..arg.end)),
+        })
+    }
+}
+
 pub struct PatternMatcher<T> {
-    args: Vec<(String, Range<u8>)>,
-    patterns: Vec<(T, Pattern)>,
+    args: [Arg; MAX_ARGS],
+    args_len: usize,
+    patterns: [Option<(T, Pattern)>; MAX_PATTERNS],
+    patterns_len: usize,
 }
 
 impl<T> PatternMatcher<T>
 where
     T: Copy,
 {
-    pub fn builder() -> PatternMatcherBuilder<T, NeedArgs> {
+    pub const fn builder() -> PatternMatcherBuilder<T, NeedArgs> {
+        let arg = Arg {
+            name: "",
+            start: 0,
+            end: 0,
+        };
         let result = PatternMatcher {
-            args: Vec::new(),
-            patterns: Vec::new(),
+            args: [arg; MAX_ARGS],
+            args_len: 0,
+            patterns: [None; MAX_PATTERNS],
+            patterns_len: 0,
         };
         let pattern = Pattern {
             pattern: 0,
@@ -28,7 +84,7 @@ where
         };
 
         PatternMatcherBuilder {
-            result: result,
+            result,
             current_instr: None,
             current_pattern: pattern,
             current_index: 0,
@@ -37,13 +93,23 @@ where
     }
 
     pub fn get_arg(&self, val: u32, idx: usize) -> u32 {
-        val.get_bits(&self.args[idx].1)
+        let arg = &self.args[..self.args_len][idx];
+
+        val.get_bits(&(arg.start..arg.end))
+    }
+
+    pub fn fields(&self, table: &'static str, val: u32) -> Fields<'_> {
+        Fields {
+            table,
+            args: self.args[..self.args_len].iter(),
+            val,
+        }
     }
 
     pub fn match_pattern(&self, target: u32) -> Option<T> {
-        for pat in &self.patterns {
-            if target.test_bits(pat.1.pattern, pat.1.mask) {
-                return Some(pat.0);
+        for (inst, pat) in self.patterns[..self.patterns_len].iter().flatten() {
+            if target.test_bits(pat.pattern, pat.mask) {
+                return Some(*inst);
             } else {
                 continue;
             }
@@ -55,7 +121,6 @@ where
 
 pub struct NeedArgs;
 pub struct NeedPattern;
-pub struct With;
 
 pub struct PatternMatcherBuilder<T, BuildState>
 where
@@ -74,17 +139,27 @@ impl<T> PatternMatcherBuilder<T, NeedArgs>
 where
     T: Copy,
 {
-    pub fn args(
+    pub const fn args(
         mut self,
-        arg_name: &str,
+        arg_name: &'static str,
         arg_range: Range<u8>,
     ) -> PatternMatcherBuilder<T, NeedArgs> {
-        self.result.args.push((arg_name.to_string(), arg_range));
+        assert!(
+            self.result.args_len < MAX_ARGS,
+            "Too many pattern arguments"
+        );
+
+        self.result.args[self.result.args_len] = Arg {
+            name: arg_name,
+            start: arg_range.start,
+            end: arg_range.end,
+        };
+        self.result.args_len += 1;
 
         self
     }
 
-    pub fn inst(mut self, instr: T) -> PatternMatcherBuilder<T, NeedPattern> {
+    pub const fn inst(mut self, instr: T) -> PatternMatcherBuilder<T, NeedPattern> {
         self.current_instr = Some(instr);
 
         self.current_pattern = Pattern {
@@ -106,21 +181,26 @@ impl<T> PatternMatcherBuilder<T, NeedPattern>
 where
     T: Copy,
 {
-    fn parse_pattern(pattern: &str, range: &Range<u8>) -> (u32, u32) {
+    const fn parse_pattern(pattern: &str, arg: &Arg) -> (u32, u32) {
         let mut pattern_result = 0b0;
         let mut mask_result = 0b0;
 
         let mut count = 0;
 
-        for char in pattern.chars() {
+        let bytes = pattern.as_bytes();
+        let mut idx = 0;
+        while idx < bytes.len() {
+            let char = bytes[idx];
+            idx += 1;
+
             let (pat, mask) = match char {
-                'x' => (0, 0),
-                '0' => (0, 1),
-                '1' => (1, 1),
-                '_' => {
+                b'x' => (0, 0),
+                b'0' => (0, 1),
+                b'1' => (1, 1),
+                b'_' => {
                     continue;
                 }
-                _ => unreachable!(),
+                _ => panic!("Invalid character in pattern"),
             };
             pattern_result <<= 1;
             pattern_result |= pat;
@@ -131,23 +211,23 @@ where
             count += 1;
         }
 
-        assert_eq!(
-            count,
-            range.len(),
-            "Pattern string doesn't match with pattern range in pattern: {:#?}",
-            pattern
+        assert!(
+            count == (arg.end - arg.start) as usize,
+            "Pattern string doesn't match with pattern range"
         );
 
-        pattern_result <<= range.start;
-        mask_result <<= range.start;
+        pattern_result <<= arg.start;
+        mask_result <<= arg.start;
 
         (pattern_result, mask_result)
     }
 
-    fn push_current(&mut self) {
-        self.result
-            .patterns
-            .push((self.current_instr.unwrap(), self.current_pattern));
+    const fn push_current(&mut self) {
+        assert!(self.result.patterns_len < MAX_PATTERNS, "Too many patterns");
+
+        self.result.patterns[self.result.patterns_len] =
+            Some((self.current_instr.unwrap(), self.current_pattern));
+        self.result.patterns_len += 1;
 
         self.current_pattern = Pattern {
             pattern: 0,
@@ -155,11 +235,15 @@ where
         };
     }
 
-    pub fn with(mut self, pattern: &str) -> PatternMatcherBuilder<T, NeedPattern> {
-        let range = &self.result.args[self.current_index].1;
+    pub const fn with(mut self, pattern: &str) -> PatternMatcherBuilder<T, NeedPattern> {
+        assert!(
+            self.current_index < self.result.args_len,
+            "More patterns than arguments"
+        );
+        let arg = self.result.args[self.current_index];
         self.current_index += 1;
 
-        let (pat, mask) = Self::parse_pattern(pattern, range);
+        let (pat, mask) = Self::parse_pattern(pattern, &arg);
 
         self.current_pattern.pattern |= pat;
         self.current_pattern.mask |= mask;
@@ -167,7 +251,7 @@ where
         self
     }
 
-    pub fn inst(mut self, instr: T) -> PatternMatcherBuilder<T, NeedPattern> {
+    pub const fn inst(mut self, instr: T) -> PatternMatcherBuilder<T, NeedPattern> {
         self.push_current();
 
         self.current_instr = Some(instr);
@@ -181,7 +265,7 @@ where
         }
     }
 
-    pub fn build(mut self) -> PatternMatcher<T> {
+    pub const fn build(mut self) -> PatternMatcher<T> {
         self.push_current();
 
         self.result
@@ -189,6 +273,11 @@ where
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::assertions_on_constants,
+    clippy::unusual_byte_groupings,
+    deprecated
+)]
 mod tests {
     use std::panic::PanicInfo;
 
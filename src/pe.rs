@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use goblin::pe::header::{COFF_MACHINE_ARM64, COFF_MACHINE_X86_64};
+use goblin::pe::section_table::{SectionTable, IMAGE_SCN_MEM_EXECUTE};
+use goblin::pe::PE;
+
+use crate::error::Error;
+use crate::program::{CodeRegion, Program};
+use crate::symbols::{Symbol, SymbolKind, SymbolTable};
+
+const COFF_MACHINE_ARM64EC: u16 = 0xa641;
+
+// Native type in the low bits of a CHPE code range's start offset.
+const CHPE_RANGE_AMD64: u32 = 2;
+
+impl<'data> Program<'data> {
+    // ARM64EC images declare an x64 machine; their ARM64 code is located through the CHPE
+    // code range map instead of the section table.
+    pub fn from_pe(data: &'data [u8]) -> Result<Self, Error> {
+        let pe = PE::parse(data)?;
+        let machine = pe.header.coff_header.machine;
+        let image_base = pe.image_base;
+
+        let code_ranges = chpe_code_ranges(&pe, data);
+        let supported = match machine {
+            COFF_MACHINE_ARM64 | COFF_MACHINE_ARM64EC => true,
+            COFF_MACHINE_X86_64 => code_ranges.is_some(),
+            _ => false,
+        };
+        if !supported {
+            return Err(Error::UnsupportedMachineError(machine));
+        }
+
+        let mut regions = Vec::new();
+        match code_ranges {
+            Some(ranges) => {
+                for (rva, len) in ranges {
+                    let Some(bytes) = rva_data(data, &pe.sections, rva) else {
+                        continue;
+                    };
+                    let section = section_at(&pe.sections, rva);
+
+                    regions.push(CodeRegion {
+                        name: section.and_then(|section| section.name().ok().map(String::from)),
+                        address: image_base.wrapping_add(rva as u64),
+                        data: &bytes[..bytes.len().min(len as usize)],
+                    });
+                }
+            }
+            None => {
+                for section in &pe.sections {
+                    if section.characteristics & IMAGE_SCN_MEM_EXECUTE == 0 {
+                        continue;
+                    }
+
+                    regions.push(CodeRegion {
+                        name: Some(section.name()?.to_string()),
+                        address: image_base.wrapping_add(section.virtual_address as u64),
+                        data: section_data(data, section),
+                    });
+                }
+            }
+        }
+
+        let mut data_regions = Vec::new();
+        for section in &pe.sections {
+            if section.characteristics & IMAGE_SCN_MEM_EXECUTE != 0 {
+                continue;
+            }
+
+            data_regions.push(CodeRegion {
+                name: Some(section.name()?.to_string()),
+                address: image_base.wrapping_add(section.virtual_address as u64),
+                data: section_data(data, section),
+            });
+        }
+
+        let is_code = |address| regions.iter().any(|region| region.contains(address));
+
+        let mut symbols = Vec::new();
+        let ranges = pdata_ranges(&pe, data);
+        for export in pe.exports.iter().filter(|export| export.reexport.is_none()) {
+            let Some(name) = export.name else {
+                continue;
+            };
+            let address = image_base.wrapping_add(export.rva as u64);
+
+            let symbol = match is_code(address) {
+                true => {
+                    let size = ranges.get(&(export.rva as u32)).copied().unwrap_or(0);
+                    Symbol::new(name, address, size as u64, SymbolKind::Function)
+                }
+                false => Symbol::new(name, address, 0, SymbolKind::Object),
+            };
+            symbols.push(symbol);
+        }
+
+        // Every non-leaf function has a .pdata entry, exported or not.
+        let named: BTreeSet<_> = symbols.iter().map(|sym| sym.address).collect();
+        for (rva, len) in ranges {
+            let address = image_base.wrapping_add(rva as u64);
+            if !named.contains(&address) {
+                let name = format!("sub_{:x}", address);
+                symbols.push(Symbol::new(
+                    &name,
+                    address,
+                    len as u64,
+                    SymbolKind::Function,
+                ));
+            }
+        }
+
+        let imports = pe
+            .imports
+            .iter()
+            .map(|import| {
+                // Ordinal-only imports have no hint/name entry.
+                let name = match import.rva {
+                    0 => format!("{}#{}", import.dll, import.ordinal),
+                    _ => import.name.to_string(),
+                };
+                (image_base.wrapping_add(import.offset as u64), name)
+            })
+            .collect();
+
+        let entry = match pe.entry {
+            0 => 0,
+            entry => image_base.wrapping_add(entry as u64),
+        };
+
+        Ok(Self::from_parts(
+            entry,
+            regions,
+            data_regions,
+            SymbolTable::new(symbols),
+            imports,
+        ))
+    }
+}
+
+// Object files and some linkers leave VirtualSize zero, meaning the raw size.
+fn virtual_size(section: &SectionTable) -> u32 {
+    match section.virtual_size {
+        0 => section.size_of_raw_data,
+        size => size,
+    }
+}
+
+fn section_data<'data>(data: &'data [u8], section: &SectionTable) -> &'data [u8] {
+    let start = section.pointer_to_raw_data as usize;
+    let len = section.size_of_raw_data.min(virtual_size(section)) as usize;
+
+    data.get(start..)
+        .and_then(|data| data.get(..len))
+        .unwrap_or(&[])
+}
+
+fn section_at(sections: &[SectionTable], rva: u32) -> Option<&SectionTable> {
+    sections.iter().find(|section| {
+        rva.checked_sub(section.virtual_address)
+            .is_some_and(|offset| offset < virtual_size(section))
+    })
+}
+
+fn rva_data<'data>(data: &'data [u8], sections: &[SectionTable], rva: u32) -> Option<&'data [u8]> {
+    let section = section_at(sections, rva)?;
+
+    section_data(data, section).get((rva - section.virtual_address) as usize..)
+}
+
+fn read_u32(data: &[u8], sections: &[SectionTable], rva: u32) -> Option<u32> {
+    let bytes = rva_data(data, sections, rva)?.get(..4)?;
+
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+// Function start RVA -> length in bytes, from either packed unwind data or the .xdata header.
+fn pdata_ranges(pe: &PE, data: &[u8]) -> BTreeMap<u32, u32> {
+    let Some(exception_data) = &pe.exception_data else {
+        return BTreeMap::new();
+    };
+
+    exception_data
+        .functions_arm64()
+        .filter_map(|function| {
+            let function = function.ok()?;
+            let len = match function.is_packed() {
+                true => function.function_length(),
+                false => (read_u32(data, &pe.sections, function.unwind_data_rva())? & 0x3_ffff) * 4,
+            };
+
+            Some((function.begin_address, len))
+        })
+        .collect()
+}
+
+// (rva, length) of every ARM64 and ARM64EC range in a hybrid image, or `None` for plain images.
+fn chpe_code_ranges(pe: &PE, data: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let pointer = pe
+        .load_config_data
+        .as_ref()?
+        .directory
+        .chpe_metadata_pointer
+        .filter(|pointer| *pointer != 0)?;
+    let metadata = u32::try_from(pointer.checked_sub(pe.image_base)?).ok()?;
+
+    let code_map = read_u32(data, &pe.sections, metadata.checked_add(4)?)?;
+    let count = read_u32(data, &pe.sections, metadata.checked_add(8)?)?;
+
+    let mut ranges = Vec::new();
+    for idx in 0..count {
+        let entry = idx
+            .checked_mul(8)
+            .and_then(|offset| code_map.checked_add(offset))?;
+        let start = read_u32(data, &pe.sections, entry)?;
+        let len = read_u32(data, &pe.sections, entry.checked_add(4)?)?;
+        if start & 0x3 != CHPE_RANGE_AMD64 {
+            ranges.push((start & !0x3, len));
+        }
+    }
+
+    Some(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{PeBuilder, PE_IMAGE_BASE};
+    use crate::AArch64Parser;
+
+    #[test]
+    fn from_pe_test() {
+        // main: sub sp, sp, #0x10; add sp, sp, #0x10; ret
+        // helper: ret
+        // local: ret
+        let builder = PeBuilder::new(COFF_MACHINE_ARM64)
+            .text(&[
+                0xd100_43ff,
+                0x9100_43ff,
+                0xd65f_03c0,
+                0xd65f_03c0,
+                0xd65f_03c0,
+            ])
+            .entry(0)
+            .export("main", 0)
+            .export("helper", 0xc)
+            .import("KERNEL32.dll", &["ExitProcess", "GetLastError"])
+            .pdata(0, 0xc, true)
+            .pdata(0xc, 4, false)
+            .pdata(0x10, 4, true);
+        let exit_process = builder.import_slot(0, 0);
+        let file_data = builder.build();
+        let program = Program::from_pe(&file_data).unwrap();
+
+        let text = PE_IMAGE_BASE + 0x1000;
+        assert_eq!(program.entry(), text);
+        assert_eq!(program.regions().len(), 1);
+        assert_eq!(program.regions()[0].name.as_deref(), Some(".text"));
+
+        let main = program.symbols().by_name("main").unwrap();
+        assert_eq!((main.address, main.size), (text, 0xc));
+        assert_eq!(program.symbols().by_name("helper").unwrap().size, 4);
+        assert_eq!(
+            program.label(text + 0x10),
+            Some(format!("sub_{:x}", text + 0x10))
+        );
+        assert_eq!(program.got_symbol(exit_process), Some("ExitProcess"));
+
+        let parser = AArch64Parser::new();
+        let (address, raw, _) = program.instructions(&parser).next().unwrap();
+        assert_eq!((address, raw), (text, 0xd100_43ff));
+    }
+
+    #[test]
+    fn arm64ec_test() {
+        // An x64 image whose first 8 bytes are ARM64EC code and the rest x64.
+        let file_data = PeBuilder::new(COFF_MACHINE_X86_64)
+            .text(&[0xd100_43ff, 0xd65f_03c0, 0xc3c3_c3c3])
+            .code_range(0, 8, 1)
+            .code_range(8, 4, CHPE_RANGE_AMD64)
+            .build();
+        let program = Program::from_pe(&file_data).unwrap();
+
+        assert_eq!(program.regions().len(), 1);
+        assert_eq!(program.regions()[0].address, PE_IMAGE_BASE + 0x1000);
+        assert_eq!(program.regions()[0].data.len(), 8);
+
+        let file_data = PeBuilder::new(COFF_MACHINE_X86_64).build();
+        assert!(matches!(
+            Program::from_pe(&file_data),
+            Err(Error::UnsupportedMachineError(COFF_MACHINE_X86_64))
+        ));
+    }
+
+    #[test]
+    fn malformed_pe_test() {
+        let build = |chpe_metadata: u64| {
+            let mut file_data = PeBuilder::new(COFF_MACHINE_X86_64)
+                .text(&[0xd65f_03c0])
+                .code_range(0, 4, 1)
+                .build();
+            file_data[0x2dc8..0x2dd0].copy_from_slice(&chpe_metadata.to_le_bytes());
+            file_data
+        };
+
+        // CHPE metadata pointers below the image base and at the end of its address space.
+        for pointer in [1, PE_IMAGE_BASE + 0xffff_fffc] {
+            assert!(matches!(
+                Program::from_pe(&build(pointer)),
+                Err(Error::UnsupportedMachineError(COFF_MACHINE_X86_64))
+            ));
+        }
+
+        // A .text VirtualSize reaching past 4 GiB.
+        let mut file_data = PeBuilder::new(COFF_MACHINE_ARM64)
+            .text(&[0xd65f_03c0])
+            .export("main", 0)
+            .pdata(0, 4, false)
+            .build();
+        file_data[0x150..0x154].copy_from_slice(&u32::MAX.to_le_bytes());
+        let program = Program::from_pe(&file_data).unwrap();
+        assert!(program.symbols().by_name("main").is_some());
+
+        // An image base at the top of the address space.
+        let mut file_data = PeBuilder::new(COFF_MACHINE_ARM64)
+            .text(&[0xd65f_03c0])
+            .entry(0)
+            .build();
+        file_data[0x70..0x78].copy_from_slice(&(!0xffffu64).to_le_bytes());
+        assert!(Program::from_pe(&file_data).is_ok());
+    }
+
+    #[test]
+    fn zero_virtual_size_test() {
+        let mut file_data = PeBuilder::new(COFF_MACHINE_ARM64)
+            .text(&[0xd65f_03c0])
+            .build();
+        // VirtualSize of .text
+        file_data[0x150..0x154].fill(0);
+        let program = Program::from_pe(&file_data).unwrap();
+
+        assert_eq!(program.regions()[0].data.len(), 0x1000);
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::aarc64_parser::AArch64Parser;
+use crate::program::CodeRegion;
+use crate::AArch64Inst;
+
+#[derive(Clone, Debug)]
+pub struct PltStub {
+    pub address: u64,
+    pub got_address: u64,
+    pub name: String,
+}
+
+// Matches the canonical stub emitted by both GNU ld and lld:
+//     adrp x16, GOT_PAGE
+//     ldr  x17, [x16, #GOT_OFFSET]
+//     add  x16, x16, #GOT_OFFSET
+//     br   x17
+pub(crate) fn find_plt_stubs(
+    parser: &AArch64Parser,
+    region: &CodeRegion,
+    got: &HashMap<u64, String>,
+) -> Vec<PltStub> {
+    let insts: Vec<_> = region.instructions(parser).collect();
+
+    insts
+        .windows(4)
+        .filter_map(
+            |window| match (&window[0].2, &window[1].2, &window[2].2, &window[3].2) {
+                (
+                    Ok(AArch64Inst::ADRP(adrp)),
+                    Ok(AArch64Inst::LDRImmediate64(ldr)),
+                    Ok(AArch64Inst::AddImmediate64(add)),
+                    Ok(AArch64Inst::BR(br)),
+                ) if adrp.rd == 16
+                    && (ldr.rn, ldr.rt) == (16, 17)
+                    && (add.rn, add.rd) == (16, 16)
+                    && br.rn == 17 =>
+                {
+                    let page = (window[0].0 & !0xfff).wrapping_add((adrp.imm() << 12) as u64);
+                    let got_address = page + ldr.offset();
+
+                    Some(PltStub {
+                        address: window[0].0,
+                        got_address,
+                        name: got.get(&got_address)?.clone(),
+                    })
+                }
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use elf::abi;
+
+    use crate::program::Program;
+    use crate::test_utils::{rela64, strtab, sym64, ElfBuilder, ElfSection};
+    use crate::AArch64Parser;
+
+    #[test]
+    fn plt_stub_test() {
+        let (dynstr, names) = strtab(&["memcpy"]);
+        let mut dynsym = sym64(0, 0, 0, 0, 0);
+        dynsym.extend(sym64(
+            names[0],
+            abi::STT_FUNC | abi::STB_GLOBAL << 4,
+            0,
+            0,
+            0,
+        ));
+
+        // 1: .text, 2: .plt, 3: .got.plt, 4: .dynsym, 5: .dynstr, 6: .rela.plt
+        let file_data = ElfBuilder::new(false)
+            // bl 0x2000
+            .section(ElfSection::text(0x1000, &[0x9400_0400]))
+            .section(ElfSection {
+                name: ".plt",
+                ..ElfSection::text(
+                    0x2000,
+                    // adrp x16, 0x3000; ldr x17, [x16, #0x18]; add x16, x16, #0x18; br x17
+                    &[0xb000_0010, 0xf940_0e11, 0x9100_6210, 0xd61f_0220],
+                )
+            })
+            .section(ElfSection::new(
+                ".got.plt",
+                abi::SHT_PROGBITS,
+                (abi::SHF_ALLOC | abi::SHF_WRITE) as u64,
+                0x3000,
+                vec![0; 0x20],
+            ))
+            .section(ElfSection::new(".dynsym", abi::SHT_DYNSYM, 0, 0, dynsym).link(5, 1, 24))
+            .section(ElfSection::new(".dynstr", abi::SHT_STRTAB, 0, 0, dynstr))
+            .section(
+                ElfSection::new(
+                    ".rela.plt",
+                    abi::SHT_RELA,
+                    0,
+                    0,
+                    rela64(0x3018, 1, abi::R_AARCH64_JUMP_SLOT, 0),
+                )
+                .link(4, 3, 24),
+            )
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+
+        let stubs: Vec<_> = program.plt_stubs().collect();
+        assert_eq!(stubs.len(), 1);
+        assert_eq!((stubs[0].address, stubs[0].got_address), (0x2000, 0x3018));
+        assert_eq!(program.got_symbol(0x3018), Some("memcpy"));
+
+        let parser = AArch64Parser::new();
+        let (address, _, bl) = program.instructions(&parser).next().unwrap();
+        let target = bl.unwrap().branch_target(address).unwrap();
+        assert_eq!(program.label(target).as_deref(), Some("memcpy@plt"));
+        assert!(program.call_graph(&parser).is_import(target));
+    }
+}
@@ -0,0 +1,596 @@
+use std::collections::{BTreeMap, HashMap};
+
+use elf::abi;
+use elf::endian::AnyEndian;
+use elf::ElfBytes;
+
+use crate::aarc64_parser::AArch64Parser;
+use crate::decoder::SliceDecoder;
+use crate::eh_frame::fde_ranges;
+use crate::error::Error;
+use crate::plt::{find_plt_stubs, PltStub};
+use crate::relocations::Relocation;
+use crate::symbols::{demangle, Symbol, SymbolKind, SymbolTable};
+use crate::AArch64Inst;
+
+pub struct CodeRegion<'data> {
+    pub name: Option<String>,
+    pub address: u64,
+    pub data: &'data [u8],
+}
+
+impl<'data> CodeRegion<'data> {
+    pub fn contains(&self, address: u64) -> bool {
+        address
+            .checked_sub(self.address)
+            .is_some_and(|offset| offset < self.data.len() as u64)
+    }
+
+    pub fn instructions<'a>(&self, parser: &'a AArch64Parser) -> SliceDecoder<'a, 'data> {
+        SliceDecoder::new(parser, self.data, self.address)
+    }
+}
+
+pub struct Function<'a, 'data> {
+    pub symbol: &'a Symbol,
+    pub code: CodeRegion<'data>,
+}
+
+pub struct Program<'data> {
+    entry: u64,
+    regions: Vec<CodeRegion<'data>>,
+    // Loaded bytes outside the code regions, such as read-only data.
+    data_regions: Vec<CodeRegion<'data>>,
+    symbols: SymbolTable,
+    relocations: Vec<Relocation>,
+    code_relocations: HashMap<u64, usize>,
+    got: HashMap<u64, String>,
+    plt_stubs: BTreeMap<u64, PltStub>,
+}
+
+impl<'data> Program<'data> {
+    pub fn from_elf(data: &'data [u8]) -> Result<Self, Error> {
+        let file = ElfBytes::<AnyEndian>::minimal_parse(data)?;
+
+        if file.ehdr.e_machine != abi::EM_AARCH64 {
+            return Err(Error::UnsupportedMachineError(file.ehdr.e_machine));
+        }
+
+        let mut regions = Vec::new();
+        let mut data_regions = Vec::new();
+        let mut section_bases = HashMap::new();
+
+        let (shdrs, strtab) = file.section_headers_with_strtab()?;
+        if let Some(shdrs) = &shdrs {
+            // Lay the allocated sections of an object file out back to back, the way a
+            // trivial linker would, so that every section gets a distinct address.
+            if file.ehdr.e_type == abi::ET_REL {
+                let mut next = 0u64;
+                for (idx, shdr) in shdrs.iter().enumerate() {
+                    if shdr.sh_flags & abi::SHF_ALLOC as u64 == 0 {
+                        continue;
+                    }
+
+                    let Some(base) = next.checked_next_multiple_of(shdr.sh_addralign.max(1)) else {
+                        break;
+                    };
+                    section_bases.insert(idx as u32, base);
+                    next = base.saturating_add(shdr.sh_size);
+                }
+            }
+
+            for (idx, shdr) in shdrs.iter().enumerate() {
+                if shdr.sh_flags & abi::SHF_ALLOC as u64 == 0
+                    || shdr.sh_type == abi::SHT_NOBITS
+                    || shdr.sh_size == 0
+                {
+                    continue;
+                }
+
+                let name = match &strtab {
+                    Some(strtab) => Some(strtab.get(shdr.sh_name as usize)?.to_string()),
+                    None => None,
+                };
+                let (section_data, _) = file.section_data(&shdr)?;
+
+                let region = CodeRegion {
+                    name,
+                    address: *section_bases.get(&(idx as u32)).unwrap_or(&shdr.sh_addr),
+                    data: section_data,
+                };
+                match shdr.sh_flags & abi::SHF_EXECINSTR as u64 {
+                    0 => data_regions.push(region),
+                    _ => regions.push(region),
+                }
+            }
+        }
+
+        // Stripped section headers leave only the executable load segments to go by.
+        if regions.is_empty() {
+            data_regions.clear();
+            if let Some(phdrs) = file.segments() {
+                for phdr in phdrs.iter() {
+                    if phdr.p_type != abi::PT_LOAD {
+                        continue;
+                    }
+
+                    let region = CodeRegion {
+                        name: None,
+                        address: phdr.p_vaddr,
+                        data: file.segment_data(&phdr)?,
+                    };
+                    match phdr.p_flags & abi::PF_X {
+                        0 => data_regions.push(region),
+                        _ => regions.push(region),
+                    }
+                }
+            }
+        }
+
+        // Every function with unwind info has an FDE, symbolized or not. Object files are
+        // skipped since their FDEs only point anywhere once relocated.
+        let mut symbols = SymbolTable::from_elf(&file, &section_bases)?;
+        let eh_frame = match (&shdrs, &strtab) {
+            (Some(shdrs), Some(strtab)) if file.ehdr.e_type != abi::ET_REL => shdrs
+                .iter()
+                .find(|shdr| strtab.get(shdr.sh_name as usize).ok() == Some(".eh_frame")),
+            _ => None,
+        };
+        if let Some(shdr) = eh_frame {
+            let (eh_data, _) = file.section_data(&shdr)?;
+            let big_endian = file.ehdr.endianness == AnyEndian::Big;
+            symbols = with_functions(symbols, fde_ranges(eh_data, shdr.sh_addr, big_endian));
+        }
+
+        // Relocations against sections that were not laid out, such as .debug_info, keep
+        // their raw offsets and would otherwise shadow the ones patching code.
+        let relocations = Relocation::from_elf(&file, &section_bases)?;
+        let code_relocations = code_relocations(&regions, &relocations, |rel| {
+            file.ehdr.e_type != abi::ET_REL || section_bases.contains_key(&rel.section)
+        });
+        let got: HashMap<_, _> = relocations
+            .iter()
+            .filter(|rel| {
+                rel.kind == abi::R_AARCH64_JUMP_SLOT || rel.kind == abi::R_AARCH64_GLOB_DAT
+            })
+            .filter_map(|rel| Some((rel.offset, rel.symbol.clone()?)))
+            .collect();
+
+        let mut plt_stubs = BTreeMap::new();
+        let plt_regions = regions
+            .iter()
+            .filter(|region| matches!(region.name.as_deref(), Some(".plt" | ".plt.sec" | ".iplt")));
+        if !got.is_empty() {
+            let parser = AArch64Parser::new();
+            for region in plt_regions {
+                for stub in find_plt_stubs(&parser, region, &got) {
+                    plt_stubs.insert(stub.address, stub);
+                }
+            }
+        }
+
+        Ok(Self {
+            entry: file.ehdr.e_entry,
+            regions,
+            data_regions,
+            symbols,
+            relocations,
+            code_relocations,
+            got,
+            plt_stubs,
+        })
+    }
+
+    // For formats that carry no PLT; `got` maps import slots to names.
+    pub(crate) fn from_parts(
+        entry: u64,
+        regions: Vec<CodeRegion<'data>>,
+        data_regions: Vec<CodeRegion<'data>>,
+        symbols: SymbolTable,
+        got: HashMap<u64, String>,
+    ) -> Self {
+        Self {
+            entry,
+            regions,
+            data_regions,
+            symbols,
+            relocations: Vec::new(),
+            code_relocations: HashMap::new(),
+            got,
+            plt_stubs: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn with_relocations(mut self, relocations: Vec<Relocation>) -> Self {
+        self.code_relocations = code_relocations(&self.regions, &relocations, |_| true);
+        self.relocations = relocations;
+        self
+    }
+
+    pub fn entry(&self) -> u64 {
+        self.entry
+    }
+
+    pub fn regions(&self) -> &[CodeRegion<'data>] {
+        &self.regions
+    }
+
+    pub fn region_at(&self, address: u64) -> Option<&CodeRegion<'data>> {
+        self.regions.iter().find(|region| region.contains(address))
+    }
+
+    pub fn data_regions(&self) -> &[CodeRegion<'data>] {
+        &self.data_regions
+    }
+
+    // `len` loaded bytes at `address`, from code or data, if they are all in one region.
+    pub fn read(&self, address: u64, len: usize) -> Option<&'data [u8]> {
+        self.regions
+            .iter()
+            .chain(&self.data_regions)
+            .find_map(|region| {
+                let offset = address.checked_sub(region.address)? as usize;
+                region.data.get(offset..offset.checked_add(len)?)
+            })
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    pub fn relocations(&self) -> &[Relocation] {
+        &self.relocations
+    }
+
+    pub fn relocation_at(&self, address: u64) -> Option<&Relocation> {
+        self.code_relocations
+            .get(&address)
+            .map(|idx| &self.relocations[*idx])
+    }
+
+    // Symbolic operand for the instruction at `address`: the relocation target in object
+    // files, otherwise the label of a direct branch destination.
+    pub fn target_label(&self, address: u64, inst: &AArch64Inst) -> Option<String> {
+        if let Some(target) = self.relocation_at(address).and_then(|rel| rel.target()) {
+            return Some(target);
+        }
+
+        self.label(inst.branch_target(address)?)
+    }
+
+    pub fn got_symbol(&self, address: u64) -> Option<&str> {
+        self.got.get(&address).map(|name| name.as_str())
+    }
+
+    pub fn plt_stubs(&self) -> impl Iterator<Item = &PltStub> {
+        self.plt_stubs.values()
+    }
+
+    pub fn plt_stub(&self, address: u64) -> Option<&PltStub> {
+        self.plt_stubs.get(&address)
+    }
+
+    pub fn label(&self, address: u64) -> Option<String> {
+        match self.plt_stub(address) {
+            Some(stub) => Some(format!("{}@plt", demangle(&stub.name))),
+            None => self.symbols.label(address),
+        }
+    }
+
+    // Functions found only by following code have no symbol of their own and are called
+    // `sub_<address>` instead of being labelled relative to the preceding symbol.
+    pub fn function_label(&self, address: u64) -> String {
+        let named = self.symbols.at(address).is_some() || self.plt_stub(address).is_some();
+        let label = match named {
+            true => self.label(address),
+            false => None,
+        };
+        label.unwrap_or_else(|| format!("sub_{:x}", address))
+    }
+
+    // Sizeless functions extend up to the next function or the end of their region.
+    pub fn functions(&self) -> Vec<Function<'_, 'data>> {
+        let starts: Vec<_> = self.symbols.functions().map(|sym| sym.address).collect();
+
+        self.symbols
+            .functions()
+            .filter_map(|symbol| {
+                let region = self.region_at(symbol.address)?;
+                let region_end = region.address.saturating_add(region.data.len() as u64);
+
+                let end = match symbol.size {
+                    0 => starts
+                        .iter()
+                        .find(|start| **start > symbol.address)
+                        .map_or(region_end, |start| (*start).min(region_end)),
+                    size => symbol.address.saturating_add(size).min(region_end),
+                };
+                let offset = (symbol.address - region.address) as usize;
+                let len = (end - symbol.address) as usize;
+
+                Some(Function {
+                    symbol,
+                    code: CodeRegion {
+                        name: Some(symbol.demangled.clone()),
+                        address: symbol.address,
+                        data: &region.data[offset..offset + len],
+                    },
+                })
+            })
+            .collect()
+    }
+
+    pub fn instructions<'a>(
+        &'a self,
+        parser: &'a AArch64Parser,
+    ) -> impl Iterator<Item = (u64, u32, Result<AArch64Inst, Error>)> + 'a {
+        self.regions
+            .iter()
+            .flat_map(move |region| region.instructions(parser))
+    }
+}
+
+// Relocations patching instructions, by the address of the instruction.
+fn code_relocations(
+    regions: &[CodeRegion],
+    relocations: &[Relocation],
+    laid_out: impl Fn(&Relocation) -> bool,
+) -> HashMap<u64, usize> {
+    relocations
+        .iter()
+        .enumerate()
+        .filter(|(_, rel)| laid_out(rel))
+        .filter(|(_, rel)| regions.iter().any(|region| region.contains(rel.offset)))
+        .map(|(idx, rel)| (rel.offset, idx))
+        .collect()
+}
+
+// Adds a `sub_<address>` function for each range that has no symbol at its start yet.
+fn with_functions(symbols: SymbolTable, ranges: Vec<(u64, u64)>) -> SymbolTable {
+    let mut all: Vec<_> = symbols.iter().cloned().collect();
+    for (address, size) in ranges {
+        if symbols.at(address).is_none() {
+            let name = format!("sub_{:x}", address);
+            all.push(Symbol::new(&name, address, size, SymbolKind::Function));
+        }
+    }
+
+    SymbolTable::new(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{eh_frame, rela64, strtab, sym64, ElfBuilder, ElfSection};
+
+    #[test]
+    fn from_elf_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+
+        let text = program
+            .regions()
+            .iter()
+            .find(|region| region.name.as_deref() == Some(".text"))
+            .unwrap();
+        assert!(text.contains(program.entry()));
+
+        let parser = AArch64Parser::new();
+        let (address, _, _) = program.instructions(&parser).next().unwrap();
+        assert_eq!(address, program.regions()[0].address);
+    }
+
+    #[test]
+    fn functions_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        let functions = program.functions();
+
+        assert_eq!(functions.len(), 9);
+
+        let start = functions
+            .iter()
+            .find(|func| func.symbol.name == "_start")
+            .unwrap();
+        assert_eq!(start.code.address, program.entry());
+        assert_eq!(start.code.data.len() as u64, start.symbol.size);
+        assert_eq!(program.label(program.entry() + 4).unwrap(), "_start+0x4");
+
+        // Every direct branch in _start lands somewhere a symbol covers.
+        let parser = AArch64Parser::new();
+        for (address, _, result) in start.code.instructions(&parser) {
+            if let Some(target) = result.ok().and_then(|inst| inst.branch_target(address)) {
+                assert!(program.label(target).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn endianness_test() {
+        // sub sp, sp, #0x10; ret
+        let words = [0xd100_43ff, 0xd65f_03c0];
+
+        for big_endian in [false, true] {
+            let file_data = ElfBuilder::new(big_endian)
+                .entry(0x40_0000)
+                .section(ElfSection::text(0x40_0000, &words))
+                .build();
+            let program = Program::from_elf(&file_data).unwrap();
+            let parser = AArch64Parser::new();
+
+            let decoded: Vec<_> = program.instructions(&parser).collect();
+            assert_eq!(program.entry(), 0x40_0000);
+            assert_eq!(decoded.len(), 2);
+            assert_eq!((decoded[0].0, decoded[0].1), (0x40_0000, words[0]));
+            assert_eq!((decoded[1].0, decoded[1].1), (0x40_0004, words[1]));
+            assert!(matches!(decoded[0].2, Ok(AArch64Inst::SubImmediate64(_))));
+        }
+    }
+
+    #[test]
+    fn eh_frame_test() {
+        // f: ret; g: sub sp, sp, #0x10; ret
+        let text = ElfSection::text(0x1000, &[0xd65f_03c0, 0xd100_43ff, 0xd65f_03c0]);
+        let eh_frame = ElfSection::new(
+            ".eh_frame",
+            abi::SHT_PROGBITS,
+            abi::SHF_ALLOC as u64,
+            0x2000,
+            eh_frame(0x2000, &[(0x1000, 4), (0x1004, 8)]),
+        );
+        let file_data = ElfBuilder::new(false)
+            .entry(0x1000)
+            .section(text)
+            .section(eh_frame)
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+
+        let functions: Vec<_> = program
+            .functions()
+            .iter()
+            .map(|func| (func.symbol.name.clone(), func.code.data.len()))
+            .collect();
+        assert_eq!(
+            functions,
+            [("sub_1000".to_string(), 4), ("sub_1004".to_string(), 8)]
+        );
+    }
+
+    #[test]
+    fn segment_fallback_test() {
+        let file_data = ElfBuilder::new(false)
+            .segment(abi::PF_R | abi::PF_X, 0x1000, vec![0xff, 0x43, 0x00, 0xd1])
+            .segment(abi::PF_R, 0x2000, vec![0; 16])
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+
+        assert_eq!(program.regions().len(), 1);
+        assert_eq!(program.regions()[0].address, 0x1000);
+        assert!(program.region_at(0x1002).is_some());
+        assert!(program.region_at(0x2000).is_none());
+    }
+
+    #[test]
+    fn object_relocations_test() {
+        let (symstr, names) = strtab(&["memcpy", "buffer"]);
+        let mut symtab = sym64(0, 0, 0, 0, 0);
+        symtab.extend(sym64(
+            names[0],
+            abi::STT_FUNC | abi::STB_GLOBAL << 4,
+            0,
+            0,
+            0,
+        ));
+        symtab.extend(sym64(names[1], abi::STT_OBJECT, 2, 0x8, 0x10));
+
+        let mut relas = rela64(0x0, 1, abi::R_AARCH64_CALL26, 0);
+        relas.extend(rela64(0x4, 2, abi::R_AARCH64_ADR_PREL_PG_HI21, 0));
+        relas.extend(rela64(0x8, 2, abi::R_AARCH64_ADD_ABS_LO12_NC, 4));
+
+        // 1: .text, 2: .data, 3: .symtab, 4: .strtab, 5: .rela.text
+        let file_data = ElfBuilder::new(false)
+            .e_type(abi::ET_REL)
+            // bl 0; adrp x0, 0; add x0, x0, #0; b 0
+            .section(ElfSection::text(
+                0,
+                &[0x9400_0000, 0x9000_0000, 0x9100_0000, 0x1400_0000],
+            ))
+            .section(ElfSection::new(
+                ".data",
+                abi::SHT_PROGBITS,
+                (abi::SHF_ALLOC | abi::SHF_WRITE) as u64,
+                0,
+                vec![0; 0x20],
+            ))
+            .section(ElfSection::new(".symtab", abi::SHT_SYMTAB, 0, 0, symtab).link(4, 2, 24))
+            .section(ElfSection::new(".strtab", abi::SHT_STRTAB, 0, 0, symstr))
+            .section(ElfSection::new(".rela.text", abi::SHT_RELA, 0, 0, relas).link(3, 1, 24))
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+
+        // .data is placed right after .text, so `buffer` gets a distinct address.
+        assert_eq!(program.symbols().by_name("buffer").unwrap().address, 0x18);
+        assert_eq!(program.relocation_at(0x8).unwrap().symbol_value, Some(0x18));
+
+        let parser = AArch64Parser::new();
+        let labels: Vec<_> = program
+            .instructions(&parser)
+            .map(|(address, _, inst)| program.target_label(address, &inst.unwrap()))
+            .collect();
+        assert_eq!(labels[0].as_deref(), Some("memcpy"));
+        assert_eq!(labels[1].as_deref(), Some("buffer"));
+        assert_eq!(labels[2].as_deref(), Some(":lo12:buffer+0x4"));
+        assert_eq!(labels[3], None);
+    }
+
+    #[test]
+    fn debug_object_test() {
+        // Built with `llvm-mc -g`: .rela.debug_info has an entry at 0xc, like the call.
+        let file_data = std::fs::read("a64_debug_example.o").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+
+        let parser = AArch64Parser::new();
+        let (address, _, bl) = program.instructions(&parser).nth(3).unwrap();
+        let bl = bl.unwrap();
+        assert_eq!(address, 0xc);
+        assert!(matches!(bl, AArch64Inst::BL(_)));
+        assert_eq!(
+            program.target_label(address, &bl).as_deref(),
+            Some("memset")
+        );
+    }
+
+    #[test]
+    fn overflow_test() {
+        let region = CodeRegion {
+            name: None,
+            address: u64::MAX - 3,
+            data: &[0; 4],
+        };
+        assert!(region.contains(u64::MAX));
+        assert!(!region.contains(0));
+
+        let data = [0xc0, 0x03, 0x5f, 0xd6];
+        let region = CodeRegion {
+            name: None,
+            address: 0x1000,
+            data: &data,
+        };
+        let symbols = SymbolTable::new(vec![Symbol::new(
+            "main",
+            0x1000,
+            u64::MAX,
+            SymbolKind::Function,
+        )]);
+        let program = Program::from_parts(0, vec![region], Vec::new(), symbols, HashMap::new());
+        assert_eq!(program.functions()[0].code.data.len(), 4);
+
+        // A .bss too large to lay anything out after it.
+        let mut file_data = ElfBuilder::new(false)
+            .e_type(abi::ET_REL)
+            .section(ElfSection::new(
+                ".bss",
+                abi::SHT_NOBITS,
+                (abi::SHF_ALLOC | abi::SHF_WRITE) as u64,
+                0,
+                Vec::new(),
+            ))
+            .section(ElfSection::text(0, &[0xd65f_03c0]))
+            .build();
+        let shoff = u64::from_le_bytes(file_data[0x28..0x30].try_into().unwrap()) as usize;
+        let bss_size = shoff + 64 + 32;
+        file_data[bss_size..bss_size + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        let program = Program::from_elf(&file_data).unwrap();
+        assert_eq!(program.regions().len(), 1);
+    }
+
+    #[test]
+    fn wrong_machine_test() {
+        let file_data = ElfBuilder::new(false).machine(abi::EM_X86_64).build();
+
+        assert!(matches!(
+            Program::from_elf(&file_data),
+            Err(Error::UnsupportedMachineError(abi::EM_X86_64))
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::program::{CodeRegion, Program};
+use crate::symbols::{Symbol, SymbolKind, SymbolTable};
+
+// Where a flat image is loaded and what is known about it up front. Excluded ranges are
+// absolute addresses of data (literal pools, headers, ...) that must not be decoded.
+#[derive(Clone, Debug, Default)]
+pub struct RawLayout {
+    pub base_address: u64,
+    pub entry_points: Vec<u64>,
+    pub excluded: Vec<Range<u64>>,
+}
+
+impl<'data> Program<'data> {
+    // The first entry point becomes the program entry; all of them are labelled as functions.
+    pub fn from_raw(data: &'data [u8], layout: &RawLayout) -> Self {
+        // Regions end before u64::MAX so that their end address stays representable; bytes
+        // beyond that are dropped.
+        let base = layout.base_address;
+        let size = (data.len() as u64).min(u64::MAX - base);
+        let offset_of = |address: u64| address.saturating_sub(base).min(size);
+
+        let mut excluded = layout.excluded.clone();
+        excluded.sort_by_key(|range| range.start);
+
+        // Excluded ranges as offsets into the image; instructions are word aligned, so
+        // decoding resumes on the next word after each one.
+        let gaps = excluded.iter().map(|range| {
+            let resume = range.end.checked_next_multiple_of(4);
+            (offset_of(range.start), resume.map_or(size, offset_of))
+        });
+
+        let mut regions = Vec::new();
+        let mut start = 0;
+        for (gap_start, gap_end) in gaps.chain([(size, size)]) {
+            let region_end = gap_start.max(start);
+            if region_end > start {
+                regions.push(CodeRegion {
+                    name: None,
+                    address: base + start,
+                    data: &data[start as usize..region_end as usize],
+                });
+            }
+            start = start.max(gap_end);
+        }
+
+        let symbols = layout
+            .entry_points
+            .iter()
+            .map(|address| {
+                Symbol::new(
+                    &format!("sub_{:x}", address),
+                    *address,
+                    0,
+                    SymbolKind::Function,
+                )
+            })
+            .collect();
+
+        Self::from_parts(
+            layout
+                .entry_points
+                .first()
+                .copied()
+                .unwrap_or(layout.base_address),
+            regions,
+            Vec::new(),
+            SymbolTable::new(symbols),
+            HashMap::new(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::{AArch64Inst, AArch64Parser};
+
+    #[test]
+    fn from_raw_test() {
+        // b 0x8; .word 0xdeadbeef; ret
+        let data: Vec<u8> = [0x1400_0002u32, 0xdead_beef, 0xd65f_03c0]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        let layout = RawLayout {
+            base_address: 0x8000_0000,
+            entry_points: vec![0x8000_0000, 0x8000_0008],
+            excluded: vec![0x9000_0000..0x9000_0010, 0x8000_0004..0x8000_0006],
+        };
+        let program = Program::from_raw(&data, &layout);
+
+        assert_eq!(program.entry(), 0x8000_0000);
+        assert_eq!(program.regions().len(), 2);
+        assert!(program.region_at(0x8000_0004).is_none());
+
+        let parser = AArch64Parser::new();
+        let decoded: Vec<_> = program
+            .instructions(&parser)
+            .map(|(address, raw, _)| (address, raw))
+            .collect();
+        assert_eq!(
+            decoded,
+            [(0x8000_0000, 0x1400_0002), (0x8000_0008, 0xd65f_03c0)]
+        );
+
+        let (address, _, b) = program.instructions(&parser).next().unwrap();
+        assert_eq!(
+            program.target_label(address, &b.unwrap()).as_deref(),
+            Some("sub_80000008")
+        );
+        assert_eq!(program.functions().len(), 2);
+    }
+
+    #[test]
+    fn end_of_address_space_test() {
+        // sub sp, sp, #0x10; ret
+        let data: Vec<u8> = [0xd100_43ffu32, 0xd65f_03c0]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        let parser = AArch64Parser::new();
+
+        // The image ends at 2^64, so its last byte is cut off.
+        let layout = RawLayout {
+            base_address: 0xffff_ffff_ffff_fff8,
+            entry_points: vec![0xffff_ffff_ffff_fff8],
+            excluded: Vec::new(),
+        };
+        let program = Program::from_raw(&data, &layout);
+        let decoded: Vec<_> = program.instructions(&parser).collect();
+        assert_eq!(decoded.len(), 2);
+        assert!(matches!(decoded[0].2, Ok(AArch64Inst::SubImmediate64(_))));
+        assert!(matches!(
+            decoded[1].2,
+            Err(Error::TruncatedInstructionError(0xffff_ffff_ffff_fffc, 3))
+        ));
+        assert_eq!(program.functions()[0].code.data.len(), 7);
+
+        let layout = RawLayout {
+            base_address: 0xffff_ffff_ffff_fffc,
+            ..layout
+        };
+        let program = Program::from_raw(&data, &layout);
+        assert_eq!(program.regions()[0].data.len(), 3);
+
+        // An excluded range whose end cannot be rounded up to the next word.
+        let layout = RawLayout {
+            base_address: 0xffff_ffff_ffff_fff8,
+            entry_points: Vec::new(),
+            excluded: vec![0x1000..0x1010, 0xffff_ffff_ffff_fffe..u64::MAX],
+        };
+        let program = Program::from_raw(&data, &layout);
+        assert_eq!(program.regions().len(), 1);
+        assert_eq!(program.regions()[0].data.len(), 6);
+    }
+}
@@ -0,0 +1,240 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(feature = "alloc")]
+use crate::aarc64_parser::AArch64Parser;
+#[cfg(feature = "alloc")]
+use crate::error::Error;
+use crate::AArch64Inst;
+
+// Bytes handed to each worker; a multiple of the instruction size.
+#[cfg(feature = "rayon")]
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Which `AArch64Inst` variant a word decoded to, without its operands. `Unknown` stands in
+// for words the decoder does not cover yet.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Opcode {
+    ADR,
+    ADRP,
+
+    AddImmediate64,
+    SubImmediate64,
+    SUBSImmediate32,
+    SUBSImmediate64,
+
+    MOVN32,
+    MOVZ32,
+    MOVK32,
+    MOVN64,
+    MOVZ64,
+    MOVK64,
+
+    BCond,
+    BcCond,
+
+    B,
+    BL,
+
+    CBZ32,
+    CBNZ32,
+    CBZ64,
+    CBNZ64,
+
+    TBZ,
+    TBNZ,
+
+    BR,
+    BLR,
+    RET,
+
+    STRImmediate64,
+    LDRImmediate64,
+    STRImmediatePreIndexed32,
+    STRImmediatePreIndexed64,
+    STRImmediatePostIndexed32,
+    STRImmediatePostIndexed64,
+    LDRImmediatePreIndexed32,
+    LDRImmediatePreIndexed64,
+    LDRImmediatePostIndexed32,
+    LDRImmediatePostIndexed64,
+    LDRBRegister,
+    LDRHRegister,
+    LDRRegister32,
+    LDRRegister64,
+    LDRSWRegister,
+    LDRLiteral32,
+    LDRLiteral64,
+    LDRSWLiteral,
+
+    ADDShiftedRegister64,
+    SUBSShiftedRegister64,
+    ADDExtendedRegister64,
+
+    UNALLOCATED,
+    RESERVED,
+    UNDEFINED,
+    UNPREDICTABLE,
+
+    Unknown,
+}
+
+impl AArch64Inst {
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            AArch64Inst::ADR(..) => Opcode::ADR,
+            AArch64Inst::ADRP(..) => Opcode::ADRP,
+            AArch64Inst::AddImmediate64(..) => Opcode::AddImmediate64,
+            AArch64Inst::SubImmediate64(..) => Opcode::SubImmediate64,
+            AArch64Inst::SUBSImmediate32(..) => Opcode::SUBSImmediate32,
+            AArch64Inst::SUBSImmediate64(..) => Opcode::SUBSImmediate64,
+            AArch64Inst::MOVN32(..) => Opcode::MOVN32,
+            AArch64Inst::MOVZ32(..) => Opcode::MOVZ32,
+            AArch64Inst::MOVK32(..) => Opcode::MOVK32,
+            AArch64Inst::MOVN64(..) => Opcode::MOVN64,
+            AArch64Inst::MOVZ64(..) => Opcode::MOVZ64,
+            AArch64Inst::MOVK64(..) => Opcode::MOVK64,
+            AArch64Inst::BCond(..) => Opcode::BCond,
+            AArch64Inst::BcCond(..) => Opcode::BcCond,
+            AArch64Inst::B(..) => Opcode::B,
+            AArch64Inst::BL(..) => Opcode::BL,
+            AArch64Inst::CBZ32(..) => Opcode::CBZ32,
+            AArch64Inst::CBNZ32(..) => Opcode::CBNZ32,
+            AArch64Inst::CBZ64(..) => Opcode::CBZ64,
+            AArch64Inst::CBNZ64(..) => Opcode::CBNZ64,
+            AArch64Inst::TBZ(..) => Opcode::TBZ,
+            AArch64Inst::TBNZ(..) => Opcode::TBNZ,
+            AArch64Inst::BR(..) => Opcode::BR,
+            AArch64Inst::BLR(..) => Opcode::BLR,
+            AArch64Inst::RET(..) => Opcode::RET,
+            AArch64Inst::STRImmediate64(..) => Opcode::STRImmediate64,
+            AArch64Inst::LDRImmediate64(..) => Opcode::LDRImmediate64,
+            AArch64Inst::STRImmediatePreIndexed32(..) => Opcode::STRImmediatePreIndexed32,
+            AArch64Inst::STRImmediatePreIndexed64(..) => Opcode::STRImmediatePreIndexed64,
+            AArch64Inst::STRImmediatePostIndexed32(..) => Opcode::STRImmediatePostIndexed32,
+            AArch64Inst::STRImmediatePostIndexed64(..) => Opcode::STRImmediatePostIndexed64,
+            AArch64Inst::LDRImmediatePreIndexed32(..) => Opcode::LDRImmediatePreIndexed32,
+            AArch64Inst::LDRImmediatePreIndexed64(..) => Opcode::LDRImmediatePreIndexed64,
+            AArch64Inst::LDRImmediatePostIndexed32(..) => Opcode::LDRImmediatePostIndexed32,
+            AArch64Inst::LDRImmediatePostIndexed64(..) => Opcode::LDRImmediatePostIndexed64,
+            AArch64Inst::LDRBRegister(..) => Opcode::LDRBRegister,
+            AArch64Inst::LDRHRegister(..) => Opcode::LDRHRegister,
+            AArch64Inst::LDRRegister32(..) => Opcode::LDRRegister32,
+            AArch64Inst::LDRRegister64(..) => Opcode::LDRRegister64,
+            AArch64Inst::LDRSWRegister(..) => Opcode::LDRSWRegister,
+            AArch64Inst::LDRLiteral32(..) => Opcode::LDRLiteral32,
+            AArch64Inst::LDRLiteral64(..) => Opcode::LDRLiteral64,
+            AArch64Inst::LDRSWLiteral(..) => Opcode::LDRSWLiteral,
+            AArch64Inst::ADDShiftedRegister64(..) => Opcode::ADDShiftedRegister64,
+            AArch64Inst::SUBSShiftedRegister64(..) => Opcode::SUBSShiftedRegister64,
+            AArch64Inst::ADDExtendedRegister64(..) => Opcode::ADDExtendedRegister64,
+            AArch64Inst::UNALLOCATED(..) => Opcode::UNALLOCATED,
+            AArch64Inst::RESERVED(..) => Opcode::RESERVED,
+            AArch64Inst::UNDEFINED(..) => Opcode::UNDEFINED,
+            AArch64Inst::UNPREDICTABLE(..) => Opcode::UNPREDICTABLE,
+        }
+    }
+}
+
+// A decoded word in eight bytes; its address is implied by its position in the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InstRecord {
+    pub raw: u32,
+    pub opcode: Opcode,
+}
+
+#[cfg(feature = "alloc")]
+impl AArch64Parser {
+    fn decode_record(&self, bytes: &[u8]) -> InstRecord {
+        let raw = u32::from_le_bytes(bytes.try_into().unwrap());
+        let opcode = match self.parse(raw) {
+            Ok(inst) => inst.opcode(),
+            Err(_) => Opcode::Unknown,
+        };
+
+        InstRecord { raw, opcode }
+    }
+
+    pub fn decode_records(&self, data: &[u8]) -> Result<Vec<InstRecord>, Error> {
+        check_len(data)?;
+
+        Ok(data
+            .chunks_exact(4)
+            .map(|bytes| self.decode_record(bytes))
+            .collect())
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn par_decode_records(&self, data: &[u8]) -> Result<Vec<InstRecord>, Error> {
+        check_len(data)?;
+
+        Ok(data
+            .par_chunks(CHUNK_SIZE)
+            .flat_map_iter(|chunk| chunk.chunks_exact(4).map(|bytes| self.decode_record(bytes)))
+            .collect())
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn check_len(data: &[u8]) -> Result<(), Error> {
+    match data.len() % 4 {
+        0 => Ok(()),
+        len => {
+            let address = (data.len() - len) as u64;
+            Err(Error::TruncatedInstructionError(address, len))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn decode_records_test() {
+        assert_send_sync::<AArch64Parser>();
+        assert_eq!(std::mem::size_of::<InstRecord>(), 8);
+
+        let parser = AArch64Parser::new();
+        let file_data = std::fs::read("a64_example").unwrap();
+        let records = parser.decode_records(&file_data).unwrap();
+        assert_eq!(records.len(), file_data.len() / 4);
+
+        // ret; udf #0; an encoding outside the decoder's coverage
+        let data = [0xd65f_03c0u32, 0x0000_0000, 0x5e20_0400]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        let opcodes: Vec<_> = parser
+            .decode_records(&data)
+            .unwrap()
+            .iter()
+            .map(|record| record.opcode)
+            .collect();
+        assert_eq!(opcodes, [Opcode::RET, Opcode::UNDEFINED, Opcode::Unknown]);
+
+        assert!(matches!(
+            parser.decode_records(&data[..6]),
+            Err(Error::TruncatedInstructionError(4, 2))
+        ));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_decode_records_test() {
+        let parser = AArch64Parser::new();
+        let file_data = std::fs::read("a64_example").unwrap();
+
+        assert_eq!(
+            parser.par_decode_records(&file_data).unwrap(),
+            parser.decode_records(&file_data).unwrap()
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use core::fmt;
+
+use crate::AArch64Inst;
+
+// General purpose registers by number, with 31 being the stack pointer, plus the flags.
+// W registers are the low halves of X registers and share their number; the zero register
+// is never part of a set since reading it yields nothing and writing it is discarded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Register {
+    X(u8),
+    Sp,
+    Nzcv,
+}
+
+impl Register {
+    fn bit(&self) -> u8 {
+        match self {
+            Register::X(num) => *num,
+            Register::Sp => 31,
+            Register::Nzcv => 32,
+        }
+    }
+
+    fn from_bit(bit: u8) -> Self {
+        match bit {
+            31 => Register::Sp,
+            32 => Register::Nzcv,
+            num => Register::X(num),
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Register::X(num) => write!(f, "x{}", num),
+            Register::Sp => f.write_str("sp"),
+            Register::Nzcv => f.write_str("nzcv"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RegisterSet(u64);
+
+impl RegisterSet {
+    pub fn contains(&self, reg: Register) -> bool {
+        self.0 & 1 << reg.bit() != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Register> + '_ {
+        (0..=32)
+            .filter(|bit| self.0 & 1 << bit != 0)
+            .map(Register::from_bit)
+    }
+
+    fn with(mut self, reg: Register) -> Self {
+        self.0 |= 1 << reg.bit();
+        self
+    }
+
+    // Register 31 of an operand that cannot be the stack pointer is the zero register.
+    fn with_gpr(self, num: u32) -> Self {
+        match num {
+            31 => self,
+            num => self.with(Register::X(num as u8)),
+        }
+    }
+
+    fn with_gpr_or_sp(self, num: u32) -> Self {
+        match num {
+            31 => self.with(Register::Sp),
+            num => self.with(Register::X(num as u8)),
+        }
+    }
+}
+
+impl AArch64Inst {
+    pub fn reads(&self) -> RegisterSet {
+        let set = RegisterSet::default();
+
+        match self {
+            AArch64Inst::AddImmediate64(data) | AArch64Inst::SubImmediate64(data) => {
+                set.with_gpr_or_sp(data.rn)
+            }
+            AArch64Inst::SUBSImmediate32(data) | AArch64Inst::SUBSImmediate64(data) => {
+                set.with_gpr_or_sp(data.rn)
+            }
+            // MOVK keeps the other bits of its destination.
+            AArch64Inst::MOVK32(data) | AArch64Inst::MOVK64(data) => set.with_gpr(data.rd),
+            AArch64Inst::BCond(_) | AArch64Inst::BcCond(_) => set.with(Register::Nzcv),
+            AArch64Inst::CBZ32(data)
+            | AArch64Inst::CBNZ32(data)
+            | AArch64Inst::CBZ64(data)
+            | AArch64Inst::CBNZ64(data) => set.with_gpr(data.rt),
+            AArch64Inst::TBZ(data) | AArch64Inst::TBNZ(data) => set.with_gpr(data.rt),
+            AArch64Inst::BR(data) | AArch64Inst::BLR(data) | AArch64Inst::RET(data) => {
+                set.with_gpr(data.rn)
+            }
+            AArch64Inst::STRImmediate64(data) => set.with_gpr(data.rt).with_gpr_or_sp(data.rn),
+            AArch64Inst::LDRImmediate64(data) => set.with_gpr_or_sp(data.rn),
+            AArch64Inst::STRImmediatePreIndexed32(data)
+            | AArch64Inst::STRImmediatePreIndexed64(data)
+            | AArch64Inst::STRImmediatePostIndexed32(data)
+            | AArch64Inst::STRImmediatePostIndexed64(data) => {
+                set.with_gpr(data.rt).with_gpr_or_sp(data.rn)
+            }
+            AArch64Inst::LDRImmediatePreIndexed32(data)
+            | AArch64Inst::LDRImmediatePreIndexed64(data)
+            | AArch64Inst::LDRImmediatePostIndexed32(data)
+            | AArch64Inst::LDRImmediatePostIndexed64(data) => set.with_gpr_or_sp(data.rn),
+            AArch64Inst::LDRBRegister(data)
+            | AArch64Inst::LDRHRegister(data)
+            | AArch64Inst::LDRRegister32(data)
+            | AArch64Inst::LDRRegister64(data)
+            | AArch64Inst::LDRSWRegister(data) => set.with_gpr_or_sp(data.rn).with_gpr(data.rm),
+            AArch64Inst::ADDShiftedRegister64(data) | AArch64Inst::SUBSShiftedRegister64(data) => {
+                set.with_gpr(data.rn).with_gpr(data.rm)
+            }
+            AArch64Inst::ADDExtendedRegister64(data) => {
+                set.with_gpr_or_sp(data.rn).with_gpr(data.rm)
+            }
+            AArch64Inst::ADR(_)
+            | AArch64Inst::ADRP(_)
+            | AArch64Inst::LDRLiteral32(_)
+            | AArch64Inst::LDRLiteral64(_)
+            | AArch64Inst::LDRSWLiteral(_)
+            | AArch64Inst::MOVN32(_)
+            | AArch64Inst::MOVZ32(_)
+            | AArch64Inst::MOVN64(_)
+            | AArch64Inst::MOVZ64(_)
+            | AArch64Inst::B(_)
+            | AArch64Inst::BL(_)
+            | AArch64Inst::UNALLOCATED(_)
+            | AArch64Inst::RESERVED(_)
+            | AArch64Inst::UNDEFINED(_)
+            | AArch64Inst::UNPREDICTABLE(..) => set,
+        }
+    }
+
+    pub fn writes(&self) -> RegisterSet {
+        let set = RegisterSet::default();
+
+        match self {
+            AArch64Inst::ADR(data) | AArch64Inst::ADRP(data) => set.with_gpr(data.rd),
+            AArch64Inst::AddImmediate64(data) | AArch64Inst::SubImmediate64(data) => {
+                set.with_gpr_or_sp(data.rd)
+            }
+            AArch64Inst::SUBSImmediate32(data) | AArch64Inst::SUBSImmediate64(data) => {
+                set.with_gpr(data.rd).with(Register::Nzcv)
+            }
+            AArch64Inst::MOVN32(data)
+            | AArch64Inst::MOVZ32(data)
+            | AArch64Inst::MOVK32(data)
+            | AArch64Inst::MOVN64(data)
+            | AArch64Inst::MOVZ64(data)
+            | AArch64Inst::MOVK64(data) => set.with_gpr(data.rd),
+            AArch64Inst::BL(_) | AArch64Inst::BLR(_) => set.with(Register::X(30)),
+            AArch64Inst::LDRImmediate64(data) => set.with_gpr(data.rt),
+            AArch64Inst::LDRBRegister(data)
+            | AArch64Inst::LDRHRegister(data)
+            | AArch64Inst::LDRRegister32(data)
+            | AArch64Inst::LDRRegister64(data)
+            | AArch64Inst::LDRSWRegister(data) => set.with_gpr(data.rt),
+            AArch64Inst::LDRLiteral32(data)
+            | AArch64Inst::LDRLiteral64(data)
+            | AArch64Inst::LDRSWLiteral(data) => set.with_gpr(data.rt),
+            // Writeback updates the base register.
+            AArch64Inst::STRImmediatePreIndexed32(data)
+            | AArch64Inst::STRImmediatePreIndexed64(data)
+            | AArch64Inst::STRImmediatePostIndexed32(data)
+            | AArch64Inst::STRImmediatePostIndexed64(data) => set.with_gpr_or_sp(data.rn),
+            AArch64Inst::LDRImmediatePreIndexed32(data)
+            | AArch64Inst::LDRImmediatePreIndexed64(data)
+            | AArch64Inst::LDRImmediatePostIndexed32(data)
+            | AArch64Inst::LDRImmediatePostIndexed64(data) => {
+                set.with_gpr(data.rt).with_gpr_or_sp(data.rn)
+            }
+            AArch64Inst::ADDShiftedRegister64(data) => set.with_gpr(data.rd),
+            AArch64Inst::SUBSShiftedRegister64(data) => set.with_gpr(data.rd).with(Register::Nzcv),
+            AArch64Inst::ADDExtendedRegister64(data) => set.with_gpr_or_sp(data.rd),
+            AArch64Inst::BCond(_)
+            | AArch64Inst::BcCond(_)
+            | AArch64Inst::B(_)
+            | AArch64Inst::CBZ32(_)
+            | AArch64Inst::CBNZ32(_)
+            | AArch64Inst::CBZ64(_)
+            | AArch64Inst::CBNZ64(_)
+            | AArch64Inst::TBZ(_)
+            | AArch64Inst::TBNZ(_)
+            | AArch64Inst::BR(_)
+            | AArch64Inst::RET(_)
+            | AArch64Inst::STRImmediate64(_)
+            | AArch64Inst::UNALLOCATED(_)
+            | AArch64Inst::RESERVED(_)
+            | AArch64Inst::UNDEFINED(_)
+            | AArch64Inst::UNPREDICTABLE(..) => set,
+        }
+    }
+}
+
+// Serialized as a list of register names, e.g. `["x0", "sp"]`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegisterSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(RegisterName))
+    }
+}
+
+#[cfg(feature = "serde")]
+struct RegisterName(Register);
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegisterName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aarc64_parser::decode;
+
+    fn names(set: RegisterSet) -> Vec<String> {
+        set.iter().map(|reg| reg.to_string()).collect()
+    }
+
+    #[test]
+    fn registers_test() {
+        // str x29, [sp, #-16]!
+        let inst = decode(0xf81f_0ffd).unwrap();
+        assert_eq!(names(inst.reads()), ["x29", "sp"]);
+        assert_eq!(names(inst.writes()), ["sp"]);
+
+        // cmp x1, x2
+        let inst = decode(0xeb02_003f).unwrap();
+        assert_eq!(names(inst.reads()), ["x1", "x2"]);
+        assert_eq!(names(inst.writes()), ["nzcv"]);
+
+        // bl; b.eq
+        assert!(decode(0x9400_0002)
+            .unwrap()
+            .writes()
+            .contains(Register::X(30)));
+        assert!(decode(0x5400_0040)
+            .unwrap()
+            .reads()
+            .contains(Register::Nzcv));
+
+        // ret
+        let inst = decode(0xd65f_03c0).unwrap();
+        assert_eq!(names(inst.reads()), ["x30"]);
+        assert!(inst.writes().is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use elf::abi;
+use elf::endian::AnyEndian;
+use elf::string_table::StringTable;
+use elf::symbol::SymbolTable as ElfSymbolTable;
+use elf::ElfBytes;
+use goblin::container::{Container, Ctx, Endian};
+use goblin::mach::relocation::{
+    ARM64_RELOC_ADDEND, ARM64_RELOC_BRANCH26, ARM64_RELOC_GOT_LOAD_PAGE21,
+    ARM64_RELOC_GOT_LOAD_PAGEOFF12, ARM64_RELOC_PAGE21, ARM64_RELOC_PAGEOFF12,
+    ARM64_RELOC_UNSIGNED,
+};
+use goblin::mach::MachO;
+
+use crate::error::Error;
+use crate::symbols::demangle;
+
+#[derive(Clone, Debug)]
+pub struct Relocation {
+    pub section: u32,
+    pub offset: u64,
+    pub kind: u32,
+    pub symbol: Option<String>,
+    // None for symbols the file does not define.
+    pub symbol_value: Option<u64>,
+    pub addend: i64,
+}
+
+impl Relocation {
+    // Relocatable objects have no addresses of their own, so offsets and symbol values are
+    // rebased onto the load address the program assigned to each section.
+    pub(crate) fn from_elf(
+        file: &ElfBytes<AnyEndian>,
+        section_bases: &HashMap<u32, u64>,
+    ) -> Result<Vec<Self>, Error> {
+        let (shdrs, shstrtab) = match file.section_headers_with_strtab()? {
+            (Some(shdrs), shstrtab) => (shdrs, shstrtab),
+            (None, _) => return Ok(Vec::new()),
+        };
+
+        let mut relocations = Vec::new();
+
+        for rela_shdr in shdrs.iter().filter(|shdr| shdr.sh_type == abi::SHT_RELA) {
+            let symbols = match rela_shdr.sh_link {
+                0 => None,
+                link => {
+                    let symtab_shdr = shdrs.get(link as usize)?;
+                    let strtab_shdr = shdrs.get(symtab_shdr.sh_link as usize)?;
+                    let (symtab_data, _) = file.section_data(&symtab_shdr)?;
+                    let (strtab_data, _) = file.section_data(&strtab_shdr)?;
+
+                    Some((
+                        ElfSymbolTable::new(file.ehdr.endianness, file.ehdr.class, symtab_data),
+                        StringTable::new(strtab_data),
+                    ))
+                }
+            };
+
+            for rela in file.section_data_as_relas(&rela_shdr)? {
+                let (symbol, symbol_value) = match (&symbols, rela.r_sym) {
+                    (Some((symtab, strtab)), idx) if idx != 0 => {
+                        let sym = symtab.get(idx as usize)?;
+                        // Section symbols are unnamed; refer to them by their section instead.
+                        let name = match (sym.st_symtype(), &shstrtab) {
+                            (abi::STT_SECTION, Some(shstrtab)) => {
+                                let shdr = shdrs.get(sym.st_shndx as usize)?;
+                                shstrtab.get(shdr.sh_name as usize)?
+                            }
+                            _ => strtab.get(sym.st_name as usize)?,
+                        };
+
+                        let base = section_bases.get(&(sym.st_shndx as u32));
+                        let value = match sym.is_undefined() {
+                            true => None,
+                            false => Some(sym.st_value.wrapping_add(*base.unwrap_or(&0))),
+                        };
+                        (Some(name.to_string()), value)
+                    }
+                    _ => (None, None),
+                };
+
+                let base = section_bases.get(&rela_shdr.sh_info);
+                relocations.push(Relocation {
+                    section: rela_shdr.sh_info,
+                    offset: rela.r_offset.wrapping_add(*base.unwrap_or(&0)),
+                    kind: rela.r_type,
+                    symbol,
+                    symbol_value,
+                    addend: rela.r_addend,
+                });
+            }
+        }
+
+        Ok(relocations)
+    }
+
+    // Mach-O relocation types are translated to their ELF counterparts, which render the
+    // same way. Sections are numbered from 1 across all segments, like `n_sect` does.
+    pub(crate) fn from_macho(macho: &MachO, data: &[u8]) -> Result<Vec<Self>, Error> {
+        let mut sections = Vec::new();
+        for segment in macho.segments.iter() {
+            for (section, _) in segment.sections()? {
+                sections.push(section);
+            }
+        }
+
+        let ctx = Ctx::new(Container::Big, Endian::Little);
+        let mut relocations = Vec::new();
+
+        for (idx, section) in sections.iter().enumerate() {
+            // ARM64_RELOC_ADDEND carries the addend of the relocation following it.
+            let mut addend = 0;
+            for info in section.iter_relocations(data, ctx) {
+                let info = info?;
+                let symbolnum = info.r_symbolnum();
+                if info.r_type() == ARM64_RELOC_ADDEND {
+                    addend = ((symbolnum as i64) << 40) >> 40;
+                    continue;
+                }
+
+                let (symbol, symbol_value) = match info.is_extern() {
+                    true => {
+                        let (name, nlist) = match &macho.symbols {
+                            Some(symbols) => symbols.get(symbolnum)?,
+                            None => continue,
+                        };
+                        let name = name.strip_prefix('_').unwrap_or(name);
+                        let value = (!nlist.is_undefined()).then_some(nlist.n_value);
+                        (name.to_string(), value)
+                    }
+                    false => match symbolnum.checked_sub(1).and_then(|n| sections.get(n)) {
+                        Some(target) => (target.name()?.to_string(), Some(target.addr)),
+                        None => continue,
+                    },
+                };
+
+                let kind = match (info.r_type(), info.r_length()) {
+                    (ARM64_RELOC_BRANCH26, _) => abi::R_AARCH64_CALL26,
+                    (ARM64_RELOC_PAGE21, _) => abi::R_AARCH64_ADR_PREL_PG_HI21,
+                    (ARM64_RELOC_PAGEOFF12, _) => abi::R_AARCH64_ADD_ABS_LO12_NC,
+                    (ARM64_RELOC_GOT_LOAD_PAGE21, _) => abi::R_AARCH64_ADR_GOT_PAGE,
+                    (ARM64_RELOC_GOT_LOAD_PAGEOFF12, _) => abi::R_AARCH64_LD64_GOT_LO12_NC,
+                    (ARM64_RELOC_UNSIGNED, 3) => abi::R_AARCH64_ABS64,
+                    (ARM64_RELOC_UNSIGNED, _) => abi::R_AARCH64_ABS32,
+                    _ => abi::R_AARCH64_NONE,
+                };
+
+                relocations.push(Relocation {
+                    section: idx as u32 + 1,
+                    offset: section.addr.wrapping_add(info.r_address as u32 as u64),
+                    kind,
+                    symbol: Some(symbol),
+                    symbol_value,
+                    addend: core::mem::take(&mut addend),
+                });
+            }
+        }
+
+        Ok(relocations)
+    }
+
+    // Where the relocated operand points once linked: the symbol plus the addend.
+    pub fn address(&self) -> Option<u64> {
+        Some(self.symbol_value?.wrapping_add_signed(self.addend))
+    }
+
+    // Renders the relocated operand in assembler syntax, e.g. `memcpy` or `:lo12:.rodata+0x10`.
+    pub fn target(&self) -> Option<String> {
+        let symbol = demangle(self.symbol.as_deref()?);
+        let expr = match self.addend {
+            0 => symbol,
+            addend if addend > 0 => format!("{}+{:#x}", symbol, addend),
+            addend => format!("{}-{:#x}", symbol, addend.unsigned_abs()),
+        };
+
+        match self.kind {
+            abi::R_AARCH64_CALL26
+            | abi::R_AARCH64_JUMP26
+            | abi::R_AARCH64_CONDBR19
+            | abi::R_AARCH64_TSTBR14
+            | abi::R_AARCH64_ADR_PREL_LO21
+            | abi::R_AARCH64_ADR_PREL_PG_HI21
+            | abi::R_AARCH64_ADR_PREL_PG_HI21_NC => Some(expr),
+            abi::R_AARCH64_ADD_ABS_LO12_NC
+            | abi::R_AARCH64_LDST8_ABS_LO12_NC
+            | abi::R_AARCH64_LDST16_ABS_LO12_NC
+            | abi::R_AARCH64_LDST32_ABS_LO12_NC
+            | abi::R_AARCH64_LDST64_ABS_LO12_NC
+            | abi::R_AARCH64_LDST128_ABS_LO12_NC => Some(format!(":lo12:{}", expr)),
+            abi::R_AARCH64_ADR_GOT_PAGE => Some(format!(":got:{}", expr)),
+            abi::R_AARCH64_LD64_GOT_LO12_NC => Some(format!(":got_lo12:{}", expr)),
+            _ => None,
+        }
+    }
+}
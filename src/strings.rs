@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use crate::aarc64_parser::AArch64Parser;
+use crate::program::Program;
+use crate::xrefs::Xrefs;
+use crate::AArch64Inst;
+
+// Shorter runs of text turn up by chance in any data.
+const MIN_LEN: usize = 4;
+const MAX_LEN: usize = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StringKind {
+    // Text referenced directly, running up to a NUL or the first byte that is not text.
+    Text,
+    // A Rust `&str`: the reference is to a pointer and a length, like in a panic message
+    // argument or at the start of a `core::panic::Location`.
+    Slice,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StringRef {
+    // The referenced address, which for slices holds the pointer.
+    pub address: u64,
+    pub text_address: u64,
+    pub text: String,
+    pub kind: StringKind,
+}
+
+// An entry of a literal pool, as loaded by `LDR (literal)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Literal {
+    pub address: u64,
+    pub size: u8,
+    // Extended the way the load extends it.
+    pub value: u64,
+}
+
+impl Program<'_> {
+    pub fn string_at(&self, address: u64) -> Option<StringRef> {
+        if let Some((text_address, text)) = self.slice_at(address) {
+            return Some(StringRef {
+                address,
+                text_address,
+                text: text.to_string(),
+                kind: StringKind::Slice,
+            });
+        }
+
+        let bytes = self.bytes_from(address)?;
+        let bytes = &bytes[..bytes.len().min(MAX_LEN)];
+        let end = bytes
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(bytes.len());
+        let text = match core::str::from_utf8(&bytes[..end]) {
+            Ok(text) => text,
+            Err(err) => core::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap(),
+        };
+        let len = text
+            .char_indices()
+            .find(|(_, c)| !is_text(*c))
+            .map_or(text.len(), |(idx, _)| idx);
+
+        (len >= MIN_LEN).then(|| StringRef {
+            address,
+            text_address: address,
+            text: text[..len].to_string(),
+            kind: StringKind::Text,
+        })
+    }
+
+    // Strings at the data addresses code refers to, in address order.
+    pub fn strings(&self, xrefs: &Xrefs) -> Vec<StringRef> {
+        xrefs
+            .targets()
+            .filter(|address| self.region_at(*address).is_none())
+            .filter_map(|address| self.string_at(address))
+            .collect()
+    }
+
+    // Every literal pool entry loaded from within the code regions, in address order.
+    pub fn literals(&self, parser: &AArch64Parser) -> Vec<Literal> {
+        let mut literals = BTreeMap::new();
+
+        for (address, _, result) in self.instructions(parser) {
+            let Ok(inst) = result else {
+                continue;
+            };
+            if let Some(literal) = self.literal(address, &inst) {
+                literals.insert(literal.address, literal);
+            }
+        }
+
+        literals.into_values().collect()
+    }
+
+    // The entry a literal load at `address` reads, if it is loaded.
+    pub fn literal(&self, address: u64, inst: &AArch64Inst) -> Option<Literal> {
+        let size = match inst {
+            AArch64Inst::LDRLiteral64(_) => 8,
+            AArch64Inst::LDRLiteral32(_) | AArch64Inst::LDRSWLiteral(_) => 4,
+            _ => return None,
+        };
+        let pool = inst.pc_rel_address(address)?;
+        let bytes = self.read(pool, size)?;
+
+        let mut raw = [0; 8];
+        raw[..size].copy_from_slice(bytes);
+        let value = match inst {
+            AArch64Inst::LDRSWLiteral(_) => u64::from_le_bytes(raw) as i32 as i64 as u64,
+            _ => u64::from_le_bytes(raw),
+        };
+
+        Some(Literal {
+            address: pool,
+            size: size as u8,
+            value,
+        })
+    }
+
+    // Rust lays out `&str` as a pointer followed by a length, both eight byte aligned.
+    fn slice_at(&self, address: u64) -> Option<(u64, &str)> {
+        if !address.is_multiple_of(8) {
+            return None;
+        }
+
+        let fat = self.read(address, 16)?;
+        let ptr = u64::from_le_bytes(fat[..8].try_into().unwrap());
+        let len = u64::from_le_bytes(fat[8..].try_into().unwrap()) as usize;
+        if !(MIN_LEN..=MAX_LEN).contains(&len) {
+            return None;
+        }
+
+        let text = core::str::from_utf8(self.read(ptr, len)?).ok()?;
+        text.chars().all(is_text).then_some((ptr, text))
+    }
+
+    fn bytes_from(&self, address: u64) -> Option<&[u8]> {
+        self.regions()
+            .iter()
+            .chain(self.data_regions())
+            .find_map(|region| {
+                let offset = address.checked_sub(region.address)? as usize;
+                region.data.get(offset..)
+            })
+    }
+}
+
+fn is_text(c: char) -> bool {
+    !c.is_control() || matches!(c, '\n' | '\r' | '\t')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ElfBuilder, ElfSection};
+    use elf::abi;
+
+    #[test]
+    fn strings_test() {
+        // 101000: adr x0, 0x102000
+        // 101004: adrp x1, 0x102000
+        // 101008: add x1, x1, #8
+        // 10100c: ldr x2, 0x101018
+        // 101010: adr x3, 0x102028
+        // 101014: ret
+        // 101018: .xword 0x10200c
+        let text = ElfSection::text(
+            0x10_1000,
+            &[
+                0x1000_8000,
+                0xb000_0001,
+                0x9100_2021,
+                0x5800_0062,
+                0x1000_80c3,
+                0xd65f_03c0,
+                0x0010_200c,
+                0x0000_0000,
+            ],
+        );
+        let mut rodata = b"oops\0\0\0\0\0\0\0\0called `Option::unwrap()`".to_vec();
+        rodata.extend([0, 0, 0]);
+        rodata.extend(0x10_200cu64.to_le_bytes());
+        rodata.extend(25u64.to_le_bytes());
+        let rodata = ElfSection::new(
+            ".rodata",
+            abi::SHT_PROGBITS,
+            abi::SHF_ALLOC as u64,
+            0x10_2000,
+            rodata,
+        );
+        let file_data = ElfBuilder::new(false)
+            .entry(0x10_1000)
+            .section(text)
+            .section(rodata)
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+        let parser = AArch64Parser::new();
+        let xrefs = program.xrefs(&parser);
+
+        let found: Vec<_> = program
+            .strings(&xrefs)
+            .into_iter()
+            .map(|string| {
+                (
+                    string.address,
+                    string.text_address,
+                    string.text,
+                    string.kind,
+                )
+            })
+            .collect();
+        let unwrap = "called `Option::unwrap()`".to_string();
+        assert_eq!(
+            found,
+            [
+                (0x10_2000, 0x10_2000, "oops".to_string(), StringKind::Text),
+                (0x10_200c, 0x10_200c, unwrap.clone(), StringKind::Text),
+                (0x10_2028, 0x10_200c, unwrap, StringKind::Slice),
+            ]
+        );
+        // The ADRP+ADD lands in the middle of the NUL padding.
+        assert!(program.string_at(0x10_2008).is_none());
+
+        assert_eq!(
+            program.literals(&parser),
+            [Literal {
+                address: 0x10_1018,
+                size: 8,
+                value: 0x10_200c
+            }]
+        );
+        assert!(xrefs.refs_to(0x10_200c).any(|xref| xref.from == 0x10_100c));
+    }
+
+    #[test]
+    fn strings_elf_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        let xrefs = program.xrefs(&AArch64Parser::new());
+        let strings = program.strings(&xrefs);
+
+        // The panic message compiler_fence passes to panic_fmt, and its location.
+        let fence = program
+            .symbols()
+            .by_name("core::sync::atomic::compiler_fence")
+            .unwrap();
+        let message = strings
+            .iter()
+            .find(|string| string.address == 0x20_01f0)
+            .unwrap();
+        assert_eq!(message.kind, StringKind::Slice);
+        assert_eq!(
+            message.text,
+            "there is no such thing as a relaxed compiler fence"
+        );
+        assert!(xrefs
+            .refs_to(message.address)
+            .any(|xref| xref.function == Some(fence.address)));
+
+        let location = program.string_at(0x20_0250).unwrap();
+        assert_eq!(location.text_address, 0x20_0200);
+        assert!(location.text.ends_with("/library/core/src/sync/atomic.rs"));
+
+        let overflow = program.string_at(0x20_02a0).unwrap();
+        assert_eq!(overflow.kind, StringKind::Text);
+        assert_eq!(overflow.text, "attempt to multiply with overflow");
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use elf::abi;
+use elf::endian::AnyEndian;
+use elf::string_table::StringTable;
+use elf::symbol::SymbolTable as ElfSymbolTable;
+use elf::ElfBytes;
+
+use crate::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Object,
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub demangled: String,
+    pub address: u64,
+    pub size: u64,
+    pub kind: SymbolKind,
+}
+
+impl Symbol {
+    pub fn new(name: &str, address: u64, size: u64, kind: SymbolKind) -> Self {
+        Self {
+            name: name.to_string(),
+            demangled: demangle(name),
+            address,
+            size,
+            kind,
+        }
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        address.wrapping_sub(self.address) < self.size
+    }
+}
+
+pub fn demangle(name: &str) -> String {
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return format!("{:#}", demangled);
+    }
+
+    if name.starts_with("_Z") {
+        if let Ok(symbol) = cpp_demangle::Symbol::new(name) {
+            if let Ok(demangled) = symbol.demangle(&Default::default()) {
+                return demangled;
+            }
+        }
+    }
+
+    name.to_string()
+}
+
+#[derive(Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new(mut symbols: Vec<Symbol>) -> Self {
+        symbols.sort_by(|a, b| (a.address, &a.name).cmp(&(b.address, &b.name)));
+        symbols.dedup_by(|a, b| a.address == b.address && a.name == b.name);
+
+        Self { symbols }
+    }
+
+    pub(crate) fn from_elf(
+        file: &ElfBytes<AnyEndian>,
+        section_bases: &HashMap<u32, u64>,
+    ) -> Result<Self, Error> {
+        let mut symbols = Vec::new();
+
+        if let Some((symtab, strtab)) = file.symbol_table()? {
+            Self::collect(&symtab, &strtab, section_bases, &mut symbols)?;
+        }
+        if let Some((dynsym, dynstr)) = file.dynamic_symbol_table()? {
+            Self::collect(&dynsym, &dynstr, section_bases, &mut symbols)?;
+        }
+
+        Ok(Self::new(symbols))
+    }
+
+    fn collect(
+        symtab: &ElfSymbolTable<AnyEndian>,
+        strtab: &StringTable,
+        section_bases: &HashMap<u32, u64>,
+        symbols: &mut Vec<Symbol>,
+    ) -> Result<(), Error> {
+        for sym in symtab.iter() {
+            let kind = match sym.st_symtype() {
+                abi::STT_FUNC => SymbolKind::Function,
+                abi::STT_OBJECT => SymbolKind::Object,
+                abi::STT_NOTYPE => SymbolKind::Other,
+                _ => continue,
+            };
+            if sym.is_undefined() {
+                continue;
+            }
+
+            let name = strtab.get(sym.st_name as usize)?;
+            // Skip empty names and the `$x`/`$d` mapping symbols.
+            if name.is_empty() || name.starts_with('$') {
+                continue;
+            }
+
+            let base = section_bases.get(&(sym.st_shndx as u32)).unwrap_or(&0);
+            symbols.push(Symbol::new(
+                name,
+                sym.st_value.wrapping_add(*base),
+                sym.st_size,
+                kind,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols
+            .iter()
+            .filter(|sym| sym.kind == SymbolKind::Function)
+    }
+
+    pub fn at(&self, address: u64) -> Option<&Symbol> {
+        let idx = self.symbols.partition_point(|sym| sym.address < address);
+
+        self.symbols[idx..]
+            .iter()
+            .take_while(|sym| sym.address == address)
+            .min_by_key(|sym| sym.kind != SymbolKind::Function)
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|sym| sym.name == name || sym.demangled == name)
+    }
+
+    // Falls back to a sizeless symbol at the closest preceding address when no sized
+    // symbol covers the address; one that ended before it leaves the address unlabelled.
+    pub fn containing(&self, address: u64) -> Option<&Symbol> {
+        let idx = self.symbols.partition_point(|sym| sym.address <= address);
+        let preceding = &self.symbols[..idx];
+
+        preceding
+            .iter()
+            .rev()
+            .find(|sym| sym.contains(address))
+            .or_else(|| {
+                let nearest = preceding.last()?.address;
+                preceding
+                    .iter()
+                    .rev()
+                    .take_while(|sym| sym.address == nearest)
+                    .find(|sym| sym.size == 0)
+            })
+    }
+
+    pub fn label(&self, address: u64) -> Option<String> {
+        let sym = self.containing(address)?;
+
+        match address - sym.address {
+            0 => Some(sym.demangled.clone()),
+            offset => Some(format!("{}+{:#x}", sym.demangled, offset)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::Program;
+    use crate::test_utils::{strtab, sym64, ElfBuilder, ElfSection};
+
+    #[test]
+    fn demangle_test() {
+        assert_eq!(
+            demangle("_ZN4core9panicking5panic17h8d5e8a6c7c3a5f0aE"),
+            "core::panicking::panic"
+        );
+        assert_eq!(demangle("_ZN3foo3barEv"), "foo::bar()");
+        assert_eq!(demangle("main"), "main");
+    }
+
+    #[test]
+    fn label_test() {
+        let symbols = SymbolTable::new(vec![
+            Symbol::new("second", 0x1010, 0x10, SymbolKind::Function),
+            Symbol::new("first", 0x1000, 0x10, SymbolKind::Function),
+            Symbol::new("sizeless", 0x2000, 0, SymbolKind::Other),
+            Symbol::new("sized", 0x3000, 0x10, SymbolKind::Object),
+            Symbol::new("top", u64::MAX - 3, 0x10, SymbolKind::Object),
+        ]);
+
+        assert_eq!(symbols.at(0x1010).unwrap().name, "second");
+        assert_eq!(symbols.label(0x1000).as_deref(), Some("first"));
+        assert_eq!(symbols.label(0x1014).as_deref(), Some("second+0x4"));
+        assert_eq!(symbols.label(0x2100).as_deref(), Some("sizeless+0x100"));
+        assert_eq!(symbols.label(0x0fff), None);
+        // Past the end of `sized`, which hides `sizeless` before it.
+        assert_eq!(symbols.label(0x3010), None);
+        assert_eq!(symbols.label(u64::MAX).as_deref(), Some("top+0x3"));
+        assert_eq!(symbols.functions().count(), 2);
+    }
+
+    #[test]
+    fn object_symbol_overflow_test() {
+        let (symstr, names) = strtab(&["huge"]);
+        let mut symtab = sym64(0, 0, 0, 0, 0);
+        symtab.extend(sym64(names[0], abi::STT_OBJECT, 2, u64::MAX, 0));
+
+        // 1: .text, 2: .data, 3: .symtab, 4: .strtab
+        let file_data = ElfBuilder::new(false)
+            .e_type(abi::ET_REL)
+            .section(ElfSection::text(0, &[0xd65f_03c0]))
+            .section(ElfSection::new(
+                ".data",
+                abi::SHT_PROGBITS,
+                (abi::SHF_ALLOC | abi::SHF_WRITE) as u64,
+                0,
+                vec![0; 0x10],
+            ))
+            .section(ElfSection::new(".symtab", abi::SHT_SYMTAB, 0, 0, symtab).link(4, 1, 24))
+            .section(ElfSection::new(".strtab", abi::SHT_STRTAB, 0, 0, symstr))
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+
+        let data = &program.data_regions()[0];
+        assert_eq!(
+            program.symbols().by_name("huge").unwrap().address,
+            data.address - 1
+        );
+    }
+}
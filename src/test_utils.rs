@@ -0,0 +1,760 @@
+use elf::abi;
+
+use crate::program::Program;
+use crate::raw::RawLayout;
+
+pub struct ElfSection {
+    pub name: &'static str,
+    pub sh_type: u32,
+    pub flags: u64,
+    pub addr: u64,
+    pub data: Vec<u8>,
+    pub link: u32,
+    pub info: u32,
+    pub entsize: u64,
+}
+
+impl ElfSection {
+    pub fn new(name: &'static str, sh_type: u32, flags: u64, addr: u64, data: Vec<u8>) -> Self {
+        Self {
+            name,
+            sh_type,
+            flags,
+            addr,
+            data,
+            link: 0,
+            info: 0,
+            entsize: 0,
+        }
+    }
+
+    pub fn text(addr: u64, words: &[u32]) -> Self {
+        let data = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+
+        Self::new(
+            ".text",
+            abi::SHT_PROGBITS,
+            (abi::SHF_ALLOC | abi::SHF_EXECINSTR) as u64,
+            addr,
+            data,
+        )
+    }
+
+    pub fn link(mut self, link: u32, info: u32, entsize: u64) -> Self {
+        self.link = link;
+        self.info = info;
+        self.entsize = entsize;
+
+        self
+    }
+}
+
+pub fn strtab(names: &[&str]) -> (Vec<u8>, Vec<u32>) {
+    let mut data = vec![0u8];
+    let mut offsets = Vec::new();
+    for name in names {
+        offsets.push(data.len() as u32);
+        data.extend(name.as_bytes());
+        data.push(0);
+    }
+
+    (data, offsets)
+}
+
+pub fn sym64(name: u32, info: u8, shndx: u16, value: u64, size: u64) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(name.to_le_bytes());
+    data.push(info);
+    data.push(0);
+    data.extend(shndx.to_le_bytes());
+    data.extend(value.to_le_bytes());
+    data.extend(size.to_le_bytes());
+
+    data
+}
+
+pub fn rela64(offset: u64, sym: u32, r_type: u32, addend: i64) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(offset.to_le_bytes());
+    data.extend((((sym as u64) << 32) | r_type as u64).to_le_bytes());
+    data.extend(addend.to_le_bytes());
+
+    data
+}
+
+pub struct ElfSegment {
+    pub flags: u32,
+    pub vaddr: u64,
+    pub data: Vec<u8>,
+}
+
+// Minimal ELF64 writer used to build synthetic test inputs.
+pub struct ElfBuilder {
+    big_endian: bool,
+    e_type: u16,
+    machine: u16,
+    entry: u64,
+    sections: Vec<ElfSection>,
+    segments: Vec<ElfSegment>,
+}
+
+impl ElfBuilder {
+    pub fn new(big_endian: bool) -> Self {
+        Self {
+            big_endian,
+            e_type: abi::ET_EXEC,
+            machine: abi::EM_AARCH64,
+            entry: 0,
+            sections: Vec::new(),
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn e_type(mut self, e_type: u16) -> Self {
+        self.e_type = e_type;
+        self
+    }
+
+    pub fn machine(mut self, machine: u16) -> Self {
+        self.machine = machine;
+        self
+    }
+
+    pub fn entry(mut self, entry: u64) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    // Sections are numbered from 1 in insertion order; 0 is the null section.
+    pub fn section(mut self, section: ElfSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    pub fn segment(mut self, flags: u32, vaddr: u64, data: Vec<u8>) -> Self {
+        self.segments.push(ElfSegment { flags, vaddr, data });
+        self
+    }
+
+    fn u16(&self, out: &mut Vec<u8>, val: u16) {
+        match self.big_endian {
+            true => out.extend(val.to_be_bytes()),
+            false => out.extend(val.to_le_bytes()),
+        }
+    }
+
+    fn u32(&self, out: &mut Vec<u8>, val: u32) {
+        match self.big_endian {
+            true => out.extend(val.to_be_bytes()),
+            false => out.extend(val.to_le_bytes()),
+        }
+    }
+
+    fn u64(&self, out: &mut Vec<u8>, val: u64) {
+        match self.big_endian {
+            true => out.extend(val.to_be_bytes()),
+            false => out.extend(val.to_le_bytes()),
+        }
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        let mut shstrtab = vec![0u8];
+        let mut name_offsets = Vec::new();
+        for section in &self.sections {
+            name_offsets.push(shstrtab.len() as u32);
+            shstrtab.extend(section.name.as_bytes());
+            shstrtab.push(0);
+        }
+        let shstrtab_name = shstrtab.len() as u32;
+        shstrtab.extend(b".shstrtab\0");
+
+        let phoff = 64u64;
+        let mut offset = phoff + 56 * self.segments.len() as u64;
+
+        let mut body = Vec::new();
+        let mut segment_offsets = Vec::new();
+        for segment in &self.segments {
+            segment_offsets.push(offset + body.len() as u64);
+            body.extend(&segment.data);
+        }
+        let mut section_offsets = Vec::new();
+        for section in &self.sections {
+            section_offsets.push(offset + body.len() as u64);
+            body.extend(&section.data);
+        }
+        let shstrtab_offset = offset + body.len() as u64;
+        body.extend(&shstrtab);
+        while body.len() % 8 != 0 {
+            body.push(0);
+        }
+        offset += body.len() as u64;
+
+        let shnum = self.sections.len() as u16 + 2;
+
+        let mut out = Vec::new();
+        out.extend(b"\x7fELF");
+        out.push(abi::ELFCLASS64);
+        out.push(if self.big_endian {
+            abi::ELFDATA2MSB
+        } else {
+            abi::ELFDATA2LSB
+        });
+        out.push(abi::EV_CURRENT);
+        out.extend([0u8; 9]);
+        self.u16(&mut out, self.e_type);
+        self.u16(&mut out, self.machine);
+        self.u32(&mut out, abi::EV_CURRENT as u32);
+        self.u64(&mut out, self.entry);
+        self.u64(&mut out, if self.segments.is_empty() { 0 } else { phoff });
+        self.u64(&mut out, offset);
+        self.u32(&mut out, 0);
+        self.u16(&mut out, 64);
+        self.u16(&mut out, 56);
+        self.u16(&mut out, self.segments.len() as u16);
+        self.u16(&mut out, 64);
+        self.u16(&mut out, shnum);
+        self.u16(&mut out, shnum - 1);
+
+        for (segment, segment_offset) in self.segments.iter().zip(&segment_offsets) {
+            self.u32(&mut out, abi::PT_LOAD);
+            self.u32(&mut out, segment.flags);
+            self.u64(&mut out, *segment_offset);
+            self.u64(&mut out, segment.vaddr);
+            self.u64(&mut out, segment.vaddr);
+            self.u64(&mut out, segment.data.len() as u64);
+            self.u64(&mut out, segment.data.len() as u64);
+            self.u64(&mut out, 0x1000);
+        }
+
+        out.extend(body);
+
+        out.extend([0u8; 64]);
+        for ((section, name), section_offset) in self
+            .sections
+            .iter()
+            .zip(&name_offsets)
+            .zip(&section_offsets)
+        {
+            self.u32(&mut out, *name);
+            self.u32(&mut out, section.sh_type);
+            self.u64(&mut out, section.flags);
+            self.u64(&mut out, section.addr);
+            self.u64(&mut out, *section_offset);
+            self.u64(&mut out, section.data.len() as u64);
+            self.u32(&mut out, section.link);
+            self.u32(&mut out, section.info);
+            self.u64(&mut out, 4);
+            self.u64(&mut out, section.entsize);
+        }
+        self.u32(&mut out, shstrtab_name);
+        self.u32(&mut out, abi::SHT_STRTAB);
+        self.u64(&mut out, 0);
+        self.u64(&mut out, 0);
+        self.u64(&mut out, shstrtab_offset);
+        self.u64(&mut out, shstrtab.len() as u64);
+        self.u32(&mut out, 0);
+        self.u32(&mut out, 0);
+        self.u64(&mut out, 1);
+        self.u64(&mut out, 0);
+
+        out
+    }
+}
+
+const MACHO_BASE: u64 = 0x1_0000_0000;
+const MACHO_TEXT_OFFSET: u64 = 0x100;
+
+// Minimal little-endian MH_EXECUTE writer: one `__TEXT,__text` section plus LC_SYMTAB,
+// LC_FUNCTION_STARTS and LC_MAIN. Addresses passed in are offsets into `__text`.
+pub struct MachOBuilder {
+    cputype: u32,
+    cpusubtype: u32,
+    text: Vec<u8>,
+    symbols: Vec<(&'static str, u64)>,
+    function_starts: Vec<u64>,
+    entry: u64,
+}
+
+impl MachOBuilder {
+    pub fn new(cputype: u32, cpusubtype: u32) -> Self {
+        Self {
+            cputype,
+            cpusubtype,
+            text: Vec::new(),
+            symbols: Vec::new(),
+            function_starts: Vec::new(),
+            entry: 0,
+        }
+    }
+
+    pub fn text(mut self, words: &[u32]) -> Self {
+        self.text = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        self
+    }
+
+    pub fn symbol(mut self, name: &'static str, offset: u64) -> Self {
+        self.symbols.push((name, offset));
+        self
+    }
+
+    pub fn function_starts(mut self, offsets: &[u64]) -> Self {
+        self.function_starts = offsets.to_vec();
+        self
+    }
+
+    pub fn entry(mut self, offset: u64) -> Self {
+        self.entry = offset;
+        self
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        let text_end = MACHO_TEXT_OFFSET + self.text.len() as u64;
+
+        let mut starts = Vec::new();
+        let mut previous = MACHO_BASE;
+        for offset in &self.function_starts {
+            let address = MACHO_BASE + MACHO_TEXT_OFFSET + offset;
+            let mut delta = address.wrapping_sub(previous);
+            previous = address;
+            loop {
+                let byte = (delta & 0x7f) as u8;
+                delta >>= 7;
+                if delta == 0 {
+                    starts.push(byte);
+                    break;
+                }
+                starts.push(byte | 0x80);
+            }
+        }
+        starts.push(0);
+        while starts.len() % 8 != 0 {
+            starts.push(0);
+        }
+
+        let (strtab, names) = strtab(
+            &self
+                .symbols
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+        );
+        let mut symtab = Vec::new();
+        for ((_, offset), name) in self.symbols.iter().zip(names) {
+            symtab.extend(name.to_le_bytes());
+            // N_SECT | N_EXT in section 1
+            symtab.extend([0x0f, 1]);
+            symtab.extend(0u16.to_le_bytes());
+            symtab.extend((MACHO_BASE + MACHO_TEXT_OFFSET + offset).to_le_bytes());
+        }
+
+        let starts_offset = text_end.next_multiple_of(8);
+        let symtab_offset = starts_offset + starts.len() as u64;
+        let strtab_offset = symtab_offset + symtab.len() as u64;
+
+        let mut out = Vec::new();
+        let u32 = |out: &mut Vec<u8>, val: u32| out.extend(val.to_le_bytes());
+        let u64 = |out: &mut Vec<u8>, val: u64| out.extend(val.to_le_bytes());
+        let name = |out: &mut Vec<u8>, name: &str| {
+            let mut field = [0u8; 16];
+            field[..name.len()].copy_from_slice(name.as_bytes());
+            out.extend(field);
+        };
+
+        u32(&mut out, 0xfeed_facf);
+        u32(&mut out, self.cputype);
+        u32(&mut out, self.cpusubtype);
+        // MH_EXECUTE, 4 load commands
+        u32(&mut out, 2);
+        u32(&mut out, 4);
+        u32(&mut out, 152 + 24 + 16 + 24);
+        u32(&mut out, 0);
+        u32(&mut out, 0);
+
+        // LC_SEGMENT_64 __TEXT covering the header and __text
+        u32(&mut out, 0x19);
+        u32(&mut out, 152);
+        name(&mut out, "__TEXT");
+        u64(&mut out, MACHO_BASE);
+        u64(&mut out, text_end.next_multiple_of(0x1000));
+        u64(&mut out, 0);
+        u64(&mut out, text_end);
+        u32(&mut out, 5);
+        u32(&mut out, 5);
+        u32(&mut out, 1);
+        u32(&mut out, 0);
+        name(&mut out, "__text");
+        name(&mut out, "__TEXT");
+        u64(&mut out, MACHO_BASE + MACHO_TEXT_OFFSET);
+        u64(&mut out, self.text.len() as u64);
+        u32(&mut out, MACHO_TEXT_OFFSET as u32);
+        u32(&mut out, 2);
+        u32(&mut out, 0);
+        u32(&mut out, 0);
+        u32(&mut out, 0x8000_0400);
+        out.extend([0u8; 12]);
+
+        // LC_SYMTAB
+        u32(&mut out, 0x2);
+        u32(&mut out, 24);
+        u32(&mut out, symtab_offset as u32);
+        u32(&mut out, self.symbols.len() as u32);
+        u32(&mut out, strtab_offset as u32);
+        u32(&mut out, strtab.len() as u32);
+
+        // LC_FUNCTION_STARTS
+        u32(&mut out, 0x26);
+        u32(&mut out, 16);
+        u32(&mut out, starts_offset as u32);
+        u32(&mut out, starts.len() as u32);
+
+        // LC_MAIN
+        u32(&mut out, 0x8000_0028);
+        u32(&mut out, 24);
+        u64(&mut out, MACHO_TEXT_OFFSET + self.entry);
+        u64(&mut out, 0);
+
+        out.resize(MACHO_TEXT_OFFSET as usize, 0);
+        out.extend(&self.text);
+        out.resize(starts_offset as usize, 0);
+        out.extend(starts);
+        out.extend(symtab);
+        out.extend(strtab);
+
+        out
+    }
+}
+
+// Universal binary wrapping the given (cputype, cpusubtype, slice) entries.
+pub fn fat(slices: &[(u32, u32, &[u8])]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend(0xcafe_babeu32.to_be_bytes());
+    header.extend((slices.len() as u32).to_be_bytes());
+
+    let mut body = Vec::new();
+    for (cputype, cpusubtype, data) in slices {
+        body.resize((body.len() + 1).next_multiple_of(0x4000), 0);
+        let offset = body.len() as u32;
+        for field in [*cputype, *cpusubtype, offset, data.len() as u32, 14] {
+            header.extend(field.to_be_bytes());
+        }
+        body.extend(*data);
+    }
+
+    body[..header.len()].copy_from_slice(&header);
+    body
+}
+
+pub const PE_IMAGE_BASE: u64 = 0x1_4000_0000;
+const PE_TEXT_RVA: u32 = 0x1000;
+const PE_RDATA_RVA: u32 = 0x2000;
+const PE_PDATA_RVA: u32 = 0x3000;
+const PE_IAT_OFFSET: u32 = 0x800;
+
+// Minimal PE32+ writer with .text, .rdata (exports, imports, unwind and CHPE data) and
+// .pdata sections. Offsets passed in are relative to the start of .text.
+pub struct PeBuilder {
+    machine: u16,
+    entry: u32,
+    text: Vec<u8>,
+    exports: Vec<(&'static str, u32)>,
+    imports: Vec<(&'static str, Vec<&'static str>)>,
+    pdata: Vec<(u32, u32, bool)>,
+    code_map: Vec<(u32, u32, u32)>,
+}
+
+impl PeBuilder {
+    pub fn new(machine: u16) -> Self {
+        Self {
+            machine,
+            entry: 0,
+            text: Vec::new(),
+            exports: Vec::new(),
+            imports: Vec::new(),
+            pdata: Vec::new(),
+            code_map: Vec::new(),
+        }
+    }
+
+    pub fn text(mut self, words: &[u32]) -> Self {
+        self.text = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        self
+    }
+
+    pub fn entry(mut self, offset: u32) -> Self {
+        self.entry = PE_TEXT_RVA + offset;
+        self
+    }
+
+    pub fn export(mut self, name: &'static str, offset: u32) -> Self {
+        self.exports.push((name, offset));
+        self
+    }
+
+    pub fn import(mut self, dll: &'static str, names: &[&'static str]) -> Self {
+        self.imports.push((dll, names.to_vec()));
+        self
+    }
+
+    // Packed entries encode the length inline; the others point at an .xdata header.
+    pub fn pdata(mut self, offset: u32, len: u32, packed: bool) -> Self {
+        self.pdata.push((offset, len, packed));
+        self
+    }
+
+    pub fn code_range(mut self, offset: u32, len: u32, native_type: u32) -> Self {
+        self.code_map.push((offset, len, native_type));
+        self
+    }
+
+    pub fn import_slot(&self, dll_idx: usize, name_idx: usize) -> u64 {
+        let slots_before: usize = self.imports[..dll_idx]
+            .iter()
+            .map(|(_, names)| names.len() + 1)
+            .sum();
+
+        PE_IMAGE_BASE + (PE_RDATA_RVA + PE_IAT_OFFSET + (slots_before + name_idx) as u32 * 8) as u64
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        let put_u16 = |out: &mut Vec<u8>, at: usize, val: u16| {
+            out[at..at + 2].copy_from_slice(&val.to_le_bytes())
+        };
+        let put_u32 = |out: &mut Vec<u8>, at: usize, val: u32| {
+            out[at..at + 4].copy_from_slice(&val.to_le_bytes())
+        };
+        let put_u64 = |out: &mut Vec<u8>, at: usize, val: u64| {
+            out[at..at + 8].copy_from_slice(&val.to_le_bytes())
+        };
+        let put_str = |out: &mut Vec<u8>, at: usize, val: &str| {
+            out[at..at + val.len()].copy_from_slice(val.as_bytes())
+        };
+
+        // .rdata layout: exports at 0x0, import descriptors at 0x400, IAT at 0x800,
+        // lookup tables at 0x900, names at 0xa00, xdata at 0xc00, load config at 0xd00
+        // and CHPE metadata at 0xe00.
+        let mut rdata = vec![0u8; 0x1000];
+        let mut directories = [(0u32, 0u32); 16];
+
+        if !self.exports.is_empty() {
+            let mut exports = self.exports.clone();
+            exports.sort();
+            let count = exports.len() as u32;
+
+            put_u32(&mut rdata, 16, 1);
+            put_u32(&mut rdata, 20, count);
+            put_u32(&mut rdata, 24, count);
+            put_u32(&mut rdata, 28, PE_RDATA_RVA + 0x40);
+            put_u32(&mut rdata, 32, PE_RDATA_RVA + 0x80);
+            put_u32(&mut rdata, 36, PE_RDATA_RVA + 0xc0);
+
+            let mut name_at = 0x100;
+            for (idx, (name, offset)) in exports.iter().enumerate() {
+                put_u32(&mut rdata, 0x40 + idx * 4, PE_TEXT_RVA + offset);
+                put_u32(&mut rdata, 0x80 + idx * 4, PE_RDATA_RVA + name_at as u32);
+                put_u16(&mut rdata, 0xc0 + idx * 2, idx as u16);
+                put_str(&mut rdata, name_at, name);
+                name_at += name.len() + 1;
+            }
+            put_u32(&mut rdata, 12, PE_RDATA_RVA + name_at as u32);
+            put_str(&mut rdata, name_at, "test.dll");
+
+            directories[0] = (PE_RDATA_RVA, 0x400);
+        }
+
+        if !self.imports.is_empty() {
+            let mut slot = 0;
+            let mut name_at = 0xa00;
+            for (idx, (dll, names)) in self.imports.iter().enumerate() {
+                let descriptor = 0x400 + idx * 20;
+                put_u32(&mut rdata, descriptor, PE_RDATA_RVA + 0x900 + slot * 8);
+                put_u32(
+                    &mut rdata,
+                    descriptor + 16,
+                    PE_RDATA_RVA + PE_IAT_OFFSET + slot * 8,
+                );
+
+                for name in names {
+                    let hint_name = PE_RDATA_RVA as u64 + name_at as u64;
+                    put_u64(&mut rdata, 0x900 + slot as usize * 8, hint_name);
+                    put_u64(
+                        &mut rdata,
+                        PE_IAT_OFFSET as usize + slot as usize * 8,
+                        hint_name,
+                    );
+                    put_str(&mut rdata, name_at + 2, name);
+                    name_at = (name_at + 2 + name.len() + 1).next_multiple_of(2);
+                    slot += 1;
+                }
+                slot += 1;
+
+                put_u32(&mut rdata, descriptor + 12, PE_RDATA_RVA + name_at as u32);
+                put_str(&mut rdata, name_at, dll);
+                name_at = (name_at + dll.len() + 1).next_multiple_of(2);
+            }
+
+            directories[1] = (PE_RDATA_RVA + 0x400, (self.imports.len() as u32 + 1) * 20);
+            directories[12] = (PE_RDATA_RVA + PE_IAT_OFFSET, slot * 8);
+        }
+
+        let mut pdata = vec![0u8; 0x1000];
+        for (idx, (offset, len, packed)) in self.pdata.iter().enumerate() {
+            put_u32(&mut pdata, idx * 8, PE_TEXT_RVA + offset);
+            let unwind = match packed {
+                true => 1 | (len / 4) << 2,
+                false => {
+                    put_u32(&mut rdata, 0xc00 + idx * 8, len / 4);
+                    PE_RDATA_RVA + 0xc00 + idx as u32 * 8
+                }
+            };
+            put_u32(&mut pdata, idx * 8 + 4, unwind);
+        }
+        if !self.pdata.is_empty() {
+            directories[3] = (PE_PDATA_RVA, self.pdata.len() as u32 * 8);
+        }
+
+        if !self.code_map.is_empty() {
+            put_u32(&mut rdata, 0xd00, 0xd0);
+            put_u64(
+                &mut rdata,
+                0xd00 + 0xc8,
+                PE_IMAGE_BASE + (PE_RDATA_RVA + 0xe00) as u64,
+            );
+
+            put_u32(&mut rdata, 0xe00, 1);
+            put_u32(&mut rdata, 0xe04, PE_RDATA_RVA + 0xe40);
+            put_u32(&mut rdata, 0xe08, self.code_map.len() as u32);
+            for (idx, (offset, len, native_type)) in self.code_map.iter().enumerate() {
+                put_u32(
+                    &mut rdata,
+                    0xe40 + idx * 8,
+                    (PE_TEXT_RVA + offset) | native_type,
+                );
+                put_u32(&mut rdata, 0xe44 + idx * 8, *len);
+            }
+
+            directories[10] = (PE_RDATA_RVA + 0xd00, 0xd0);
+        }
+
+        let mut text = self.text.clone();
+        text.resize(0x1000, 0);
+
+        let mut out = vec![0u8; 0x1000];
+        put_str(&mut out, 0, "MZ");
+        put_u32(&mut out, 0x3c, 0x40);
+        put_str(&mut out, 0x40, "PE\0\0");
+
+        // COFF header
+        put_u16(&mut out, 0x44, self.machine);
+        put_u16(&mut out, 0x46, 3);
+        put_u16(&mut out, 0x54, 240);
+        put_u16(&mut out, 0x56, 0x22);
+
+        // PE32+ optional header
+        let opt = 0x58;
+        put_u16(&mut out, opt, 0x20b);
+        put_u32(&mut out, opt + 4, 0x1000);
+        put_u32(&mut out, opt + 16, self.entry);
+        put_u32(&mut out, opt + 20, PE_TEXT_RVA);
+        put_u64(&mut out, opt + 24, PE_IMAGE_BASE);
+        put_u32(&mut out, opt + 32, 0x1000);
+        put_u32(&mut out, opt + 36, 0x1000);
+        put_u16(&mut out, opt + 40, 6);
+        put_u16(&mut out, opt + 48, 6);
+        put_u32(&mut out, opt + 56, 0x4000);
+        put_u32(&mut out, opt + 60, 0x1000);
+        put_u16(&mut out, opt + 68, 3);
+        put_u32(&mut out, opt + 108, 16);
+        for (idx, (rva, size)) in directories.iter().enumerate() {
+            put_u32(&mut out, opt + 112 + idx * 8, *rva);
+            put_u32(&mut out, opt + 116 + idx * 8, *size);
+        }
+
+        let sections = [
+            (".text", PE_TEXT_RVA, 0x6000_0020u32),
+            (".rdata", PE_RDATA_RVA, 0x4000_0040),
+            (".pdata", PE_PDATA_RVA, 0x4000_0040),
+        ];
+        for (idx, (name, rva, characteristics)) in sections.iter().enumerate() {
+            let header = opt + 240 + idx * 40;
+            put_str(&mut out, header, name);
+            put_u32(&mut out, header + 8, 0x1000);
+            put_u32(&mut out, header + 12, *rva);
+            put_u32(&mut out, header + 16, 0x1000);
+            put_u32(&mut out, header + 20, *rva);
+            put_u32(&mut out, header + 36, *characteristics);
+        }
+
+        out.extend(text);
+        out.extend(rdata);
+        out.extend(pdata);
+
+        out
+    }
+}
+
+// An .eh_frame at `address` with one CIE, using the pc-relative sdata4 encoding GCC and
+// LLVM emit, and an FDE per function.
+pub fn eh_frame(address: u64, functions: &[(u64, u32)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(16u32.to_le_bytes());
+    data.extend(0u32.to_le_bytes());
+    // version, "zR", code align 4, data align -8, return register 30, R: 0x1b
+    data.extend([1, b'z', b'R', 0, 4, 0x78, 30, 1, 0x1b, 0, 0, 0]);
+
+    for (begin, len) in functions {
+        let start = data.len();
+        data.extend(16u32.to_le_bytes());
+        data.extend((start as u32 + 4).to_le_bytes());
+        let pc = address + data.len() as u64;
+        data.extend((begin.wrapping_sub(pc) as u32).to_le_bytes());
+        data.extend(len.to_le_bytes());
+        data.extend([0, 0, 0, 0]);
+    }
+    data.extend(0u32.to_le_bytes());
+
+    data
+}
+
+// A bounded switch over a table of byte offsets, loaded at 0x1000:
+// 1000: cmp w0, #2
+// 1004: b.hi 0x102c
+// 1008: adr x9, 0x1030
+// 100c: adr x10, 0x1020
+// 1010: ldrb w11, [x9, x0]
+// 1014: add x10, x10, x11, lsl #2
+// 1018: br x10
+// 101c: udf #0
+// 1020: ret (case 0)
+// 1024: ret (case 2)
+// 1028: ret (case 1)
+// 102c: ret (default)
+// 1030: .byte 0, 2, 1, 3
+pub fn switch_code() -> Vec<u32> {
+    vec![
+        0x7100_081f,
+        0x5400_0148,
+        0x1000_0149,
+        0x1000_00aa,
+        0x3860_692b,
+        0x8b0b_094a,
+        0xd61f_0140,
+        0x0000_0000,
+        0xd65f_03c0,
+        0xd65f_03c0,
+        0xd65f_03c0,
+        0xd65f_03c0,
+        0x0301_0200,
+    ]
+}
+
+// Raw code loaded at 0x1000. The bytes are leaked so the program can outlive the call.
+pub fn raw_program(words: &[u32], entries: &[u64]) -> Program<'static> {
+    let data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+    let layout = RawLayout {
+        base_address: 0x1000,
+        entry_points: entries.to_vec(),
+        ..Default::default()
+    };
+
+    Program::from_raw(data.leak(), &layout)
+}
@@ -1,7 +1,6 @@
-use std::{
-    ops::{BitAnd, BitXor, Range, Shl, Shr},
-    slice::Iter,
-};
+use core::ops::{Range, Shl, Shr};
+
+use crate::error::Error;
 
 impl GetBits for u32 {}
 pub trait GetBits
@@ -27,13 +26,90 @@ impl TestBits for u32 {
     }
 }
 
+pub fn sign_extend(val: u32, bits: u8) -> i64 {
+    let shift = 64 - bits;
+
+    ((val as i64) << shift) >> shift
+}
+
+// Returns the decoded value and the number of bytes it occupied.
+#[cfg(feature = "std")]
+pub fn read_uleb128(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (idx, byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (idx * 7);
+        if byte & 0x80 == 0 {
+            return Some((value, idx + 1));
+        }
+    }
+
+    None
+}
+
+#[cfg(feature = "std")]
+pub fn read_sleb128(data: &[u8]) -> Option<(i64, usize)> {
+    let mut value = 0i64;
+    for (idx, byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as i64) << (idx * 7);
+        if byte & 0x80 == 0 {
+            let shift = 63 - (idx * 7 + 6).min(63);
+            return Some(((value << shift) >> shift, idx + 1));
+        }
+    }
+
+    None
+}
+
+// Byte order of the words in an instruction stream. AArch64 fetches instructions
+// little-endian even on aarch64_be, but dumps taken through a debugger are not always.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+    // Little-endian halfwords stored high halfword first.
+    Mixed,
+}
+
+impl Endianness {
+    pub fn word(&self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+            Endianness::Mixed => u32::from_le_bytes(bytes).rotate_left(16),
+        }
+    }
+}
+
 pub struct InstReader<T> {
     iter: T,
+    address: u64,
+    endianness: Endianness,
+    done: bool,
 }
 
 impl<T> InstReader<T> {
     pub fn new(iter: T) -> Self {
-        Self { iter }
+        Self {
+            iter,
+            address: 0,
+            endianness: Endianness::Little,
+            done: false,
+        }
+    }
+
+    pub fn with_address(mut self, address: u64) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    pub fn address(&self) -> u64 {
+        self.address
     }
 }
 
@@ -41,16 +117,34 @@ impl<T> Iterator for InstReader<T>
 where
     T: Iterator<Item = u8>,
 {
-    type Item = u32;
+    type Item = (u64, Result<u32, Error>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut inst: u32 = 0;
-        for i in 0..4 {
-            let byte = self.iter.next()?;
-            inst |= (byte as u32) << (i * 8);
+        if self.done {
+            return None;
         }
 
-        Some(inst)
+        let address = self.address;
+        let mut bytes = [0u8; 4];
+        for (idx, byte) in bytes.iter_mut().enumerate() {
+            match self.iter.next() {
+                Some(next) => *byte = next,
+                None => {
+                    self.done = true;
+                    return match idx {
+                        0 => None,
+                        len => Some((address, Err(Error::TruncatedInstructionError(address, len)))),
+                    };
+                }
+            }
+        }
+        // Nothing can follow a word at the very end of the address space.
+        match self.address.checked_add(4) {
+            Some(next) => self.address = next,
+            None => self.done = true,
+        }
+
+        Some((address, Ok(self.endianness.word(bytes))))
     }
 }
 
@@ -75,4 +169,59 @@ mod tests {
         assert!(val.test_bits(0b11_00_00, 0b11_11_00));
         assert!(val.test_bits(0b00_01_10, 0b00_10_11));
     }
+
+    #[test]
+    fn sign_extend_test() {
+        assert_eq!(sign_extend(0b0111, 4), 7);
+        assert_eq!(sign_extend(0b1000, 4), -8);
+        assert_eq!(sign_extend(0x3ff_ffff, 26), -1);
+    }
+
+    #[test]
+    fn inst_reader_test() {
+        let bytes = [
+            0xc0, 0x03, 0x5f, 0xd6, 0xd6, 0x5f, 0x03, 0xc0, 0x5f, 0xd6, 0xc0, 0x03,
+        ];
+        let words = [0xd65f_03c0; 3];
+
+        for (idx, endianness) in [Endianness::Little, Endianness::Big, Endianness::Mixed]
+            .into_iter()
+            .enumerate()
+        {
+            let mut reader = InstReader::new(bytes[idx * 4..idx * 4 + 4].iter().cloned())
+                .with_address(0x1000)
+                .with_endianness(endianness);
+            assert!(matches!(reader.next(), Some((0x1000, Ok(word))) if word == words[idx]));
+            assert!(reader.next().is_none());
+            assert_eq!(reader.address(), 0x1004);
+        }
+
+        let mut reader = InstReader::new(bytes[..6].iter().cloned());
+        assert!(matches!(reader.next(), Some((0, Ok(0xd65f_03c0)))));
+        assert!(matches!(
+            reader.next(),
+            Some((4, Err(Error::TruncatedInstructionError(4, 2))))
+        ));
+        assert!(reader.next().is_none());
+
+        let mut reader = InstReader::new(bytes.iter().cloned()).with_address(u64::MAX - 3);
+        assert!(matches!(
+            reader.next(),
+            Some((0xffff_ffff_ffff_fffc, Ok(0xd65f_03c0)))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_uleb128_test() {
+        assert_eq!(read_uleb128(&[0x02]), Some((2, 1)));
+        assert_eq!(read_uleb128(&[0xe5, 0x8e, 0x26, 0xff]), Some((624485, 3)));
+        assert_eq!(read_uleb128(&[0x80, 0x80]), None);
+
+        assert_eq!(read_sleb128(&[0x02]), Some((2, 1)));
+        assert_eq!(read_sleb128(&[0x7c]), Some((-4, 1)));
+        assert_eq!(read_sleb128(&[0xc0, 0xbb, 0x78]), Some((-123456, 3)));
+        assert_eq!(read_sleb128(&[0x80]), None);
+    }
 }
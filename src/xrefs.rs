@@ -0,0 +1,460 @@
+use std::collections::BTreeMap;
+
+use elf::abi;
+
+use crate::aarc64_parser::AArch64Parser;
+use crate::program::Program;
+use crate::registers::Register;
+use crate::symbols::SymbolKind;
+use crate::AArch64Inst;
+
+// Registers a call may clobber besides the link register.
+const CALLER_SAVED: core::ops::RangeInclusive<u8> = 0..=18;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum XrefKind {
+    Call,
+    // A `B` to the start of a function.
+    Jump,
+    // An address materialised in a register by ADR, ADRP+ADD, a MOVZ/MOVK chain or a
+    // literal load.
+    Address,
+    Read,
+    Write,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetKind {
+    Function,
+    Code,
+    // Text, or a `&str` pointing at it.
+    String,
+    Data,
+    // Outside the loaded code and data, like `.bss`.
+    Unmapped,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Xref {
+    // The instruction making the reference; for address pairs that is the second one.
+    pub from: u64,
+    pub to: u64,
+    pub kind: XrefKind,
+    pub target: TargetKind,
+    // Start of the function symbol covering `from`.
+    pub function: Option<u64>,
+}
+
+pub struct Xrefs {
+    // In `from` order.
+    xrefs: Vec<Xref>,
+    by_target: BTreeMap<u64, Vec<usize>>,
+}
+
+impl Xrefs {
+    pub fn iter(&self) -> impl Iterator<Item = &Xref> {
+        self.xrefs.iter()
+    }
+
+    // References made by the instruction at `address`.
+    pub fn refs_from(&self, address: u64) -> impl Iterator<Item = &Xref> {
+        let start = self.xrefs.partition_point(|xref| xref.from < address);
+        self.xrefs[start..]
+            .iter()
+            .take_while(move |xref| xref.from == address)
+    }
+
+    pub fn refs_to(&self, address: u64) -> impl Iterator<Item = &Xref> {
+        self.by_target
+            .get(&address)
+            .into_iter()
+            .flatten()
+            .map(|idx| &self.xrefs[*idx])
+    }
+
+    // Every referenced address, in order.
+    pub fn targets(&self) -> impl Iterator<Item = u64> + '_ {
+        self.by_target.keys().copied()
+    }
+}
+
+impl Program<'_> {
+    // Sweeps the code regions tracking the registers ADR, ADRP, ADD, MOVZ/MOVN/MOVK and
+    // literal loads leave known values in. Values are forgotten at function starts and after
+    // unconditional branches, so a pair split across a join point is only resolved when
+    // the fall-through path sets it up. Words the decoder does not know are assumed to
+    // leave the registers alone. Instructions with a relocation only hold placeholder
+    // operands, so their targets come from the relocation instead; references to symbols
+    // the file does not define are left out.
+    pub fn xrefs(&self, parser: &AArch64Parser) -> Xrefs {
+        let mut xrefs = Vec::new();
+
+        for region in self.regions() {
+            let insts: Vec<_> = region
+                .instructions(parser)
+                .filter_map(|(address, _, result)| Some((address, result.ok()?)))
+                .collect();
+            let mut values: [Option<u64>; 31] = [None; 31];
+
+            for (idx, (address, inst)) in insts.iter().enumerate() {
+                let address = *address;
+                if self.is_function_start(address) {
+                    values = [None; 31];
+                }
+
+                let mut push = |to, kind| {
+                    xrefs.push(Xref {
+                        from: address,
+                        to,
+                        kind,
+                        target: self.target_kind(to),
+                        function: self
+                            .symbols()
+                            .containing(address)
+                            .filter(|symbol| symbol.kind == SymbolKind::Function)
+                            .map(|symbol| symbol.address),
+                    })
+                };
+
+                let value = |reg: u32| values.get(reg as usize).copied().flatten();
+                let relocation = self.relocation_at(address);
+                let resolve = |computed: Option<u64>| match relocation {
+                    Some(rel) => rel.address(),
+                    None => computed,
+                };
+                let mut result = None;
+                match inst {
+                    AArch64Inst::ADR(data) => {
+                        if let Some(target) = resolve(inst.pc_rel_address(address)) {
+                            push(target, XrefKind::Address);
+                            result = Some((data.rd, target));
+                        }
+                    }
+                    AArch64Inst::ADRP(data) => {
+                        result = resolve(inst.pc_rel_address(address))
+                            .map(|target| (data.rd, target & !0xfff));
+                    }
+                    AArch64Inst::AddImmediate64(data) => {
+                        if let Some(target) =
+                            resolve(value(data.rn).map(|base| base.wrapping_add(data.imm())))
+                        {
+                            push(target, XrefKind::Address);
+                            result = Some((data.rd, target));
+                        }
+                    }
+                    AArch64Inst::LDRImmediate64(_)
+                        if relocation
+                            .is_some_and(|rel| rel.kind == abi::R_AARCH64_LD64_GOT_LO12_NC) =>
+                    {
+                        // The GOT entry holds the address of the symbol.
+                        if let Some(target) = resolve(None) {
+                            push(target, XrefKind::Address);
+                        }
+                    }
+                    AArch64Inst::LDRImmediate64(data) => {
+                        let computed = value(data.rn).map(|base| base.wrapping_add(data.offset()));
+                        if let Some(target) = resolve(computed) {
+                            push(target, XrefKind::Read);
+                        }
+                    }
+                    AArch64Inst::STRImmediate64(data) => {
+                        let computed = value(data.rn).map(|base| base.wrapping_add(data.offset()));
+                        if let Some(target) = resolve(computed) {
+                            push(target, XrefKind::Write);
+                        }
+                    }
+                    AArch64Inst::LDRLiteral32(_)
+                    | AArch64Inst::LDRLiteral64(_)
+                    | AArch64Inst::LDRSWLiteral(_)
+                        if relocation.is_some() =>
+                    {
+                        if let Some(target) = resolve(None) {
+                            push(target, XrefKind::Read);
+                        }
+                    }
+                    AArch64Inst::LDRLiteral32(data)
+                    | AArch64Inst::LDRLiteral64(data)
+                    | AArch64Inst::LDRSWLiteral(data) => {
+                        push(inst.pc_rel_address(address).unwrap(), XrefKind::Read);
+                        if let Some(literal) = self.literal(address, inst) {
+                            // Only a doubleword entry is taken for a pointer.
+                            if literal.size == 8 && self.read(literal.value, 1).is_some() {
+                                push(literal.value, XrefKind::Address);
+                            }
+                            result = Some((data.rt, literal.value));
+                        }
+                    }
+                    AArch64Inst::MOVZ32(data)
+                    | AArch64Inst::MOVN32(data)
+                    | AArch64Inst::MOVK32(data)
+                    | AArch64Inst::MOVZ64(data)
+                    | AArch64Inst::MOVN64(data)
+                    | AArch64Inst::MOVK64(data) => {
+                        let moved = match inst {
+                            _ if relocation.is_some() => resolve(None),
+                            AArch64Inst::MOVN32(_) | AArch64Inst::MOVN64(_) => Some(!data.imm()),
+                            AArch64Inst::MOVK32(_) | AArch64Inst::MOVK64(_) => value(data.rd)
+                                .map(|value| value & !(0xffff << data.shift()) | data.imm()),
+                            _ => Some(data.imm()),
+                        };
+                        let moved = match data.sf {
+                            0 => moved.map(|value| value as u32 as u64),
+                            _ => moved,
+                        };
+
+                        // Only the last MOVK of a chain holds the whole address. A lone MOV
+                        // of 16 bits is far more likely a constant than an address.
+                        let continued = insts.get(idx + 1).is_some_and(|(_, next)| {
+                            matches!(next, AArch64Inst::MOVK32(next) | AArch64Inst::MOVK64(next)
+                                if next.rd == data.rd)
+                        });
+                        let chained =
+                            matches!(inst, AArch64Inst::MOVK32(_) | AArch64Inst::MOVK64(_));
+                        if let Some(target) = moved {
+                            if !continued
+                                && (chained || target > 0xffff)
+                                && self.read(target, 1).is_some()
+                            {
+                                push(target, XrefKind::Address);
+                            }
+                        }
+                        result = moved.map(|value| (data.rd, value));
+                    }
+                    AArch64Inst::BL(_) => {
+                        if let Some(target) = resolve(inst.branch_target(address)) {
+                            push(target, XrefKind::Call);
+                        }
+                    }
+                    AArch64Inst::B(_) => {
+                        let target = resolve(inst.branch_target(address));
+                        if let Some(target) =
+                            target.filter(|target| self.is_function_start(*target))
+                        {
+                            push(target, XrefKind::Jump);
+                        }
+                    }
+                    _ => {}
+                }
+
+                for reg in inst.writes().iter() {
+                    if let Register::X(num) = reg {
+                        values[num as usize] = None;
+                    }
+                }
+                if let AArch64Inst::BL(_) | AArch64Inst::BLR(_) = inst {
+                    for num in CALLER_SAVED {
+                        values[num as usize] = None;
+                    }
+                }
+                if let AArch64Inst::B(_) | AArch64Inst::BR(_) | AArch64Inst::RET(_) = inst {
+                    values = [None; 31];
+                }
+                if let Some((rd, value)) = result.filter(|(rd, _)| *rd != 31) {
+                    values[rd as usize] = Some(value);
+                }
+            }
+        }
+
+        xrefs.sort_by_key(|xref| xref.from);
+        let mut by_target = BTreeMap::<_, Vec<_>>::new();
+        for (idx, xref) in xrefs.iter().enumerate() {
+            by_target.entry(xref.to).or_default().push(idx);
+        }
+
+        Xrefs { xrefs, by_target }
+    }
+
+    pub fn target_kind(&self, address: u64) -> TargetKind {
+        if self.is_function_start(address) {
+            TargetKind::Function
+        } else if self.region_at(address).is_some() {
+            TargetKind::Code
+        } else if self.string_at(address).is_some() {
+            TargetKind::String
+        } else if self.read(address, 1).is_some() {
+            TargetKind::Data
+        } else {
+            TargetKind::Unmapped
+        }
+    }
+
+    fn is_function_start(&self, address: u64) -> bool {
+        self.plt_stub(address).is_some()
+            || self
+                .symbols()
+                .at(address)
+                .is_some_and(|symbol| symbol.kind == SymbolKind::Function)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{rela64, strtab, sym64, ElfBuilder, ElfSection};
+
+    #[test]
+    fn xrefs_test() {
+        // 101000: adrp x0, 0x102000
+        // 101004: add x0, x0, #0x10
+        // 101008: adrp x1, 0x102000
+        // 10100c: ldr x2, [x1, #8]
+        // 101010: str x2, [x1, #0x18]
+        // 101014: mov x3, #0x100000
+        // 101018: movk x3, #0x201c
+        // 10101c: bl 0x101028
+        // 101020: add x4, x0, #4 (x0 clobbered by the call)
+        // 101024: b 0x101028
+        // 101028: mov x5, #0x10
+        // 10102c: ret
+        let text = ElfSection::text(
+            0x10_1000,
+            &[
+                0xb000_0000,
+                0x9100_4000,
+                0xb000_0001,
+                0xf940_0422,
+                0xf900_0c22,
+                0xd2a0_0203,
+                0xf284_0383,
+                0x9400_0003,
+                0x9100_1004,
+                0x1400_0001,
+                0xd280_0205,
+                0xd65f_03c0,
+            ],
+        );
+        let rodata = ElfSection::new(
+            ".rodata",
+            abi::SHT_PROGBITS,
+            abi::SHF_ALLOC as u64,
+            0x10_2000,
+            vec![0; 0x20],
+        );
+        let file_data = ElfBuilder::new(false)
+            .entry(0x10_1000)
+            .section(text)
+            .section(rodata)
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+        let xrefs = program.xrefs(&AArch64Parser::new());
+
+        let found: Vec<_> = xrefs
+            .iter()
+            .map(|xref| (xref.from, xref.to, xref.kind, xref.target))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (0x10_1004, 0x10_2010, XrefKind::Address, TargetKind::Data),
+                (0x10_100c, 0x10_2008, XrefKind::Read, TargetKind::Data),
+                (0x10_1010, 0x10_2018, XrefKind::Write, TargetKind::Data),
+                (0x10_1018, 0x10_201c, XrefKind::Address, TargetKind::Data),
+                (0x10_101c, 0x10_1028, XrefKind::Call, TargetKind::Code),
+            ]
+        );
+        assert_eq!(xrefs.refs_to(0x10_2008).count(), 1);
+        assert_eq!(xrefs.refs_from(0x10_1010).next().unwrap().to, 0x10_2018);
+        assert_eq!(xrefs.refs_from(0x10_1020).count(), 0);
+        assert_eq!(
+            xrefs.targets().collect::<Vec<_>>(),
+            [0x10_1028, 0x10_2008, 0x10_2010, 0x10_2018, 0x10_201c]
+        );
+    }
+
+    #[test]
+    fn xrefs_elf_test() {
+        let file_data = std::fs::read("a64_example").unwrap();
+        let program = Program::from_elf(&file_data).unwrap();
+        let xrefs = program.xrefs(&AArch64Parser::new());
+
+        // adr x0, 0x2001f0 in core::sync::atomic::compiler_fence
+        let fence = program
+            .symbols()
+            .by_name("core::sync::atomic::compiler_fence")
+            .unwrap();
+        let xref = xrefs.refs_from(0x21_0450).next().unwrap();
+        assert_eq!(xref.to, 0x20_01f0);
+        assert_eq!(xref.kind, XrefKind::Address);
+        assert_eq!(xref.target, TargetKind::String);
+        assert_eq!(xref.function, Some(fence.address));
+        assert!(xrefs.refs_to(0x20_01f0).any(|xref| xref.from == 0x21_0450));
+
+        let panic_fmt = program
+            .symbols()
+            .by_name("core::panicking::panic_fmt")
+            .unwrap();
+        assert!(xrefs
+            .refs_to(panic_fmt.address)
+            .all(|xref| xref.kind == XrefKind::Call && xref.target == TargetKind::Function));
+        assert!(xrefs.refs_to(panic_fmt.address).count() > 0);
+    }
+
+    #[test]
+    fn object_xrefs_test() {
+        let (symstr, names) = strtab(&["memcpy", "helper", "buffer"]);
+        let mut symtab = sym64(0, 0, 0, 0, 0);
+        symtab.extend(sym64(
+            names[0],
+            abi::STT_FUNC | abi::STB_GLOBAL << 4,
+            0,
+            0,
+            0,
+        ));
+        symtab.extend(sym64(names[1], abi::STT_FUNC, 1, 0x14, 4));
+        symtab.extend(sym64(names[2], abi::STT_OBJECT, 2, 0x8, 0x10));
+
+        let mut relas = rela64(0x0, 1, abi::R_AARCH64_CALL26, 0);
+        relas.extend(rela64(0x4, 3, abi::R_AARCH64_ADR_PREL_PG_HI21, 0));
+        relas.extend(rela64(0x8, 3, abi::R_AARCH64_ADD_ABS_LO12_NC, 4));
+        relas.extend(rela64(0xc, 2, abi::R_AARCH64_CALL26, 0));
+
+        // 1: .text, 2: .data, 3: .symtab, 4: .strtab, 5: .rela.text
+        let file_data = ElfBuilder::new(false)
+            .e_type(abi::ET_REL)
+            // bl memcpy; adrp x0, buffer; add x0, x0, :lo12:buffer+4; bl helper; bl 0x18;
+            // ret; ret
+            .section(ElfSection::text(
+                0,
+                &[
+                    0x9400_0000,
+                    0x9000_0000,
+                    0x9100_0000,
+                    0x9400_0000,
+                    0x9400_0002,
+                    0xd65f_03c0,
+                    0xd65f_03c0,
+                ],
+            ))
+            .section(ElfSection::new(
+                ".data",
+                abi::SHT_PROGBITS,
+                (abi::SHF_ALLOC | abi::SHF_WRITE) as u64,
+                0,
+                vec![0; 0x20],
+            ))
+            .section(ElfSection::new(".symtab", abi::SHT_SYMTAB, 0, 0, symtab).link(4, 3, 24))
+            .section(ElfSection::new(".strtab", abi::SHT_STRTAB, 0, 0, symstr))
+            .section(ElfSection::new(".rela.text", abi::SHT_RELA, 0, 0, relas).link(3, 1, 24))
+            .build();
+        let program = Program::from_elf(&file_data).unwrap();
+        let xrefs = program.xrefs(&AArch64Parser::new());
+        let buffer = program.symbols().by_name("buffer").unwrap().address;
+
+        // Relocated placeholders resolve to their symbols; memcpy is not defined here.
+        let found: Vec<_> = xrefs
+            .iter()
+            .map(|xref| (xref.from, xref.to, xref.kind))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (0x8, buffer + 4, XrefKind::Address),
+                (0xc, 0x14, XrefKind::Call),
+                (0x10, 0x18, XrefKind::Call),
+            ]
+        );
+    }
+}